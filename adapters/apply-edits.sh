@@ -5,13 +5,25 @@
 #
 # Usage: ./apply-edits.sh apply --file <json_file> --workdir <path>
 #    or: echo "<json>" | ./apply-edits.sh apply --stdin --workdir <path>
+#    or: ./apply-edits.sh apply --file <json_file> --workdir <path> --verify "<command>" [--verify-timeout <secs>]
 #    or: ./apply-edits.sh read --file <file_path> --workdir <path>
+#    or: ./apply-edits.sh read --glob "src/**/*.ts" --max-files <n> --workdir <path>
+#    or: ./apply-edits.sh read --file <file_path> --truncate head|head_tail|relevant [--keywords a,b] --workdir <path>
+#    or: ./apply-edits.sh read --files <a,b> --format prompt|markdown|xml|numbered-plain [--no-line-numbers] --workdir <path>
+#    or: ./apply-edits.sh detect --workdir <path>
+#    or: ./apply-edits.sh tree --workdir <path> [--max-depth <n>] [--format text|json|prompt]
+#    or: ./apply-edits.sh search --query "<text>" [--regex] [--glob "*.ts"] [--context <n>] --workdir <path>
+#    or: ./apply-edits.sh rank --directive-file <path> [--limit <n>] [--format json|paths] --workdir <path>
 #
 # The Rust tool provides:
 # - Multi-line search/replace (fixes AWK line-by-line limitation)
 # - Structured JSON error output with closest matches
 # - Human-readable progress output
 # - File reading with line numbers
+# - Project detection (package manager, languages, validation commands) as JSON
+# - Repository map with line counts, sizes and languages (tree)
+# - Repository search with numbered context around each match (search)
+# - Directive relevance ranking of files with score breakdowns (rank)
 #
 # For legacy compatibility, this wrapper also handles the old argument format:
 #   ./apply-edits.sh --file <json_file> --workdir <path>
@@ -52,7 +64,7 @@ convert_legacy_args() {
     # Check if first arg is a subcommand
     if [[ ${#args[@]} -gt 0 ]]; then
         case "${args[0]}" in
-            apply|read|help|--help|-h)
+            apply|read|detect|tree|search|rank|help|--help|-h)
                 has_subcommand=true
                 ;;
         esac
@@ -115,7 +127,7 @@ main() {
     # Check if using legacy argument format
     if [[ $# -gt 0 ]]; then
         case "$1" in
-            apply|read|help|--help|-h|--version|-V)
+            apply|read|detect|tree|search|rank|help|--help|-h|--version|-V)
                 # New format - pass through directly
                 exec "$RUST_BINARY" "$@"
                 ;;
@@ -1,6 +1,10 @@
 #!/bin/bash
 # Adapter: Read file contents from the target repository
 #
+# Legacy: superseded by `apply-edits.sh read --glob <pattern> --max-files <n>`, which
+# also honors .gitignore and skips binary files. Kept as the fallback when the Rust
+# tool can't be built.
+#
 # Usage: ./git-read-files.sh --workdir <path> [--files "file1.js,file2.js"] [--pattern "*.tsx"]
 #
 # Arguments:
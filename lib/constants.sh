@@ -76,6 +76,10 @@ export ENGINEER_SPACING_MS=3000
 # Maximum lines to read from a single file when providing context
 export MAX_FILE_LINES=500
 
+# Layout of file contents in prompts: markdown, xml or numbered-plain
+# Empty picks one per persona from its provider (see get_read_format_for_persona)
+export READ_FORMAT="${READ_FORMAT:-}"
+
 # =============================================================================
 # CHUNKED EDIT CONFIGURATION
 # =============================================================================
@@ -51,6 +51,7 @@ refresh_working_repo() {
 # Reads existing file contents for files that need to be modified.
 read_existing_files() {
     local file_paths="$1"
+    local persona_name="${2:-engineer}"
 
     if [[ -z "$TARGET_REPO_PATH" ]]; then
         echo ""
@@ -65,7 +66,19 @@ read_existing_files() {
         return
     fi
     
-    # Use Rust tool to read files with line numbers
+    # NOTE(jimmylee): Ask the Rust tool for the layout that suits the persona's model
+    local read_format files_text=""
+    read_format=$(get_read_format_for_persona "$persona_name")
+    files_text=$("${ADAPTERS_DIR}/apply-edits.sh" read --files "$file_paths" --workdir "$TARGET_REPO_PATH" --max-lines "${MAX_FILE_LINES:-500}" --format "$read_format" 2>/dev/null) || files_text=""
+    if [[ -n "$files_text" ]]; then
+        printf '%s\n\n%s\n%s\n\n%s\n' "## Existing File Contents" \
+            "Below are the current contents of files you may need to modify." \
+            "Line numbers are provided to help you reference specific locations." \
+            "$files_text"
+        return
+    fi
+
+    # Fall back to JSON (or the legacy reader) formatted here
     local files_output
     files_output=$("${ADAPTERS_DIR}/apply-edits.sh" read --files "$file_paths" --workdir "$TARGET_REPO_PATH" --max-lines "${MAX_FILE_LINES:-500}" --format json 2>/dev/null) || {
         # Fallback to legacy git-read-files if Rust tool not available
@@ -109,8 +122,20 @@ read_existing_files() {
                 if [[ "$truncated" == "true" ]]; then
                     truncated_note=" (truncated)"
                 fi
+
+                # NOTE(jimmylee): State the file's indentation and line endings so edits match them
+                local conventions_note=""
+                conventions_note=$(echo "$files_output" | jq -r ".files[$i].metadata // {} | [
+                    (.indent | if type == \"object\" then \"indent: \\(.spaces) spaces\"
+                        elif . == \"tabs\" then \"indent: tabs\"
+                        elif . == \"mixed\" then \"indent: mixed tabs and spaces\"
+                        else empty end),
+                    (.line_ending | if . == \"crlf\" then \"CRLF line endings\"
+                        elif . == \"mixed\" then \"mixed line endings\"
+                        else empty end)
+                ] | map(\", \" + .) | join(\"\")" 2>/dev/null) || conventions_note=""
                 
-                formatted+="### ${path} (${lines} lines${truncated_note})\n\n"
+                formatted+="### ${path} (${lines} lines${conventions_note}${truncated_note})\n\n"
                 formatted+="\`\`\`${ext}\n${content}\n\`\`\`\n\n"
             else
                 formatted+="### ${path}\n\n"
@@ -344,10 +369,25 @@ Output a JSON block with targeted edits (NOT full file content):
 - \`replace_all\`: Replace ALL occurrences of 'search' with 'replace'
 - \`insert_after\`: Insert 'content' after line containing 'anchor'
 - \`insert_before\`: Insert 'content' before line containing 'anchor'
-- \`create\`: Create new file with 'content'
+- \`create\`: Create new file with 'content' (optional 'mode': \"create_new\" fails if the file exists, \"if_missing\" leaves an existing file alone, \"overwrite\" is the default; optional 'executable': true or octal 'permissions' like \"755\")
 - \`delete_file\`: Delete the file
+- \`rename\`: Move the file at 'from' to 'to' (set 'overwrite' to replace an existing file); later edits use the new path
+- \`copy\`: Copy the file at 'from' to 'to'
 - \`append\`: Append 'content' to end of file
 - \`delete_match\`: Delete line(s) containing 'search'
+- \`replace_lines\`: Replace lines 'start_line' to 'end_line' with 'content' (optional 'expected' holds the current text of those lines)
+- \`replace_between\`: Replace the lines between the 'start' and 'end' marker lines with 'content' (set 'inclusive' to also replace the markers)
+- \`delete_between\`: Delete the lines between the 'start' and 'end' marker lines
+- \`replace_block\`: Replace the whole block opened by the 'header' line (e.g. a function signature) through its closing brace or end of indentation with 'content'
+- \`set_key\`: Set 'key_path' (dotted like \"scripts.build\" or a JSON pointer like \"/scripts/build\") to the JSON 'value' in a JSON, TOML or YAML file; comments and formatting are kept
+- \`delete_key\`: Remove 'key_path' from a JSON, TOML or YAML file
+- \`append_to_array\`: Append the JSON 'value' to the array at 'key_path' (created if missing)
+- \`ensure_line\`: Add 'line' only if the file has no such line yet (optional 'after' anchor, or 'section' header like \"# Logs\" to add it at the end of that section); prefer this for .gitignore and similar list files
+- \`ensure_import\`: Add the 'import' statement (JS/TS import or require, Python import, Rust use, Go import path) to the import block unless it is already imported; names are merged into an existing import of the same module
+- \`replace_all\`, \`delete_match\`, \`append\`, \`prepend\` and \`ensure_line\` also accept 'paths' globs (e.g. [\"src/**/*.tsx\"]) instead of 'path' to apply the same edit to every matching file (gitignored files are skipped); use this for license headers or replacing a deprecated import everywhere
+- \`rename_symbol\`: Rename identifier 'from' to 'to' in every file matching the 'paths' globs (e.g. [\"src/**/*.ts\"]), whole words only; set 'skip_strings_and_comments' to leave text in strings and comments alone
+
+Line numbers in \`insert_at_line\`, \`delete_lines\` and \`replace_lines\` always refer to the file contents shown above, even after earlier edits in the same response.
 
 ### ⛔ ABSOLUTE RULE - READ THIS FIRST:
 Your search/anchor strings MUST come from the \"Current File Contents\" section above.
@@ -513,18 +553,18 @@ apply_code_changes() {
 
     # Detect format and use appropriate applier
     local has_edits
-    has_edits=$(echo "$changes_json" | jq 'has("edits")' 2>/dev/null)
+    has_edits=$(echo "$changes_json" | jq 'has("edits") or has("groups")' 2>/dev/null)
 
     # Validate edit structure before calling Rust tool
     if [[ "$has_edits" == "true" ]]; then
         local validation_errors
         validation_errors=$(echo "$changes_json" | jq -r '
-            [.edits | to_entries[] |
+            [[(.edits // [])[], (.groups // [])[].edits[]] | to_entries[] |
                 .key as $idx |
                 .value |
                 if .type == null then
                     "Edit \($idx): missing required field \"type\""
-                elif .path == null then
+                elif .path == null and .paths == null and .from == null then
                     "Edit \($idx): missing required field \"path\""
                 elif (.type == "insert_after" or .type == "insert_before") and .anchor == null then
                     "Edit \($idx) (\(.type)): missing required field \"anchor\""
@@ -700,6 +740,8 @@ Output ONLY the corrected edits for the failed operations:
 - \`insert_after\`/\`insert_before\`: Requires \"anchor\" and \"content\" (NOT \"replace\"!)
 - \`create\`/\`append\`: Requires \"content\"
 - \`delete_match\`: Requires \"search\"
+- \`ensure_line\`: Requires \"line\"; \`ensure_import\`: Requires \"import\"
+- \`rename_symbol\`: Requires \"from\", \"to\", \"paths\" (no \"path\")
 
 Output ONLY the JSON block for the failed edits - do not re-output edits that already succeeded."
 
@@ -890,12 +932,12 @@ validate_engineer_output() {
     fi
 
     local has_edits has_files
-    has_edits=$(echo "$json_content" | jq 'has("edits")' 2>/dev/null)
+    has_edits=$(echo "$json_content" | jq 'has("edits") or has("groups")' 2>/dev/null)
     has_files=$(echo "$json_content" | jq 'has("files")' 2>/dev/null)
 
     if [[ "$has_edits" == "true" ]]; then
         local edits_count
-        edits_count=$(echo "$json_content" | jq -r '.edits | length // 0' 2>/dev/null)
+        edits_count=$(echo "$json_content" | jq -r '[(.edits // [])[], (.groups // [])[].edits[]] | length' 2>/dev/null)
         if [[ "$edits_count" == "0" || "$edits_count" == "null" ]]; then
             log_error "VALIDATE" "No edits in Engineer's output"
             return 1
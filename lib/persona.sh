@@ -90,6 +90,28 @@ get_persona_transition_phrase() {
     esac
 }
 
+# NOTE(jimmylee)
+# Returns the `apply-edits read --format` layout that suits a persona's model.
+# Anthropic models parse <file> tags well; others get fenced markdown.
+# READ_FORMAT overrides the choice for every persona.
+get_read_format_for_persona() {
+    local persona_name="${1:-engineer}"
+
+    if [[ -n "${READ_FORMAT:-}" ]]; then
+        echo "$READ_FORMAT"
+        return
+    fi
+
+    case "$(get_provider_for_persona "$persona_name")" in
+        anthropic)
+            echo "xml"
+            ;;
+        *)
+            echo "markdown"
+            ;;
+    esac
+}
+
 # NOTE(jimmylee)
 # Core function that invokes a persona with a prompt and saves to memory.
 # Returns the raw response content for backwards compatibility.
@@ -69,20 +69,28 @@ get_repo_context() {
     # =========================================================================
     context+="## Complete Repository Structure\n\n"
     context+="\`\`\`\n"
-    context+=$(cd "$TARGET_REPO_PATH" && find . -type f \
-        -not -path '*/\.*' \
-        -not -path '*/node_modules/*' \
-        -not -path '*/vendor/*' \
-        -not -path '*/__pycache__/*' \
-        -not -path '*/dist/*' \
-        -not -path '*/build/*' \
-        -not -path '*/.next/*' \
-        -not -path '*/coverage/*' \
-        -not -name '*.lock' \
-        -not -name 'package-lock.json' \
-        -not -name 'yarn.lock' \
-        -not -name 'pnpm-lock.yaml' \
-        2>/dev/null | sort)
+    # NOTE(jimmylee): The tree subcommand honors .gitignore, collapses huge directories
+    # and annotates line counts and languages; the find pipeline is the fallback
+    local tree_output=""
+    tree_output=$("${ADAPTERS_DIR}/apply-edits.sh" tree --workdir "$TARGET_REPO_PATH" --format text 2>/dev/null) || tree_output=""
+    if [[ -n "$tree_output" ]]; then
+        context+="$tree_output"
+    else
+        context+=$(cd "$TARGET_REPO_PATH" && find . -type f \
+            -not -path '*/\.*' \
+            -not -path '*/node_modules/*' \
+            -not -path '*/vendor/*' \
+            -not -path '*/__pycache__/*' \
+            -not -path '*/dist/*' \
+            -not -path '*/build/*' \
+            -not -path '*/.next/*' \
+            -not -path '*/coverage/*' \
+            -not -name '*.lock' \
+            -not -name 'package-lock.json' \
+            -not -name 'yarn.lock' \
+            -not -name 'pnpm-lock.yaml' \
+            2>/dev/null | sort)
+    fi
     context+="\n\`\`\`\n\n"
     
     # =========================================================================
@@ -227,93 +235,54 @@ get_repo_context() {
 
 # NOTE(angeldev)
 # Prioritizes files for context based on relevance to the directive.
-# Uses scoring to rank files by importance for the given task.
+# Uses `apply-edits rank` to score files for the given task.
 #
-# Scoring algorithm:
-# - +10 if filename contains directive keywords
-# - +5 if path contains directive keywords
-# - +3 for recently modified files
-# - +2 for files imported by high-scoring files
-# - +1 for common entry points and configs
+# Scoring (summed, see tools/apply-edits/src/rank.rs):
+# - BM25 relevance of the file contents to the directive terms
+# - Directive terms in the filename (strong) or directory names (weak)
+# - Common entry points and configs
+# - How often the file changed in recent git history
 #
 # Returns a newline-separated list of prioritized file paths.
 prioritize_files_for_context() {
     local directive="$1"
     local all_files="$2"
-    local max_chars="${MAX_CODEBASE_CHARS:-640000}"
 
-    # Extract keywords from directive (simple word extraction)
-    local keywords
-    keywords=$(echo "$directive" | tr '[:upper:]' '[:lower:]' | tr -cs '[:alnum:]' '\n' | grep -E '^[a-z]{3,}$' | sort -u | head -20)
+    # NOTE(jimmylee): Scoring is done by `apply-edits rank` (BM25 over file contents,
+    # path matches, entry point/config heuristics, git churn); on failure the list
+    # is returned in its original order
+    local directive_file
+    directive_file=$(make_temp_file "directive")
+    printf '%s\n' "$directive" > "$directive_file"
+
+    local ranked=""
+    ranked=$("${ADAPTERS_DIR}/apply-edits.sh" rank --workdir "$TARGET_REPO_PATH" \
+        --directive-file "$directive_file" --limit 0 --format paths 2>/dev/null) || ranked=""
+    rm -f "$directive_file"
 
-    if [[ -z "$keywords" ]]; then
-        # No useful keywords, return original file list
+    if [[ -z "$ranked" ]]; then
         echo "$all_files"
         return
     fi
 
-    # Create temp file for scoring
-    local score_file
-    score_file=$(make_temp_file "file_scores")
-
-    # Score each file
-    while IFS= read -r file; do
-        [[ -z "$file" ]] && continue
-
-        local score=0
-        local filename
-        filename=$(basename "$file")
-        local filepath_lower
-        filepath_lower=$(echo "$file" | tr '[:upper:]' '[:lower:]')
-        local filename_lower
-        filename_lower=$(echo "$filename" | tr '[:upper:]' '[:lower:]')
-
-        # +10 for filename containing directive keywords
-        for kw in $keywords; do
-            if [[ "$filename_lower" == *"$kw"* ]]; then
-                ((score += 10))
-            fi
-        done
-
-        # +5 for path containing directive keywords
-        for kw in $keywords; do
-            if [[ "$filepath_lower" == *"$kw"* && "$filename_lower" != *"$kw"* ]]; then
-                ((score += 5))
-            fi
-        done
-
-        # +3 for common entry points
-        case "$filename" in
-            index.ts|index.tsx|index.js|index.jsx|main.ts|main.tsx|main.js|main.py|main.go|main.rs)
-                ((score += 3))
-                ;;
-            app.ts|app.tsx|app.js|app.jsx|App.tsx|App.vue|App.svelte)
-                ((score += 3))
-                ;;
-            routes.ts|router.ts|routes.js|router.js)
-                ((score += 2))
-                ;;
-        esac
-
-        # +2 for config files (always useful)
-        case "$filename" in
-            package.json|tsconfig.json|next.config.*|vite.config.*|tailwind.config.*)
-                ((score += 2))
-                ;;
-        esac
-
-        # +1 for core directories
-        if [[ "$file" == *"/src/lib/"* || "$file" == *"/src/utils/"* || "$file" == *"/src/core/"* || "$file" == *"/src/api/"* ]]; then
-            ((score += 1))
-        fi
-
-        echo "$score $file" >> "$score_file"
-    done <<< "$all_files"
-
-    # Sort by score descending and return files
-    sort -t' ' -k1 -nr "$score_file" | awk '{print $2}'
-
-    rm -f "$score_file"
+    # Files from the list in rank order, then any the ranker skipped (binary,
+    # generated, ignored) in their original order. Paths may start with "./"
+    awk '
+        FNR == NR {
+            if ($0 == "") next
+            key = $0
+            sub(/^\.\//, "", key)
+            if (!(key in original)) order[++count] = key
+            original[key] = $0
+            next
+        }
+        ($0 in original) && !($0 in printed) { print original[$0]; printed[$0] = 1 }
+        END {
+            for (i = 1; i <= count; i++) {
+                if (!(order[i] in printed)) print original[order[i]]
+            }
+        }
+    ' <(printf '%s\n' "$all_files") <(printf '%s\n' "$ranked")
 }
 
 # NOTE(angeldev)
@@ -120,9 +120,7 @@ fn try_indentation_correction(
         if window_matches {
             // Extract the actual content with correct indentation
             let actual_content: String = content_lines[start_idx..end_idx]
-                .iter()
-                .map(|s| *s)
-                .collect::<Vec<_>>()
+                .to_vec()
                 .join("\n");
 
             let search_first_indent = search.lines().next()
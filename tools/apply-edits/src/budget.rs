@@ -0,0 +1,225 @@
+// NOTE(jimmylee)
+// Token-budgeted context packing for `read --budget-tokens`.
+// Files are packed in the order they were requested (highest priority first):
+// each file is shown in full if it still fits, otherwise as the best head/tail or
+// outline view of its share of what's left, and is omitted and listed when
+// nothing useful fits. Tokens are estimated as chars / CHARS_PER_TOKEN, the same
+// heuristic lib/providers.sh uses.
+
+use crate::read::{add_line_numbers_in_windows, outline_view, read_text, MultiFileReadResult, View};
+use serde::Serialize;
+use std::path::Path;
+
+// NOTE(jimmylee)
+// Characters per token for estimates (CHARS_PER_TOKEN in lib/constants.sh).
+pub const CHARS_PER_TOKEN: usize = 4;
+
+// NOTE(jimmylee)
+// Fewest lines a head/tail view may keep before the file is omitted instead.
+const MIN_VIEW_LINES: usize = 10;
+
+// NOTE(jimmylee)
+// Summary of a packing run.
+#[derive(Debug, Serialize)]
+pub struct BudgetReport {
+    pub budget_tokens: usize,
+    pub used_tokens: usize,
+    pub omitted: Vec<String>,
+}
+
+// NOTE(jimmylee)
+// Estimated token count of text.
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN)
+}
+
+// NOTE(jimmylee)
+// Chooses a view for every readable file so the shown content fits budget_tokens.
+// Sets each file's view and token estimate and records the report on results.
+pub fn pack(workdir: &Path, results: &mut MultiFileReadResult, budget_tokens: usize) {
+    let mut remaining = budget_tokens;
+    let mut omitted = Vec::new();
+
+    let mut left = results.files.iter().filter(|f| f.content_with_line_numbers.is_some()).count();
+
+    for file in results.files.iter_mut() {
+        let Some(text) = &file.content_with_line_numbers else { continue };
+        left -= 1;
+        let tokens = estimate_tokens(text);
+        if tokens <= remaining {
+            remaining -= tokens;
+            file.view = Some(file.view.unwrap_or(View::Full));
+            file.tokens = Some(tokens);
+            continue;
+        }
+
+        // Doesn't fit in full: take the best reduced view of an even share of what's
+        // left, so the files after it still get one; an unused share carries over
+        file.truncated = Some(true);
+        file.content = None;
+        let share = remaining / (left + 1);
+
+        let head_tail = head_tail(workdir, &file.path, file.start_line, file.end_line, share);
+        // Outlines cover the whole file, so ranged reads don't get one
+        let outline = read_text(&workdir.join(&file.path))
+            .ok()
+            .filter(|_| file.start_line.is_none())
+            .and_then(|content| outline_view(&file.path, &content, content.lines().count()))
+            .filter(|view| view.content_with_line_numbers.as_deref().is_some_and(|t| estimate_tokens(t) <= share));
+
+        // A head/tail view that keeps most of the file beats an outline
+        let (view, text, items) = match (head_tail, outline) {
+            (Some((text, kept)), _) if kept >= 0.5 => (View::HeadTail, Some(text), None),
+            (_, Some(outline)) => (View::Outline, outline.content_with_line_numbers, outline.outline),
+            (Some((text, _)), None) => (View::HeadTail, Some(text), None),
+            (None, None) => (View::Omitted, None, None),
+        };
+
+        match text {
+            Some(text) => {
+                let tokens = estimate_tokens(&text);
+                remaining -= tokens;
+                file.view = Some(view);
+                file.tokens = Some(tokens);
+                file.content_with_line_numbers = Some(text);
+                file.outline = items;
+            }
+            None => {
+                file.view = Some(View::Omitted);
+                file.tokens = Some(0);
+                file.content_with_line_numbers = None;
+                omitted.push(file.path.clone());
+            }
+        }
+    }
+
+    results.budget = Some(BudgetReport {
+        budget_tokens,
+        used_tokens: budget_tokens - remaining,
+        omitted,
+    });
+}
+
+// Largest head/tail view of lines first..=last that fits allowance tokens, with the
+// fraction of those lines it keeps. The head gets two thirds of the kept lines,
+// the tail the rest.
+fn head_tail(
+    workdir: &Path,
+    path: &str,
+    first: Option<usize>,
+    last: Option<usize>,
+    allowance: usize,
+) -> Option<(String, f64)> {
+    let content = read_text(&workdir.join(path)).ok()?;
+    let total = content.lines().count();
+    let first = first.unwrap_or(1);
+    let last = last.unwrap_or(total).min(total);
+    let span = (last + 1).checked_sub(first)?;
+
+    let full = add_line_numbers_in_windows(&content, &[(first, last)]);
+    let per_line = estimate_tokens(&full) as f64 / span.max(1) as f64;
+    let mut keep = ((allowance as f64 / per_line) as usize).min(span.saturating_sub(1));
+
+    while keep >= MIN_VIEW_LINES {
+        let tail = keep / 3;
+        let head = keep - tail;
+        let text = add_line_numbers_in_windows(&content, &[(first, first + head - 1), (last + 1 - tail, last)]);
+        if estimate_tokens(&text) <= allowance {
+            return Some((text, keep as f64 / span as f64));
+        }
+        keep = keep * 9 / 10;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use crate::read::{read_files_with_line_numbers, read_file_range, LineRange};
+    use tempfile::tempdir;
+
+    fn numbered(count: usize) -> String {
+        (1..=count).map(|i| format!("line {}\n", i)).collect()
+    }
+
+    #[test]
+    fn test_full_then_head_tail_then_omitted() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("small.ts"), numbered(20)).unwrap();
+        fs::write(dir.path().join("big.ts"), numbered(2000)).unwrap();
+        fs::write(dir.path().join("also_big.ts"), numbered(2000)).unwrap();
+        fs::write(dir.path().join("tiny.ts"), numbered(2)).unwrap();
+
+        let paths: Vec<String> = ["small.ts", "big.ts", "also_big.ts", "tiny.ts"].map(String::from).to_vec();
+        let mut results = read_files_with_line_numbers(dir.path(), &paths, Some(usize::MAX));
+        pack(dir.path(), &mut results, 1000);
+
+        let views: Vec<Option<View>> = results.files.iter().map(|f| f.view).collect();
+        assert_eq!(views, vec![Some(View::Full), Some(View::HeadTail), Some(View::HeadTail), Some(View::Full)]);
+
+        let big = results.files[1].content_with_line_numbers.as_ref().unwrap();
+        assert!(big.starts_with("   1 | line 1\n"));
+        assert!(big.ends_with("2000 | line 2000"));
+        assert!(big.contains(" omitted)"));
+
+        let report = results.budget.unwrap();
+        assert!(report.used_tokens <= 1000 && report.omitted.is_empty());
+        let total: usize = results.files.iter().filter_map(|f| f.tokens).sum();
+        assert_eq!(total, report.used_tokens);
+
+        let mut results = read_files_with_line_numbers(dir.path(), &paths, Some(usize::MAX));
+        pack(dir.path(), &mut results, 150);
+        let views: Vec<Option<View>> = results.files.iter().map(|f| f.view).collect();
+        assert_eq!(views, vec![Some(View::Full), Some(View::Omitted), Some(View::Omitted), Some(View::Full)]);
+        assert_eq!(results.budget.unwrap().omitted, vec!["big.ts", "also_big.ts"]);
+    }
+
+    #[test]
+    fn test_oversized_files_share_the_rest() {
+        let dir = tempdir().unwrap();
+        let body: String = (0..40).map(|i| format!("fn f{}() {{\n{}}}\n\n", i, "    step();\n".repeat(30))).collect();
+        fs::write(dir.path().join("a.rs"), &body).unwrap();
+        fs::write(dir.path().join("b.rs"), &body).unwrap();
+
+        let paths = vec!["a.rs".to_string(), "b.rs".to_string()];
+        let mut results = read_files_with_line_numbers(dir.path(), &paths, Some(usize::MAX));
+        pack(dir.path(), &mut results, 800);
+
+        let views: Vec<Option<View>> = results.files.iter().map(|f| f.view).collect();
+        assert_eq!(views, vec![Some(View::Outline), Some(View::Outline)]);
+        assert!(results.files.iter().all(|f| f.tokens.unwrap() <= 400));
+        assert!(results.budget.unwrap().omitted.is_empty());
+    }
+
+    #[test]
+    fn test_outline_when_head_tail_would_be_thin() {
+        let dir = tempdir().unwrap();
+        let body: String = (0..40).map(|i| format!("fn f{}() {{\n{}}}\n\n", i, "    step();\n".repeat(30))).collect();
+        fs::write(dir.path().join("lib.rs"), body).unwrap();
+
+        let mut results = read_files_with_line_numbers(dir.path(), &["lib.rs".to_string()], Some(usize::MAX));
+        pack(dir.path(), &mut results, 400);
+        let file = &results.files[0];
+        assert_eq!((file.view, file.outline.as_ref().map(|o| o.len())), (Some(View::Outline), Some(40)));
+        assert!(file.tokens.unwrap() <= 400);
+    }
+
+    #[test]
+    fn test_head_tail_stays_in_range() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("a.rs"), numbered(500)).unwrap();
+
+        let range = LineRange::parse("100-300").ok();
+        let mut results = MultiFileReadResult {
+            files: vec![read_file_range(dir.path(), "a.rs", range, None)],
+            glob: None,
+            budget: None,
+        };
+        pack(dir.path(), &mut results, 200);
+
+        let text = results.files[0].content_with_line_numbers.as_ref().unwrap();
+        assert!(text.starts_with("... | ... (lines 1-99 omitted)\n100 | line 100\n"));
+        assert!(text.ends_with("300 | line 300\n... | ... (lines 301-500 omitted)"));
+    }
+}
@@ -0,0 +1,290 @@
+// NOTE(angeldev)
+// Conflict analysis for a request's edits.
+// Locates what each edit targets in the original file contents and reports pairs
+// whose targets overlap: two edits changing the same text or lines, an anchor or
+// insertion point inside lines another edit changes, or edits to a file another
+// edit deletes.
+// Such requests give order-dependent results, so they fail before anything is written.
+
+use crate::edits::between::locate_between;
+use crate::edits::block::locate_block;
+use crate::edits::{read_file, Edit};
+use crate::matcher::{find_line_with_anchor, get_affected_lines};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+// NOTE(angeldev)
+// Two edits that can't both be applied predictably (indexes into the edit list).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub first: usize,
+    pub second: usize,
+    pub path: String,
+    pub reason: String,
+}
+
+// What an edit targets in the original content (1-indexed lines)
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Target {
+    // Text the edit rewrites: byte range start..end, on lines first-last
+    Text(usize, usize, usize, usize),
+    // Lines the edit rewrites or removes (inclusive)
+    Lines(usize, usize),
+    // Line the edit positions itself on without changing it
+    Anchor(usize),
+    // Insertion before this line
+    Point(usize),
+    // The whole file is removed
+    Deleted,
+}
+
+// NOTE(angeldev)
+// Finds conflicting pairs among edits, in the order of the later edit.
+// Edits whose target can't be located in the original content (text added by an
+// earlier edit, files created by the request) are not analyzed.
+pub fn find_conflicts(workdir: &Path, edits: &[Edit]) -> Vec<Conflict> {
+    let mut contents: HashMap<&str, Option<String>> = HashMap::new();
+    let mut replaced: HashSet<&str> = HashSet::new();
+    let mut targets: Vec<(usize, &str, Vec<Target>)> = Vec::new();
+
+    for (index, edit) in edits.iter().enumerate() {
+        let path = edit.path();
+        if edit.glob_paths().is_some() || replaced.contains(path) {
+            continue;
+        }
+        // Later edits to a created, moved or copied path see different content
+        if matches!(edit, Edit::Create { .. } | Edit::Rename { .. } | Edit::Copy { .. }) {
+            replaced.insert(path);
+            replaced.extend(edit.destination());
+            continue;
+        }
+        let content = contents.entry(path).or_insert_with(|| read_file(workdir, path).ok());
+        let Some(content) = content.as_deref() else { continue };
+        let found = locate(edit, content);
+        if !found.is_empty() {
+            targets.push((index, path, found));
+        }
+    }
+
+    let mut conflicts = Vec::new();
+    for (later, (second, path, second_targets)) in targets.iter().enumerate() {
+        for (first, first_path, first_targets) in &targets[..later] {
+            if first_path != path {
+                continue;
+            }
+            let reason = first_targets
+                .iter()
+                .flat_map(|a| second_targets.iter().map(move |b| (a, b)))
+                .find_map(|(a, b)| overlap(*a, *b));
+            if let Some(reason) = reason {
+                conflicts.push(Conflict {
+                    first: *first,
+                    second: *second,
+                    path: path.to_string(),
+                    reason,
+                });
+                break;
+            }
+        }
+    }
+    conflicts
+}
+
+// Locates an edit's targets in the original content
+fn locate(edit: &Edit, content: &str) -> Vec<Target> {
+    let path = edit.path();
+    match edit {
+        Edit::Replace { search, .. } if !search.is_empty() => content
+            .find(search.as_str())
+            .map(|pos| text_at(content, pos, search.len()))
+            .into_iter()
+            .collect(),
+        Edit::ReplaceAll { search, .. } if !search.is_empty() => content
+            .match_indices(search.as_str())
+            .map(|(pos, _)| text_at(content, pos, search.len()))
+            .collect(),
+        Edit::InsertAfter { anchor, .. } | Edit::InsertBefore { anchor, .. } if !anchor.is_empty() => {
+            find_line_with_anchor(content, anchor).map(Target::Anchor).into_iter().collect()
+        }
+        Edit::InsertAtLine { line, .. } => vec![Target::Point(*line)],
+        Edit::DeleteLines { start_line, end_line, .. } | Edit::ReplaceLines { start_line, end_line, .. } => {
+            vec![Target::Lines(*start_line, *end_line)]
+        }
+        Edit::ReplaceBetween { start, end, inclusive, .. } | Edit::DeleteBetween { start, end, inclusive, .. } => {
+            match locate_between(content, path, start, end, *inclusive) {
+                Ok(region) if region.is_empty() => vec![Target::Point(region.first_line)],
+                Ok(region) => vec![Target::Lines(region.first_line, region.last_line)],
+                Err(_) => Vec::new(),
+            }
+        }
+        Edit::ReplaceBlock { header, .. } => match locate_block(content, path, header) {
+            Ok(region) => vec![Target::Lines(region.first_line, region.last_line)],
+            Err(_) => Vec::new(),
+        },
+        Edit::DeleteMatch { search, .. } if !search.is_empty() => content
+            .lines()
+            .enumerate()
+            .filter(|(_, l)| l.contains(search.as_str()))
+            .map(|(i, _)| Target::Lines(i + 1, i + 1))
+            .collect(),
+        Edit::DeleteFile { .. } => vec![Target::Deleted],
+        _ => Vec::new(),
+    }
+}
+
+fn text_at(content: &str, pos: usize, len: usize) -> Target {
+    let (start, end) = get_affected_lines(content, pos, len);
+    Target::Text(pos, pos + len, start, end.max(start))
+}
+
+// Describes why target b of the later edit collides with target a of the earlier
+// one, from the later edit's point of view.
+// Two text targets collide only if their bytes overlap; against anything else a
+// text target stands for the whole lines it is on.
+fn overlap(a: Target, b: Target) -> Option<String> {
+    use Target::*;
+
+    match (a, b) {
+        (Text(s1, e1, l1, _), Text(s2, e2, l2, _)) => {
+            (s1 < e2 && s2 < e1).then(|| format!("both change the same text on line {}", l1.max(l2)))
+        }
+        (Text(_, _, first, last), other) => overlap(Lines(first, last), other),
+        (other, Text(_, _, first, last)) => overlap(other, Lines(first, last)),
+        (Deleted, _) => Some("it changes a file the earlier edit deletes".to_string()),
+        (_, Deleted) => Some("it deletes a file the earlier edit changes".to_string()),
+        (Lines(s1, e1), Lines(s2, e2)) if s1 <= e2 && s2 <= e1 => {
+            Some(format!("both change lines {}-{}", s1.max(s2), e1.min(e2)))
+        }
+        (Lines(s, e), Anchor(l)) if s <= l && l <= e => {
+            Some(format!("its anchor on line {} is changed by the earlier edit", l))
+        }
+        (Anchor(l), Lines(s, e)) if s <= l && l <= e => {
+            Some(format!("it changes line {}, the earlier edit's anchor", l))
+        }
+        (Lines(s, e), Point(p)) if s < p && p <= e => Some(format!(
+            "it inserts at line {}, inside lines {}-{} changed by the earlier edit",
+            p, s, e
+        )),
+        (Point(p), Lines(s, e)) if s < p && p <= e => Some(format!(
+            "it changes lines {}-{}, around the earlier edit's insertion at line {}",
+            s, e, p
+        )),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn conflicts(content: &str, json: &str) -> Vec<Conflict> {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("a.js"), content).unwrap();
+        let edits: Vec<Edit> = serde_json::from_str(json).unwrap();
+        find_conflicts(dir.path(), &edits)
+    }
+
+    #[test]
+    fn test_detects_overlaps() {
+        let content = "import a from 'a';\nfunction f() {\n  return 1;\n}\nf();\n";
+
+        let found = conflicts(
+            content,
+            r#"[
+                {"type": "replace", "path": "a.js", "search": "import a from 'a';\nfunction f() {", "replace": "x"},
+                {"type": "insert_after", "path": "a.js", "anchor": "import a", "content": "import b from 'b';"},
+                {"type": "delete_lines", "path": "a.js", "start_line": 2, "end_line": 4},
+                {"type": "insert_at_line", "path": "a.js", "line": 3, "content": "  log();"}
+            ]"#,
+        );
+        let pairs: Vec<(usize, usize)> = found.iter().map(|c| (c.first, c.second)).collect();
+        assert_eq!(pairs, vec![(0, 1), (0, 2), (2, 3)]);
+        assert_eq!(found[0].reason, "its anchor on line 1 is changed by the earlier edit");
+        assert_eq!(found[2].reason, "it inserts at line 3, inside lines 2-4 changed by the earlier edit");
+    }
+
+    #[test]
+    fn test_independent_edits_and_deleted_files() {
+        let content = "one\ntwo\nthree\nfour\n";
+        let found = conflicts(
+            content,
+            r#"[
+                {"type": "replace", "path": "a.js", "search": "one", "replace": "1"},
+                {"type": "insert_after", "path": "a.js", "anchor": "two", "content": "2.5"},
+                {"type": "replace_lines", "path": "a.js", "start_line": 4, "end_line": 4, "content": "4"},
+                {"type": "insert_at_line", "path": "a.js", "line": 4, "content": "3.5"},
+                {"type": "append", "path": "a.js", "content": "five\n"}
+            ]"#,
+        );
+        assert!(found.is_empty(), "{:?}", found);
+
+        let found = conflicts(
+            content,
+            r#"[
+                {"type": "delete_file", "path": "a.js"},
+                {"type": "replace", "path": "a.js", "search": "two", "replace": "2"}
+            ]"#,
+        );
+        assert_eq!(found[0].reason, "it changes a file the earlier edit deletes");
+    }
+
+    #[test]
+    fn test_replaces_on_one_line() {
+        let content = "let a = 1, b = 2;\n";
+        let found = conflicts(
+            content,
+            r#"[
+                {"type": "replace", "path": "a.js", "search": "a = 1", "replace": "a = 10"},
+                {"type": "replace", "path": "a.js", "search": "b = 2", "replace": "b = 20"}
+            ]"#,
+        );
+        assert!(found.is_empty(), "{:?}", found);
+
+        let found = conflicts(
+            content,
+            r#"[
+                {"type": "replace", "path": "a.js", "search": "a = 1, b", "replace": "x"},
+                {"type": "replace_all", "path": "a.js", "search": "b = 2", "replace": "b = 20"},
+                {"type": "delete_lines", "path": "a.js", "start_line": 1, "end_line": 1}
+            ]"#,
+        );
+        let pairs: Vec<(usize, usize)> = found.iter().map(|c| (c.first, c.second)).collect();
+        assert_eq!(pairs, vec![(0, 1), (0, 2)]);
+        assert_eq!(found[0].reason, "both change the same text on line 1");
+        assert_eq!(found[1].reason, "both change lines 1-1");
+    }
+
+    #[test]
+    fn test_adjacent_lines_do_not_conflict() {
+        let found = conflicts(
+            "function a() {}\nfunction b() {}\n",
+            r#"[
+                {"type": "insert_after", "path": "a.js", "anchor": "function a() {}", "content": "a();"},
+                {"type": "replace", "path": "a.js", "search": "function b() {}", "replace": "function b() { a(); }"}
+            ]"#,
+        );
+        assert!(found.is_empty(), "{:?}", found);
+
+        let content = "line1\nline2\nline3\nline4\nline5\nline6\n";
+        let found = conflicts(
+            content,
+            r#"[
+                {"type": "delete_lines", "path": "a.js", "start_line": 2, "end_line": 4},
+                {"type": "replace", "path": "a.js", "search": "line5", "replace": "five"},
+                {"type": "replace", "path": "a.js", "search": "line6\n", "replace": ""}
+            ]"#,
+        );
+        assert!(found.is_empty(), "{:?}", found);
+
+        let found = conflicts(
+            content,
+            r#"[
+                {"type": "delete_lines", "path": "a.js", "start_line": 2, "end_line": 4},
+                {"type": "replace", "path": "a.js", "search": "line4\nline5", "replace": "x"}
+            ]"#,
+        );
+        assert_eq!(found[0].reason, "both change lines 4-4");
+    }
+}
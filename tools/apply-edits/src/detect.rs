@@ -0,0 +1,504 @@
+// NOTE(jimmylee)
+// Project detection for the `detect` subcommand.
+// Port of adapters/detect-validation.sh: inspects manifests and task runners in the
+// workdir and reports the package manager, the language mix, and the commands to
+// run for test/build/lint/typecheck/format, each with the file that implied it.
+// Ecosystems are checked in a fixed order and the first command found for a kind
+// wins; Makefile and justfile targets only fill in what's still missing.
+
+use crate::language::Language;
+use crate::walk::relative_path;
+use ignore::WalkBuilder;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+// NOTE(jimmylee)
+// Most files counted for the language mix.
+const MAX_SCANNED_FILES: usize = 20_000;
+
+// NOTE(jimmylee)
+// A detected value and the workdir-relative file it was inferred from.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Detected {
+    pub value: String,
+    pub evidence: String,
+}
+
+// NOTE(jimmylee)
+// Validation commands by kind. null when nothing was detected.
+#[derive(Debug, Default, Serialize)]
+pub struct Commands {
+    pub test: Option<Detected>,
+    pub build: Option<Detected>,
+    pub lint: Option<Detected>,
+    pub typecheck: Option<Detected>,
+    pub format: Option<Detected>,
+}
+
+// NOTE(jimmylee)
+// Number of source files of one language.
+#[derive(Debug, Serialize)]
+pub struct LanguageCount {
+    pub language: String,
+    pub files: usize,
+}
+
+// NOTE(jimmylee)
+// Result of the detect subcommand.
+#[derive(Debug, Serialize)]
+pub struct Detection {
+    pub package_manager: Option<Detected>,
+    pub languages: Vec<LanguageCount>,
+    pub commands: Commands,
+}
+
+impl Commands {
+    fn set(slot: &mut Option<Detected>, command: impl Into<String>, evidence: &str) {
+        if slot.is_none() {
+            *slot = Some(Detected {
+                value: command.into(),
+                evidence: evidence.to_string(),
+            });
+        }
+    }
+}
+
+// Manifest reading relative to the workdir
+struct Project<'a> {
+    workdir: &'a Path,
+    package_manager: Option<Detected>,
+    commands: Commands,
+}
+
+impl Project<'_> {
+    fn exists(&self, rel_path: &str) -> bool {
+        self.workdir.join(rel_path).exists()
+    }
+
+    fn read(&self, rel_path: &str) -> Option<String> {
+        fs::read_to_string(self.workdir.join(rel_path)).ok()
+    }
+
+    fn first_existing<'p>(&self, names: &[&'p str]) -> Option<&'p str> {
+        names.iter().copied().find(|name| self.exists(name))
+    }
+
+    fn manager(&mut self, name: &str, evidence: &str) {
+        Commands::set(&mut self.package_manager, name, evidence);
+    }
+}
+
+// NOTE(jimmylee)
+// Inspects workdir and returns what it found.
+pub fn detect(workdir: &Path) -> Detection {
+    let mut project = Project {
+        workdir,
+        package_manager: None,
+        commands: Commands::default(),
+    };
+
+    detect_node(&mut project);
+    detect_deno(&mut project);
+    detect_rust(&mut project);
+    detect_go(&mut project);
+    detect_python(&mut project);
+    detect_ruby(&mut project);
+    detect_jvm(&mut project);
+    detect_make(&mut project);
+    detect_just(&mut project);
+
+    Detection {
+        package_manager: project.package_manager,
+        languages: language_mix(workdir),
+        commands: project.commands,
+    }
+}
+
+// package.json scripts, run with the package manager its lockfile belongs to
+fn detect_node(project: &mut Project) {
+    let Some(manifest) = project.read("package.json") else { return };
+    let json: serde_json::Value = serde_json::from_str(&manifest).unwrap_or_default();
+
+    let lockfiles = [
+        ("pnpm-lock.yaml", "pnpm"),
+        ("yarn.lock", "yarn"),
+        ("bun.lockb", "bun"),
+        ("bun.lock", "bun"),
+        ("package-lock.json", "npm"),
+    ];
+    let (manager, evidence) = lockfiles
+        .iter()
+        .find(|(lockfile, _)| project.exists(lockfile))
+        .map(|(lockfile, manager)| (*manager, *lockfile))
+        .or_else(|| {
+            // Corepack's "packageManager": "pnpm@9.1.0"
+            let declared = json.get("packageManager")?.as_str()?.split('@').next()?;
+            ["pnpm", "yarn", "bun", "npm"]
+                .into_iter()
+                .find(|m| *m == declared)
+                .map(|m| (m, "package.json"))
+        })
+        .unwrap_or(("npm", "package.json"));
+    project.manager(manager, evidence);
+
+    let scripts = json.get("scripts").and_then(|s| s.as_object());
+    let script = |names: &[&str]| {
+        names
+            .iter()
+            .find(|name| scripts.is_some_and(|s| s.contains_key(**name)))
+            .map(|name| match *name {
+                "test" => format!("{} test", manager),
+                _ => format!("{} run {}", manager, name),
+            })
+    };
+
+    let commands = &mut project.commands;
+    if let Some(command) = script(&["test", "test:unit"]) {
+        Commands::set(&mut commands.test, command, "package.json");
+    }
+    if let Some(command) = script(&["build"]) {
+        Commands::set(&mut commands.build, command, "package.json");
+    }
+    if let Some(command) = script(&["lint", "eslint"]) {
+        Commands::set(&mut commands.lint, command, "package.json");
+    }
+    if let Some(command) = script(&["typecheck", "type-check", "tsc"]) {
+        Commands::set(&mut commands.typecheck, command, "package.json");
+    }
+    if let Some(command) = script(&["format:check", "fmt:check", "format", "fmt", "prettier"]) {
+        Commands::set(&mut commands.format, command, "package.json");
+    }
+    if project.exists("tsconfig.json") {
+        // TypeScript project without an explicit typecheck script
+        Commands::set(&mut project.commands.typecheck, "npx tsc --noEmit", "tsconfig.json");
+    }
+}
+
+// deno.json tasks, falling back to the built-in subcommands
+fn detect_deno(project: &mut Project) {
+    let Some(evidence) = project.first_existing(&["deno.json", "deno.jsonc"]) else { return };
+    project.manager("deno", evidence);
+
+    // Tasks are only read from plain JSON; deno.jsonc may carry comments
+    let config: serde_json::Value = project
+        .read(evidence)
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default();
+    let has_task = |name: &str| config.get("tasks").and_then(|t| t.get(name)).is_some();
+    let command = |task: &str, builtin: &str| {
+        if has_task(task) {
+            format!("deno task {}", task)
+        } else {
+            builtin.to_string()
+        }
+    };
+
+    let commands = &mut project.commands;
+    Commands::set(&mut commands.test, command("test", "deno test"), evidence);
+    if has_task("build") {
+        Commands::set(&mut commands.build, "deno task build", evidence);
+    }
+    Commands::set(&mut commands.lint, command("lint", "deno lint"), evidence);
+    if has_task("check") {
+        Commands::set(&mut commands.typecheck, "deno task check", evidence);
+    }
+    Commands::set(&mut commands.format, "deno fmt --check", evidence);
+}
+
+fn detect_rust(project: &mut Project) {
+    let Some(manifest) = project.read("Cargo.toml") else { return };
+    project.manager("cargo", "Cargo.toml");
+
+    let workspace = manifest
+        .parse::<toml_edit::DocumentMut>()
+        .is_ok_and(|doc| doc.contains_key("workspace"));
+    let scope = if workspace { " --workspace" } else { "" };
+
+    let commands = &mut project.commands;
+    Commands::set(&mut commands.test, format!("cargo test{}", scope), "Cargo.toml");
+    Commands::set(&mut commands.build, format!("cargo build{}", scope), "Cargo.toml");
+    Commands::set(&mut commands.lint, format!("cargo clippy{}", scope), "Cargo.toml");
+    Commands::set(&mut commands.format, "cargo fmt --check", "Cargo.toml");
+}
+
+fn detect_go(project: &mut Project) {
+    if !project.exists("go.mod") {
+        return;
+    }
+    project.manager("go", "go.mod");
+
+    let commands = &mut project.commands;
+    Commands::set(&mut commands.test, "go test ./...", "go.mod");
+    Commands::set(&mut commands.build, "go build ./...", "go.mod");
+    Commands::set(&mut commands.lint, "go vet ./...", "go.mod");
+    // gofmt -l lists unformatted files but always exits 0
+    Commands::set(&mut commands.format, "test -z \"$(gofmt -l .)\"", "go.mod");
+}
+
+// NOTE(jimmylee)
+// Python tools are found by their config sections or by being listed as
+// dependencies, across pyproject.toml, setup.cfg, tox.ini and requirements files.
+fn detect_python(project: &mut Project) {
+    let configs = [
+        "pyproject.toml",
+        "setup.cfg",
+        "tox.ini",
+        "setup.py",
+        "requirements.txt",
+        "requirements-dev.txt",
+    ];
+    let found: Vec<(&str, String)> = configs
+        .iter()
+        .filter_map(|name| project.read(name).map(|content| (*name, content)))
+        .collect();
+    if found.is_empty() {
+        return;
+    }
+
+    let lockfiles = [
+        ("poetry.lock", "poetry"),
+        ("uv.lock", "uv"),
+        ("pdm.lock", "pdm"),
+        ("Pipfile.lock", "pipenv"),
+    ];
+    let (manager, evidence) = lockfiles
+        .iter()
+        .find(|(lockfile, _)| project.exists(lockfile))
+        .map(|(lockfile, manager)| (*manager, *lockfile))
+        .unwrap_or(("pip", found[0].0));
+    project.manager(manager, evidence);
+    // Tools are installed in the manager's environment, not globally
+    let run = match manager {
+        "pip" => String::new(),
+        other => format!("{} run ", other),
+    };
+
+    // First config file that mentions any of the markers
+    let mentions = |markers: &[&str]| {
+        found
+            .iter()
+            .find(|(_, content)| markers.iter().any(|m| content.contains(m)))
+            .map(|(name, _)| *name)
+    };
+    let mypy_ini = project.first_existing(&["mypy.ini", ".mypy.ini"]);
+    let flake8_ini = project.first_existing(&[".flake8"]);
+    let pyright_config = project.first_existing(&["pyrightconfig.json"]);
+    let test_dir = project.first_existing(&["tests", "test"]);
+
+    let commands = &mut project.commands;
+    if let Some(evidence) = mentions(&["pytest"]) {
+        Commands::set(&mut commands.test, format!("{}pytest", run), evidence);
+    } else if let Some(evidence) = mentions(&["[tox]"]) {
+        Commands::set(&mut commands.test, "tox", evidence);
+    } else if let Some(dir) = test_dir {
+        Commands::set(&mut commands.test, format!("{}python -m pytest", run), dir);
+    }
+
+    if let Some(evidence) = mentions(&["ruff"]) {
+        Commands::set(&mut commands.lint, format!("{}ruff check .", run), evidence);
+        Commands::set(&mut commands.format, format!("{}ruff format --check .", run), evidence);
+    } else if let Some(evidence) = flake8_ini.or_else(|| mentions(&["flake8"])) {
+        Commands::set(&mut commands.lint, format!("{}flake8", run), evidence);
+    }
+
+    if let Some(evidence) = mentions(&["black"]) {
+        Commands::set(&mut commands.format, format!("{}black --check .", run), evidence);
+    }
+
+    if let Some(evidence) = mypy_ini.or_else(|| mentions(&["mypy"])) {
+        Commands::set(&mut commands.typecheck, format!("{}mypy .", run), evidence);
+    } else if let Some(evidence) = pyright_config.or_else(|| mentions(&["pyright"])) {
+        Commands::set(&mut commands.typecheck, format!("{}pyright", run), evidence);
+    }
+}
+
+fn detect_ruby(project: &mut Project) {
+    let Some(gemfile) = project.read("Gemfile") else { return };
+    project.manager("bundler", "Gemfile");
+
+    let rakefile = project.read("Rakefile").unwrap_or_default();
+    if rakefile.contains(":test") {
+        Commands::set(&mut project.commands.test, "bundle exec rake test", "Rakefile");
+    } else if let Some(dir) = project.first_existing(&["spec", "test"]) {
+        let command = if dir == "spec" { "bundle exec rspec" } else { "bundle exec rake test" };
+        Commands::set(&mut project.commands.test, command, dir);
+    }
+    if gemfile.contains("rubocop") {
+        Commands::set(&mut project.commands.lint, "bundle exec rubocop", "Gemfile");
+    }
+}
+
+// Gradle and Maven, preferring the checked-in wrapper script
+fn detect_jvm(project: &mut Project) {
+    if let Some(evidence) = project.first_existing(&["build.gradle.kts", "build.gradle"]) {
+        project.manager("gradle", evidence);
+        let gradle = if project.exists("gradlew") { "./gradlew" } else { "gradle" };
+        let build_script = project.read(evidence).unwrap_or_default();
+
+        let commands = &mut project.commands;
+        Commands::set(&mut commands.test, format!("{} test", gradle), evidence);
+        Commands::set(&mut commands.build, format!("{} build", gradle), evidence);
+        if build_script.contains("spotless") {
+            Commands::set(&mut commands.format, format!("{} spotlessCheck", gradle), evidence);
+        }
+    }
+
+    if let Some(pom) = project.read("pom.xml") {
+        project.manager("maven", "pom.xml");
+        let maven = if project.exists("mvnw") { "./mvnw" } else { "mvn" };
+
+        let commands = &mut project.commands;
+        Commands::set(&mut commands.test, format!("{} test", maven), "pom.xml");
+        Commands::set(&mut commands.build, format!("{} compile", maven), "pom.xml");
+        if pom.contains("spotless") {
+            Commands::set(&mut commands.format, format!("{} spotless:check", maven), "pom.xml");
+        }
+    }
+}
+
+// Makefile targets fill in kinds no ecosystem above provided
+fn detect_make(project: &mut Project) {
+    let Some(evidence) = project.first_existing(&["GNUmakefile", "makefile", "Makefile"]) else { return };
+    let targets = make_targets(&project.read(evidence).unwrap_or_default());
+    fill_from_targets(&mut project.commands, &targets, "make", evidence);
+}
+
+fn detect_just(project: &mut Project) {
+    let Some(evidence) = project.first_existing(&["justfile", "Justfile", ".justfile"]) else { return };
+    let recipes = just_recipes(&project.read(evidence).unwrap_or_default());
+    fill_from_targets(&mut project.commands, &recipes, "just", evidence);
+}
+
+fn fill_from_targets(commands: &mut Commands, targets: &[String], runner: &str, evidence: &str) {
+    let kinds: [(&mut Option<Detected>, &[&str]); 5] = [
+        (&mut commands.test, &["test", "tests"]),
+        (&mut commands.build, &["build"]),
+        (&mut commands.lint, &["lint", "check"]),
+        (&mut commands.typecheck, &["typecheck", "type-check"]),
+        (&mut commands.format, &["format-check", "fmt-check", "format", "fmt"]),
+    ];
+    for (slot, names) in kinds {
+        if let Some(name) = names.iter().find(|n| targets.iter().any(|t| t == *n)) {
+            Commands::set(slot, format!("{} {}", runner, name), evidence);
+        }
+    }
+}
+
+// Rule targets ("test:" or "test lint: deps"), skipping variable assignments
+fn make_targets(makefile: &str) -> Vec<String> {
+    makefile
+        .lines()
+        .filter(|line| !line.starts_with(['\t', ' ', '#', '.']))
+        .filter_map(|line| line.split_once(':'))
+        .filter(|(_, rest)| !rest.starts_with('='))
+        .flat_map(|(names, _)| names.split_whitespace().map(str::to_string).collect::<Vec<_>>())
+        .filter(|name| !name.contains(['=', '$', '%']))
+        .collect()
+}
+
+// Recipe names ("test:" or "test *args: build"), skipping ":=" assignments
+fn just_recipes(justfile: &str) -> Vec<String> {
+    justfile
+        .lines()
+        .filter(|line| !line.starts_with([' ', '\t', '#', '[']))
+        .filter_map(|line| line.split_once(':'))
+        .filter(|(_, rest)| !rest.starts_with('='))
+        .filter_map(|(head, _)| {
+            let name = head.trim_start_matches('@').split_whitespace().next()?;
+            let valid = !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+            (valid && name != "set" && name != "alias").then(|| name.to_string())
+        })
+        .collect()
+}
+
+// Source files per language, most common first
+fn language_mix(workdir: &Path) -> Vec<LanguageCount> {
+    let mut counts: HashMap<Language, usize> = HashMap::new();
+    let files = WalkBuilder::new(workdir)
+        .require_git(false)
+        .build()
+        .flatten()
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .take(MAX_SCANNED_FILES);
+
+    for entry in files {
+        let language = Language::from_path(&relative_path(workdir, entry.path()));
+        if language != Language::Unknown {
+            *counts.entry(language).or_default() += 1;
+        }
+    }
+
+    let mut languages: Vec<LanguageCount> = counts
+        .into_iter()
+        .map(|(language, files)| LanguageCount {
+            language: language.name().to_string(),
+            files,
+        })
+        .collect();
+    languages.sort_by(|a, b| b.files.cmp(&a.files).then_with(|| a.language.cmp(&b.language)));
+    languages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn command(slot: &Option<Detected>) -> (&str, &str) {
+        let found = slot.as_ref().expect("command detected");
+        (found.value.as_str(), found.evidence.as_str())
+    }
+
+    #[test]
+    fn test_node_with_lockfile_and_makefile_fallback() {
+        let dir = tempdir().unwrap();
+        let package = r#"{"scripts": {"test": "vitest", "lint": "eslint .", "format": "prettier -w ."}}"#;
+        fs::write(dir.path().join("package.json"), package).unwrap();
+        fs::write(dir.path().join("pnpm-lock.yaml"), "").unwrap();
+        fs::write(dir.path().join("tsconfig.json"), "{}").unwrap();
+        fs::write(dir.path().join("Makefile"), "VERSION := 1\nbuild: deps\n\tnpx tsc\n.PHONY: test\n").unwrap();
+        fs::write(dir.path().join("index.ts"), "").unwrap();
+
+        let detection = detect(dir.path());
+        let manager = detection.package_manager.unwrap();
+        assert_eq!((manager.value.as_str(), manager.evidence.as_str()), ("pnpm", "pnpm-lock.yaml"));
+        assert_eq!(command(&detection.commands.test), ("pnpm test", "package.json"));
+        assert_eq!(command(&detection.commands.lint), ("pnpm run lint", "package.json"));
+        assert_eq!(command(&detection.commands.format), ("pnpm run format", "package.json"));
+        assert_eq!(command(&detection.commands.typecheck), ("npx tsc --noEmit", "tsconfig.json"));
+        assert_eq!(command(&detection.commands.build), ("make build", "Makefile"));
+        assert_eq!(detection.languages[0].language, "json");
+    }
+
+    #[test]
+    fn test_python_tools_from_config_sections() {
+        let dir = tempdir().unwrap();
+        let pyproject = "[project]\nname = \"x\"\n\n[tool.ruff]\nline-length = 100\n";
+        fs::write(dir.path().join("pyproject.toml"), pyproject).unwrap();
+        fs::write(dir.path().join("setup.cfg"), "[tool:pytest]\naddopts = -q\n\n[mypy]\nstrict = true\n").unwrap();
+        fs::write(dir.path().join("uv.lock"), "").unwrap();
+        fs::write(dir.path().join("justfile"), "set shell := [\"bash\"]\n\n@build *args:\n    uv build\n").unwrap();
+
+        let detection = detect(dir.path());
+        assert_eq!(detection.package_manager.unwrap().value, "uv");
+        assert_eq!(command(&detection.commands.test), ("uv run pytest", "setup.cfg"));
+        assert_eq!(command(&detection.commands.lint), ("uv run ruff check .", "pyproject.toml"));
+        assert_eq!(command(&detection.commands.format), ("uv run ruff format --check .", "pyproject.toml"));
+        assert_eq!(command(&detection.commands.typecheck), ("uv run mypy .", "setup.cfg"));
+        assert_eq!(command(&detection.commands.build), ("just build", "justfile"));
+    }
+
+    #[test]
+    fn test_rust_workspace_and_empty_dir() {
+        let dir = tempdir().unwrap();
+        assert!(detect(dir.path()).package_manager.is_none());
+
+        fs::write(dir.path().join("Cargo.toml"), "[workspace]\nmembers = [\"a\"]\n").unwrap();
+        let detection = detect(dir.path());
+        assert_eq!(command(&detection.commands.test), ("cargo test --workspace", "Cargo.toml"));
+        assert_eq!(command(&detection.commands.format), ("cargo fmt --check", "Cargo.toml"));
+        assert!(detection.commands.typecheck.is_none());
+    }
+}
@@ -0,0 +1,256 @@
+// NOTE(angeldev)
+// Marker-delimited block edits.
+// Handles replace_between and delete_between, which operate on the lines enclosed by
+// a start and end marker so the Engineer never has to quote the old block verbatim.
+
+use crate::edits::{read_file, write_file};
+use crate::error::{EditError, EditResult};
+use crate::matcher::{find_closest_matches, find_marker_lines, truncate_preview};
+use std::path::Path;
+
+// NOTE(angeldev)
+// Similarity threshold for finding closest matches when a marker fails.
+const SIMILARITY_THRESHOLD: f64 = 0.5;
+
+// NOTE(angeldev)
+// Maximum number of closest matches to return in error messages.
+const MAX_CLOSEST_MATCHES: usize = 3;
+
+// NOTE(angeldev)
+// The lines a between-edit operates on (1-indexed, inclusive).
+// An empty region (first_line == last_line + 1) means the markers are adjacent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MarkedRegion {
+    pub first_line: usize,
+    pub last_line: usize,
+}
+
+impl MarkedRegion {
+    // NOTE(angeldev)
+    // Number of lines inside the region.
+    pub fn len(&self) -> usize {
+        (self.last_line + 1).saturating_sub(self.first_line)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+// NOTE(angeldev)
+// Locates the region enclosed by the start and end markers.
+// The start marker must be unique; the end marker is the first one after it.
+// With inclusive = true the marker lines themselves are part of the region.
+pub fn locate_between(
+    content: &str,
+    path: &str,
+    start: &str,
+    end: &str,
+    inclusive: bool,
+) -> EditResult<MarkedRegion> {
+    let starts = find_marker_lines(content, start);
+    let ends = find_marker_lines(content, end);
+
+    if starts.is_empty() {
+        return Err(marker_not_found(content, path, "Start", start));
+    }
+    if ends.is_empty() {
+        return Err(marker_not_found(content, path, "End", end));
+    }
+
+    // Identical markers (e.g. "---" front matter) pair the first two occurrences
+    let same_marker = start == end;
+    if (!same_marker && starts.len() > 1) || (same_marker && starts.len() > 2) {
+        let lines: Vec<String> = starts.iter().map(|(l, _)| l.to_string()).collect();
+        return Err(EditError::InvalidMarkers {
+            path: path.to_string(),
+            reason: format!(
+                "start marker matches {} times (lines {}); nested or repeated blocks are ambiguous, use a more specific marker",
+                starts.len(),
+                lines.join(", ")
+            ),
+        });
+    }
+
+    let (start_first, start_last) = starts[0];
+    let (end_first, end_last) = match ends.iter().find(|(first, _)| *first > start_last) {
+        Some(found) => *found,
+        None => {
+            return Err(EditError::InvalidMarkers {
+                path: path.to_string(),
+                reason: format!(
+                    "no end marker after the start marker at line {} (end marker found at line {})",
+                    start_first, ends[0].0
+                ),
+            })
+        }
+    };
+
+    if inclusive {
+        Ok(MarkedRegion {
+            first_line: start_first,
+            last_line: end_last,
+        })
+    } else {
+        Ok(MarkedRegion {
+            first_line: start_last + 1,
+            last_line: end_first - 1,
+        })
+    }
+}
+
+// NOTE(angeldev)
+// Replaces the lines of a region with new content.
+// Works for empty regions too, inserting the content between adjacent markers.
+pub fn replace_region(content: &str, region: MarkedRegion, new_content: &str) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let first_idx = region.first_line - 1;
+    let last_idx = first_idx + region.len();
+
+    let mut result: Vec<&str> = Vec::with_capacity(lines.len());
+    result.extend_from_slice(&lines[..first_idx]);
+    result.extend(new_content.lines());
+    result.extend_from_slice(&lines[last_idx..]);
+
+    let mut output = result.join("\n");
+
+    // Handle trailing newline
+    if content.ends_with('\n') && !output.is_empty() {
+        output.push('\n');
+    }
+
+    output
+}
+
+// NOTE(angeldev)
+// Applies a replace_between operation.
+// Replaces everything between the start and end markers with content.
+pub fn apply_replace_between(
+    workdir: &Path,
+    path: &str,
+    start: &str,
+    end: &str,
+    content: &str,
+    inclusive: bool,
+) -> EditResult<String> {
+    let file_content = read_file(workdir, path)?;
+    let region = locate_between(&file_content, path, start, end, inclusive)?;
+
+    let new_content = replace_region(&file_content, region, content);
+    write_file(workdir, path, &new_content)?;
+
+    if region.is_empty() {
+        Ok(format!(
+            "Inserted {} line(s) between markers at line {}",
+            content.lines().count(),
+            region.first_line
+        ))
+    } else {
+        Ok(format!(
+            "Replaced lines {}-{} between markers with {} line(s)",
+            region.first_line,
+            region.last_line,
+            content.lines().count()
+        ))
+    }
+}
+
+// NOTE(angeldev)
+// Applies a delete_between operation.
+// Deletes everything between the start and end markers.
+pub fn apply_delete_between(
+    workdir: &Path,
+    path: &str,
+    start: &str,
+    end: &str,
+    inclusive: bool,
+) -> EditResult<String> {
+    let file_content = read_file(workdir, path)?;
+    let region = locate_between(&file_content, path, start, end, inclusive)?;
+
+    if region.is_empty() {
+        return Ok("Nothing between markers (nothing deleted)".to_string());
+    }
+
+    let new_content = replace_region(&file_content, region, "");
+    write_file(workdir, path, &new_content)?;
+
+    Ok(format!(
+        "Deleted {} line(s) between markers ({}-{})",
+        region.len(),
+        region.first_line,
+        region.last_line
+    ))
+}
+
+// NOTE(angeldev)
+// Builds a marker_not_found error with closest matches for the missing marker.
+fn marker_not_found(content: &str, path: &str, which: &str, marker: &str) -> EditError {
+    EditError::MarkerNotFound {
+        path: path.to_string(),
+        which: which.to_string(),
+        marker_preview: truncate_preview(marker, 200),
+        closest_matches: find_closest_matches(content, marker, SIMILARITY_THRESHOLD, MAX_CLOSEST_MATCHES),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    const GENERATED: &str = "a\n// BEGIN generated\nold1\nold2\n// END generated\nb\n";
+
+    #[test]
+    fn test_replace_between() {
+        let dir = tempdir().unwrap();
+        let path = "test.ts";
+        fs::write(dir.path().join(path), GENERATED).unwrap();
+
+        let result = apply_replace_between(dir.path(), path, "// BEGIN generated", "// END generated", "new", false);
+        assert!(result.is_ok());
+
+        let content = fs::read_to_string(dir.path().join(path)).unwrap();
+        assert_eq!(content, "a\n// BEGIN generated\nnew\n// END generated\nb\n");
+    }
+
+    #[test]
+    fn test_replace_between_inclusive() {
+        let dir = tempdir().unwrap();
+        let path = "test.ts";
+        fs::write(dir.path().join(path), GENERATED).unwrap();
+
+        let result = apply_replace_between(dir.path(), path, "BEGIN generated", "END generated", "new", true);
+        assert!(result.is_ok());
+
+        let content = fs::read_to_string(dir.path().join(path)).unwrap();
+        assert_eq!(content, "a\nnew\nb\n");
+    }
+
+    #[test]
+    fn test_delete_between() {
+        let dir = tempdir().unwrap();
+        let path = "test.ts";
+        fs::write(dir.path().join(path), GENERATED).unwrap();
+
+        let result = apply_delete_between(dir.path(), path, "// BEGIN generated", "// END generated", false);
+        assert!(result.unwrap().contains("Deleted 2 line(s)"));
+
+        let content = fs::read_to_string(dir.path().join(path)).unwrap();
+        assert_eq!(content, "a\n// BEGIN generated\n// END generated\nb\n");
+    }
+
+    #[test]
+    fn test_locate_between_errors() {
+        let missing = locate_between(GENERATED, "f", "// BEGIN other", "// END generated", false);
+        assert!(matches!(missing, Err(EditError::MarkerNotFound { .. })));
+
+        let reversed = locate_between(GENERATED, "f", "// END generated", "// BEGIN generated", false);
+        assert!(matches!(reversed, Err(EditError::InvalidMarkers { .. })));
+
+        let nested = "// BEGIN\n// BEGIN\nx\n// END\n// END\n";
+        let ambiguous = locate_between(nested, "f", "// BEGIN", "// END", false);
+        assert!(matches!(ambiguous, Err(EditError::InvalidMarkers { .. })));
+    }
+}
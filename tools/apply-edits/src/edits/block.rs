@@ -0,0 +1,379 @@
+// NOTE(angeldev)
+// Block replacement by opening line.
+// Handles replace_block, which finds a header line like `function Footer(props) {`
+// and replaces the whole block it opens, so the Engineer can rewrite a function
+// without quoting its old body. Brackets are balanced with the lexical scanner;
+// Python and YAML blocks are delimited by indentation instead.
+
+use crate::edits::{read_file, write_file};
+use crate::error::{EditError, EditResult};
+use crate::language::Language;
+use crate::matcher::{find_closest_matches, truncate_preview};
+use crate::scanner::{brackets_match, code_mask, line_of_offset, line_starts};
+use std::path::Path;
+
+// NOTE(angeldev)
+// Similarity threshold for finding closest matches when the header fails.
+const SIMILARITY_THRESHOLD: f64 = 0.5;
+
+// NOTE(angeldev)
+// Maximum number of closest matches to return in error messages.
+const MAX_CLOSEST_MATCHES: usize = 3;
+
+// NOTE(angeldev)
+// How many lines after the header may pass before the block's opening bracket
+// (covers Allman-style braces and multi-line signatures).
+const MAX_HEADER_LOOKAHEAD: usize = 3;
+
+// NOTE(angeldev)
+// The lines a block occupies (1-indexed, inclusive).
+// `suffix` holds code that follows the closing bracket on its line (e.g. ` else {`)
+// and must survive the replacement.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockRegion {
+    pub first_line: usize,
+    pub last_line: usize,
+    pub suffix: Option<String>,
+}
+
+// NOTE(angeldev)
+// Finds the block opened by the header line.
+// The header must match exactly one line outside strings and comments.
+pub fn locate_block(content: &str, path: &str, header: &str) -> EditResult<BlockRegion> {
+    let needle = header.lines().find(|l| !l.trim().is_empty()).unwrap_or("").trim();
+    if needle.is_empty() {
+        return Err(EditError::InvalidEdit {
+            reason: "Header cannot be empty".to_string(),
+        });
+    }
+
+    let language = Language::from_path(path);
+    let mask = code_mask(content, language);
+    let starts = line_starts(content);
+
+    // Header matches whose first character is code (not a comment or string)
+    let mut header_lines: Vec<usize> = content
+        .match_indices(needle)
+        .filter(|(pos, _)| mask[*pos])
+        .map(|(pos, _)| line_of_offset(&starts, pos))
+        .collect();
+    header_lines.dedup();
+
+    let header_line = match header_lines.as_slice() {
+        [] => {
+            return Err(EditError::AnchorNotFound {
+                path: path.to_string(),
+                anchor_preview: truncate_preview(header, 200),
+                closest_matches: find_closest_matches(content, needle, SIMILARITY_THRESHOLD, MAX_CLOSEST_MATCHES),
+            })
+        }
+        [line] => *line,
+        _ => {
+            return Err(EditError::MultipleMatches {
+                path: path.to_string(),
+                count: header_lines.len(),
+                search_preview: truncate_preview(header, 200),
+            })
+        }
+    };
+
+    if language.uses_indentation_blocks() {
+        locate_indented_block(content, path, &mask, &starts, header_line, language)
+    } else {
+        locate_bracket_block(content, path, &mask, &starts, header_line)
+    }
+}
+
+// NOTE(angeldev)
+// Finds the balanced bracket block opened on (or just after) the header line.
+// A paren/bracket group that closes is only the end of the block if no `{` follows
+// it, so `function foo(a) {` and `fn foo() -> T\n{` extend to the closing brace.
+fn locate_bracket_block(
+    content: &str,
+    path: &str,
+    mask: &[bool],
+    starts: &[usize],
+    header_line: usize,
+) -> EditResult<BlockRegion> {
+    let bytes = content.as_bytes();
+    let block_error = |reason: String| EditError::BlockNotFound {
+        path: path.to_string(),
+        line: header_line,
+        reason,
+    };
+
+    let mut stack: Vec<u8> = Vec::new();
+    let mut opened = false;
+    let mut i = starts[header_line - 1];
+
+    while i < bytes.len() {
+        if !mask[i] {
+            i += 1;
+            continue;
+        }
+
+        match bytes[i] {
+            b'{' | b'(' | b'[' => {
+                stack.push(bytes[i]);
+                opened = true;
+            }
+            b'}' | b')' | b']' => match stack.pop() {
+                // A closer before anything opened belongs to a previous block (`} else {`)
+                None => {}
+                Some(open) if !brackets_match(open, bytes[i]) => {
+                    return Err(block_error(format!(
+                        "mismatched '{}' at line {}",
+                        bytes[i] as char,
+                        line_of_offset(starts, i)
+                    )));
+                }
+                Some(b'{') if stack.is_empty() => {
+                    return Ok(region_ending_at(content, mask, starts, header_line, i));
+                }
+                // A closed signature group: skip ahead to the body's brace if one follows
+                Some(_) if stack.is_empty() => match following_brace(bytes, mask, i + 1) {
+                    Some(brace) => {
+                        i = brace;
+                        continue;
+                    }
+                    None => return Ok(region_ending_at(content, mask, starts, header_line, i)),
+                },
+                Some(_) => {}
+            },
+            b'\n' if !opened && line_of_offset(starts, i) >= header_line + MAX_HEADER_LOOKAHEAD => {
+                return Err(block_error("no opening bracket follows the header".to_string()));
+            }
+            _ => {}
+        }
+
+        i += 1;
+    }
+
+    if opened {
+        Err(block_error(format!("{} unclosed bracket(s) at end of file", stack.len())))
+    } else {
+        Err(block_error("no opening bracket follows the header".to_string()))
+    }
+}
+
+// NOTE(angeldev)
+// Returns the offset of the brace that opens a body after a closed group:
+// either later on the same line (`) -> Result<T, E> {`) or as the first
+// character of the next non-blank line (Allman style). A top-level `;` or `,`
+// ends the search; commas inside generics and tuples do not.
+fn following_brace(bytes: &[u8], mask: &[bool], from: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut i = from;
+    while i < bytes.len() {
+        if !mask[i] {
+            i += 1;
+            continue;
+        }
+        match bytes[i] {
+            b'{' => return Some(i),
+            b'(' | b'[' | b'<' => depth += 1,
+            // `->` and `=>` are arrows, not closing angle brackets
+            b'>' if i > 0 && (bytes[i - 1] == b'-' || bytes[i - 1] == b'=') => {}
+            b')' | b']' | b'>' => depth = depth.saturating_sub(1),
+            b';' => return None,
+            b',' if depth == 0 => return None,
+            b'\n' => {
+                return (i..bytes.len())
+                    .find(|&j| mask[j] && !bytes[j].is_ascii_whitespace())
+                    .filter(|&j| bytes[j] == b'{');
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+// NOTE(angeldev)
+// Builds the region for a block whose closing bracket is at byte `close`.
+// Code after the closer other than `;`/`,` is kept as a suffix.
+fn region_ending_at(
+    content: &str,
+    mask: &[bool],
+    starts: &[usize],
+    header_line: usize,
+    close: usize,
+) -> BlockRegion {
+    let last_line = line_of_offset(starts, close);
+    let line_end = content[close..].find('\n').map(|p| close + p).unwrap_or(content.len());
+    let rest = &content[close + 1..line_end];
+
+    let has_code = rest
+        .bytes()
+        .zip(&mask[close + 1..line_end])
+        .any(|(b, is_code)| *is_code && !b.is_ascii_whitespace() && b != b';' && b != b',');
+
+    BlockRegion {
+        first_line: header_line,
+        last_line,
+        suffix: if has_code { Some(rest.to_string()) } else { None },
+    }
+}
+
+// NOTE(angeldev)
+// Finds an indentation-defined block (Python, YAML).
+// The header may span several lines while brackets are open (multi-line signatures);
+// the body is every following line indented deeper than the header. Blank lines,
+// comments and string continuations only count if deeper code follows them.
+fn locate_indented_block(
+    content: &str,
+    path: &str,
+    mask: &[bool],
+    starts: &[usize],
+    header_line: usize,
+    language: Language,
+) -> EditResult<BlockRegion> {
+    let lines: Vec<&str> = content.lines().collect();
+    let indent_of = |line: &str| line.len() - line.trim_start().len();
+    let header_indent = indent_of(lines[header_line - 1]);
+
+    // Extend the header over lines with open brackets
+    let mut depth: i32 = 0;
+    let mut header_end = header_line;
+    for (offset, b) in content.bytes().enumerate().skip(starts[header_line - 1]) {
+        if !mask[offset] {
+            continue;
+        }
+        match b {
+            b'{' | b'(' | b'[' => depth += 1,
+            b'}' | b')' | b']' => depth -= 1,
+            b'\n' if depth <= 0 => {
+                header_end = line_of_offset(starts, offset);
+                break;
+            }
+            _ => {}
+        }
+        header_end = line_of_offset(starts, offset);
+    }
+
+    let yaml_mapping = language == Language::Yaml && lines[header_line - 1].trim_end().ends_with(':');
+    let mut last_line = header_end;
+
+    for (idx, line) in lines.iter().enumerate().skip(header_end) {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        let first_code = starts[idx] + indent_of(line);
+        if !mask[first_code] {
+            // Comment or string continuation - included only if the block goes on
+            continue;
+        }
+
+        let indent = indent_of(line);
+        if indent > header_indent || (yaml_mapping && indent == header_indent && trimmed.starts_with("- ")) {
+            last_line = idx + 1;
+        } else {
+            break;
+        }
+    }
+
+    if last_line == header_end {
+        return Err(EditError::BlockNotFound {
+            path: path.to_string(),
+            line: header_line,
+            reason: "no indented block follows the header".to_string(),
+        });
+    }
+
+    Ok(BlockRegion {
+        first_line: header_line,
+        last_line,
+        suffix: None,
+    })
+}
+
+// NOTE(angeldev)
+// Applies a replace_block operation.
+// Replaces the header line through the end of its block with content.
+pub fn apply_replace_block(workdir: &Path, path: &str, header: &str, content: &str) -> EditResult<String> {
+    let file_content = read_file(workdir, path)?;
+    let region = locate_block(&file_content, path, header)?;
+
+    let lines: Vec<&str> = file_content.lines().collect();
+    let mut replacement = content.trim_end_matches('\n').to_string();
+    if let Some(ref suffix) = region.suffix {
+        replacement.push_str(suffix);
+    }
+
+    let mut result: Vec<&str> = Vec::with_capacity(lines.len());
+    result.extend_from_slice(&lines[..region.first_line - 1]);
+    result.extend(replacement.lines());
+    result.extend_from_slice(&lines[region.last_line..]);
+
+    let mut new_content = result.join("\n");
+    if file_content.ends_with('\n') && !new_content.is_empty() {
+        new_content.push('\n');
+    }
+
+    write_file(workdir, path, &new_content)?;
+
+    Ok(format!(
+        "Replaced block at lines {}-{} with {} line(s)",
+        region.first_line,
+        region.last_line,
+        content.lines().count()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_locate_block_braces() {
+        let js = "import x;\n\nfunction Footer(props) {\n  const s = \"}\";\n  if (a) {\n    b();\n  }\n}\n\nexport default Footer;\n";
+        let region = locate_block(js, "Footer.tsx", "function Footer(props) {").unwrap();
+        assert_eq!((region.first_line, region.last_line), (3, 8));
+        assert_eq!(region.suffix, None);
+    }
+
+    #[test]
+    fn test_locate_block_signature_and_suffix() {
+        let rs = "impl Foo for Bar {\n    fn run(\n        &self,\n    ) -> Result<(), E>\n    {\n        if x {\n            y\n        } else {\n            z\n        }\n    }\n}\n";
+        let region = locate_block(rs, "lib.rs", "fn run(").unwrap();
+        assert_eq!((region.first_line, region.last_line), (2, 11));
+
+        let region = locate_block(rs, "lib.rs", "if x {").unwrap();
+        assert_eq!((region.first_line, region.last_line), (6, 8));
+        assert_eq!(region.suffix.as_deref(), Some(" else {"));
+    }
+
+    #[test]
+    fn test_locate_block_indentation() {
+        let py = "class A:\n    def f(\n        self,\n    ):\n        x = 1\n\n        return x\n\n    def g(self):\n        pass\n";
+        let region = locate_block(py, "a.py", "def f(").unwrap();
+        assert_eq!((region.first_line, region.last_line), (2, 7));
+
+        let yaml = "jobs:\n  build:\n    steps:\n    - run: make\n    - run: test\n  lint:\n    steps: []\n";
+        let region = locate_block(yaml, "ci.yml", "steps:").unwrap_err();
+        assert!(matches!(region, EditError::MultipleMatches { .. }));
+        let region = locate_block(yaml, "ci.yml", "build:").unwrap();
+        assert_eq!((region.first_line, region.last_line), (2, 5));
+    }
+
+    #[test]
+    fn test_apply_replace_block() {
+        let dir = tempdir().unwrap();
+        let path = "app.js";
+        fs::write(
+            dir.path().join(path),
+            "// function Footer() {\nfunction Footer() {\n  return 1;\n}\nfoo();\n",
+        )
+        .unwrap();
+
+        let result = apply_replace_block(dir.path(), path, "function Footer() {", "function Footer() {\n  return 2;\n}\n");
+        assert!(result.is_ok(), "{:?}", result);
+
+        let content = fs::read_to_string(dir.path().join(path)).unwrap();
+        assert_eq!(content, "// function Footer() {\nfunction Footer() {\n  return 2;\n}\nfoo();\n");
+    }
+}
@@ -2,7 +2,7 @@
 // Delete edit operations.
 // Handles delete_file, delete_lines, and delete_match.
 
-use crate::edits::{read_file, write_file};
+use crate::edits::{check_expected, read_file, write_file};
 use crate::error::{EditError, EditResult};
 use crate::matcher::{delete_line_range, delete_matching_lines};
 use std::path::Path;
@@ -29,11 +29,13 @@ pub fn apply_delete_file(workdir: &Path, path: &str) -> EditResult<String> {
 // NOTE(jimmylee)
 // Applies a delete_lines operation.
 // Deletes lines from start_line to end_line (1-indexed, inclusive).
+// NOTE(angeldev): When expected is given, the range must still contain that text.
 pub fn apply_delete_lines(
     workdir: &Path,
     path: &str,
     start_line: usize,
     end_line: usize,
+    expected: Option<&str>,
 ) -> EditResult<String> {
     let content = read_file(workdir, path)?;
     let total_lines = content.lines().count();
@@ -63,6 +65,8 @@ pub fn apply_delete_lines(
         });
     }
 
+    check_expected(&content, path, start_line, end_line, expected)?;
+
     // Delete the line range
     match delete_line_range(&content, start_line, end_line) {
         Some(new_content) => {
@@ -144,7 +148,7 @@ mod tests {
         let path = "test.txt";
         fs::write(dir.path().join(path), "line1\nline2\nline3\nline4\n").unwrap();
 
-        let result = apply_delete_lines(dir.path(), path, 2, 3);
+        let result = apply_delete_lines(dir.path(), path, 2, 3, None);
         assert!(result.is_ok());
 
         let content = fs::read_to_string(dir.path().join(path)).unwrap();
@@ -160,10 +164,26 @@ mod tests {
         let path = "test.txt";
         fs::write(dir.path().join(path), "line1\nline2\n").unwrap();
 
-        let result = apply_delete_lines(dir.path(), path, 3, 5);
+        let result = apply_delete_lines(dir.path(), path, 3, 5, None);
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_delete_lines_expected_mismatch() {
+        let dir = tempdir().unwrap();
+        let path = "test.txt";
+        fs::write(dir.path().join(path), "line1\nline2\nline3\n").unwrap();
+
+        let result = apply_delete_lines(dir.path(), path, 2, 2, Some("other"));
+        match result.unwrap_err() {
+            EditError::ExpectedMismatch { .. } => (),
+            e => panic!("Expected ExpectedMismatch, got {:?}", e),
+        }
+
+        let result = apply_delete_lines(dir.path(), path, 2, 2, Some("  line2  "));
+        assert!(result.is_ok());
+    }
+
     #[test]
     fn test_delete_match() {
         let dir = tempdir().unwrap();
@@ -0,0 +1,234 @@
+// NOTE(angeldev)
+// Idempotent insert edits.
+// Handles ensure_line and ensure_import, which add content only when the file
+// doesn't already have it. retry_failed_edits re-sends edits that succeeded the
+// first time, and these must not duplicate imports or .gitignore entries.
+
+mod imports;
+
+use crate::edits::{read_file, write_file};
+use crate::error::{EditError, EditResult};
+use crate::language::Language;
+use crate::matcher::{find_closest_matches, truncate_preview};
+use std::path::Path;
+
+// NOTE(angeldev)
+// Similarity threshold for finding closest matches when the `after` anchor fails.
+const SIMILARITY_THRESHOLD: f64 = 0.5;
+
+// NOTE(angeldev)
+// Maximum number of closest matches to return in error messages.
+const MAX_CLOSEST_MATCHES: usize = 3;
+
+// NOTE(angeldev)
+// What an ensure edit did (line numbers are 1-indexed in the resulting file).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Ensured {
+    // Already there; the file is unchanged
+    Present { line: usize },
+    // A new line or statement was inserted
+    Inserted { content: String, line: usize },
+    // Names were added to an existing import statement
+    Merged { content: String, line: usize },
+}
+
+impl Ensured {
+    // NOTE(angeldev)
+    // Describes the result for edit outcomes; `what` is "Line" or "Import".
+    pub fn describe(&self, what: &str) -> String {
+        match self {
+            Ensured::Present { line } => format!("{} already present at line {}", what, line),
+            Ensured::Inserted { line, .. } => format!("{} added at line {}", what, line),
+            Ensured::Merged { line, .. } => format!("{} merged into line {}", what, line),
+        }
+    }
+}
+
+// NOTE(angeldev)
+// Ensures a line exists in content.
+// A line is already present if any line matches it ignoring surrounding whitespace.
+// Otherwise it goes after the first line containing `after`, at the end of the
+// section opened by the `section` header line (which is appended if missing),
+// or at the end of the file.
+pub fn ensure_line(
+    content: &str,
+    path: &str,
+    line: &str,
+    after: Option<&str>,
+    section: Option<&str>,
+) -> EditResult<Ensured> {
+    let wanted = line.trim();
+    if wanted.is_empty() || line.contains('\n') {
+        return Err(EditError::InvalidEdit {
+            reason: "ensure_line needs a single non-empty line".to_string(),
+        });
+    }
+
+    let lines: Vec<&str> = content.lines().collect();
+    if let Some(i) = lines.iter().position(|l| l.trim() == wanted) {
+        return Ok(Ensured::Present { line: i + 1 });
+    }
+
+    let (at, insert): (usize, Vec<&str>) = match (after, section) {
+        (Some(anchor), _) => {
+            if anchor.is_empty() {
+                return Err(EditError::InvalidEdit {
+                    reason: "Anchor string cannot be empty".to_string(),
+                });
+            }
+            let i = lines.iter().position(|l| l.contains(anchor)).ok_or_else(|| EditError::AnchorNotFound {
+                path: path.to_string(),
+                anchor_preview: truncate_preview(anchor, 200),
+                closest_matches: find_closest_matches(content, anchor, SIMILARITY_THRESHOLD, MAX_CLOSEST_MATCHES),
+            })?;
+            (i + 1, vec![line])
+        }
+        (None, Some(header)) => match lines.iter().position(|l| l.trim() == header.trim()) {
+            Some(h) => (section_last_line(&lines, h) + 1, vec![line]),
+            None if lines.last().is_some_and(|l| !l.trim().is_empty()) => (lines.len(), vec!["", header.trim(), line]),
+            None => (lines.len(), vec![header.trim(), line]),
+        },
+        (None, None) => (lines.len(), vec![line]),
+    };
+
+    let line_number = at + insert.len();
+    let mut new_lines: Vec<&str> = lines.clone();
+    new_lines.splice(at..at, insert);
+
+    let mut new_content = new_lines.join("\n");
+    if content.ends_with('\n') || content.is_empty() {
+        new_content.push('\n');
+    }
+    Ok(Ensured::Inserted {
+        content: new_content,
+        line: line_number,
+    })
+}
+
+// NOTE(angeldev)
+// Finds the last non-blank line of the section whose header is at line h.
+// The section ends at the next line starting with the header's leading punctuation
+// (`[` for INI/TOML tables, `#` for .gitignore comments, `##` for Markdown), or at
+// the first blank line when the header has none.
+fn section_last_line(lines: &[&str], h: usize) -> usize {
+    let header = lines[h].trim();
+    let marker: String = header.chars().take_while(|c| !c.is_alphanumeric() && *c != ' ').collect();
+
+    let end = (h + 1..lines.len())
+        .find(|&k| {
+            let t = lines[k].trim();
+            if marker.is_empty() {
+                t.is_empty()
+            } else {
+                t.starts_with(&marker)
+            }
+        })
+        .unwrap_or(lines.len());
+
+    (h..end).rev().find(|&k| !lines[k].trim().is_empty()).unwrap_or(h)
+}
+
+// NOTE(angeldev)
+// Ensures an import/use/require statement exists in content.
+// Supports JavaScript, TypeScript, Python, Rust and Go by extension.
+pub fn ensure_import(content: &str, path: &str, import: &str) -> EditResult<Ensured> {
+    let language = Language::from_path(path);
+    if !imports::supports(language) {
+        return Err(EditError::InvalidEdit {
+            reason: format!("ensure_import does not support this file type: {}", path),
+        });
+    }
+    imports::ensure(content, language, import).map_err(|reason| EditError::InvalidEdit { reason })
+}
+
+fn write_ensured(workdir: &Path, path: &str, ensured: Ensured, what: &str) -> EditResult<String> {
+    if let Ensured::Inserted { content, .. } | Ensured::Merged { content, .. } = &ensured {
+        write_file(workdir, path, content)?;
+    }
+    Ok(ensured.describe(what))
+}
+
+// NOTE(angeldev)
+// Applies an ensure_line operation.
+pub fn apply_ensure_line(
+    workdir: &Path,
+    path: &str,
+    line: &str,
+    after: Option<&str>,
+    section: Option<&str>,
+) -> EditResult<String> {
+    let content = read_file(workdir, path)?;
+    let ensured = ensure_line(&content, path, line, after, section)?;
+    write_ensured(workdir, path, ensured, "Line")
+}
+
+// NOTE(angeldev)
+// Applies an ensure_import operation.
+pub fn apply_ensure_import(workdir: &Path, path: &str, import: &str) -> EditResult<String> {
+    let content = read_file(workdir, path)?;
+    let ensured = ensure_import(&content, path, import)?;
+    write_ensured(workdir, path, ensured, "Import")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    const GITIGNORE: &str = "# Logs\n*.log\n\n# Build\ndist/\n";
+
+    #[test]
+    fn test_ensure_line_is_idempotent() {
+        let dir = tempdir().unwrap();
+        let path = ".gitignore";
+        fs::write(dir.path().join(path), GITIGNORE).unwrap();
+
+        let first = apply_ensure_line(dir.path(), path, ".env", None, None).unwrap();
+        let second = apply_ensure_line(dir.path(), path, "  .env", None, None).unwrap();
+        assert_eq!(first, "Line added at line 6");
+        assert_eq!(second, "Line already present at line 6");
+
+        let content = fs::read_to_string(dir.path().join(path)).unwrap();
+        assert_eq!(content, format!("{}.env\n", GITIGNORE));
+    }
+
+    #[test]
+    fn test_ensure_line_section_and_after() {
+        let ensured = ensure_line(GITIGNORE, ".gitignore", "npm-debug.log*", None, Some("# Logs")).unwrap();
+        assert_eq!(
+            ensured,
+            Ensured::Inserted {
+                content: "# Logs\n*.log\nnpm-debug.log*\n\n# Build\ndist/\n".to_string(),
+                line: 3,
+            }
+        );
+
+        let ensured = ensure_line(GITIGNORE, ".gitignore", ".env", None, Some("# Secrets")).unwrap();
+        assert!(matches!(ensured, Ensured::Inserted { ref content, line: 8 } if content.ends_with("dist/\n\n# Secrets\n.env\n")));
+
+        let ensured = ensure_line(GITIGNORE, ".gitignore", "build/", Some("dist/"), None).unwrap();
+        assert!(matches!(ensured, Ensured::Inserted { ref content, .. } if content.ends_with("dist/\nbuild/\n")));
+
+        let err = ensure_line(GITIGNORE, ".gitignore", "x", Some("missing"), None).unwrap_err();
+        assert!(matches!(err, EditError::AnchorNotFound { .. }));
+    }
+
+    #[test]
+    fn test_apply_ensure_import() {
+        let dir = tempdir().unwrap();
+        let path = "src/app.ts";
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join(path), "import { a } from './a';\n\nexport const x = a;\n").unwrap();
+
+        apply_ensure_import(dir.path(), path, "import { b } from './a'").unwrap();
+        let again = apply_ensure_import(dir.path(), path, "import { b } from './a'").unwrap();
+        assert_eq!(again, "Import already present at line 1");
+
+        let content = fs::read_to_string(dir.path().join(path)).unwrap();
+        assert_eq!(content, "import { a, b } from './a';\n\nexport const x = a;\n");
+
+        let err = apply_ensure_import(dir.path(), "README.md", "import x").unwrap_err();
+        assert!(matches!(err, EditError::FileNotFound { .. } | EditError::InvalidEdit { .. }));
+    }
+}
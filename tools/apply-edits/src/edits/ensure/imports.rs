@@ -0,0 +1,746 @@
+// NOTE(angeldev)
+// Import block handling for ensure_import.
+// Finds the leading import statements of a JS/TS, Python, Rust or Go file, decides
+// whether a requested import is already covered, and otherwise either merges the
+// missing names into an existing list (`{ a, b }`, `from m import a, b`) or inserts
+// the statement into the block at its sorted position.
+
+use super::Ensured;
+use crate::language::Language;
+
+// NOTE(angeldev)
+// The parts of an import statement that decide duplicates and placement.
+#[derive(Debug, Clone)]
+struct Parsed {
+    // Text before the name list; statements merge only when heads match
+    // ("import", "import type", "from", "use", "pub use", a Go alias)
+    head: String,
+    // Module imported from
+    key: String,
+    // Key the block is sorted by
+    sort_key: String,
+    // Names of list-style imports ({ a, b } / from m import a, b / use m::{a, b})
+    names: Option<Vec<String>>,
+}
+
+// NOTE(angeldev)
+// An import statement occupying lines start..end (0-indexed, end exclusive).
+#[derive(Debug)]
+struct Stmt {
+    start: usize,
+    end: usize,
+    parsed: Parsed,
+    // Go: a line inside an `import ( ... )` block
+    in_block: bool,
+}
+
+// NOTE(angeldev)
+// The import section of a file.
+// `insert_at` is where the first import goes when the file has none.
+struct Block {
+    stmts: Vec<Stmt>,
+    insert_at: usize,
+}
+
+// NOTE(angeldev)
+// Returns true for languages ensure_import understands.
+pub(super) fn supports(language: Language) -> bool {
+    matches!(
+        language,
+        Language::JavaScript | Language::TypeScript | Language::Python | Language::Rust | Language::Go
+    )
+}
+
+// NOTE(angeldev)
+// Ensures `import` is present in content. Errors are reasons for InvalidEdit.
+pub(super) fn ensure(content: &str, language: Language, import: &str) -> Result<Ensured, String> {
+    let request = normalize_request(language, import.trim());
+    let import = request.as_str();
+    let wanted = parse(language, import)
+        .ok_or_else(|| format!("'{}' is not a {} import statement", import, language.name()))?;
+
+    let lines: Vec<&str> = content.lines().collect();
+    let block = scan(language, &lines);
+
+    let same: Vec<&Stmt> = block
+        .stmts
+        .iter()
+        .filter(|s| s.parsed.key == wanted.key && s.parsed.head == wanted.head)
+        .collect();
+
+    // Work out which of the requested names are still missing
+    let missing = match &wanted.names {
+        None => {
+            if let Some(stmt) = same.first() {
+                return Ok(Ensured::Present { line: stmt.start + 1 });
+            }
+            None
+        }
+        Some(names) => {
+            let present: Vec<String> = same.iter().filter_map(|s| s.parsed.names.clone()).flatten().collect();
+            let missing: Vec<String> = names.iter().filter(|n| !present.contains(n)).cloned().collect();
+            if missing.is_empty() {
+                let line = same.iter().find(|s| s.parsed.names.is_some()).map(|s| s.start + 1).unwrap_or(1);
+                return Ok(Ensured::Present { line });
+            }
+            Some(missing)
+        }
+    };
+
+    // Merge into an existing list of the same module when there is one
+    if let Some(missing) = &missing {
+        for stmt in same.iter().filter(|s| s.parsed.names.is_some()) {
+            let text = lines[stmt.start..stmt.end].join("\n");
+            if let Some(merged) = merge_names(language, &text, missing) {
+                let mut new_lines: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+                new_lines.splice(stmt.start..stmt.end, merged.lines().map(|l| l.to_string()));
+                return Ok(Ensured::Merged {
+                    content: join_lines(content, new_lines),
+                    line: stmt.start + 1,
+                });
+            }
+        }
+    }
+
+    // Otherwise insert a statement for whatever is missing
+    let statement = match &missing {
+        Some(names) if Some(names) != wanted.names.as_ref() => render(language, &wanted, import, names),
+        _ => restyle(language, import, &block.stmts, &lines),
+    };
+
+    let (at, new_stmt_lines) = placement(language, &block, &lines, &wanted, &statement);
+    let mut new_lines: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+    let first_code = new_stmt_lines.iter().position(|l| !l.trim().is_empty()).unwrap_or(0);
+    new_lines.splice(at..at, new_stmt_lines);
+
+    Ok(Ensured::Inserted {
+        content: join_lines(content, new_lines),
+        line: at + first_code + 1,
+    })
+}
+
+// NOTE(angeldev)
+// Go imports may be given as `fmt`, `"fmt"`, `import "fmt"` or `log "x/log"`.
+fn normalize_request(language: Language, import: &str) -> String {
+    if language != Language::Go {
+        return import.to_string();
+    }
+    let spec = import.strip_prefix("import").map(str::trim).unwrap_or(import);
+    if spec.contains('"') {
+        spec.to_string()
+    } else {
+        format!("\"{}\"", spec)
+    }
+}
+
+// NOTE(angeldev)
+// Parses one (possibly multi-line) import statement.
+fn parse(language: Language, text: &str) -> Option<Parsed> {
+    let flat = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    match language {
+        Language::JavaScript | Language::TypeScript => parse_js(&flat),
+        Language::Python => parse_python(&flat),
+        Language::Rust => parse_rust(&flat),
+        Language::Go => {
+            let spec = flat.strip_prefix("import ").unwrap_or(&flat);
+            let open = spec.find('"')?;
+            let close = spec[open + 1..].find('"')? + open + 1;
+            let key = spec[open + 1..close].to_string();
+            Some(Parsed {
+                head: spec[..open].trim().to_string(),
+                sort_key: key.clone(),
+                key,
+                names: None,
+            })
+        }
+        _ => None,
+    }
+}
+
+fn parse_js(flat: &str) -> Option<Parsed> {
+    let key = last_quoted(flat)?;
+    let braces = |s: &str| -> Option<Vec<String>> {
+        let open = s.find('{')?;
+        let close = s[open..].find('}')? + open;
+        Some(split_items(&s[open + 1..close]))
+    };
+
+    let (head, names) = if let Some(pos) = flat.find(" require(") {
+        // const { a, b } = require('m')
+        let binding = flat[..pos].trim_end_matches('=').trim();
+        match binding.find('{') {
+            Some(open) => (binding[..open].trim().to_string(), braces(binding)),
+            None => (binding.to_string(), None),
+        }
+    } else if let Some(pos) = flat.rfind(" from ") {
+        let clause = &flat[..pos];
+        match clause.find('{') {
+            Some(open) => (clause[..open].trim().trim_end_matches(',').trim().to_string(), braces(clause)),
+            None => (clause.trim().to_string(), None),
+        }
+    } else if flat.starts_with("import") {
+        // Side-effect import: import 'm'
+        ("import".to_string(), None)
+    } else {
+        return None;
+    };
+
+    Some(Parsed {
+        head,
+        sort_key: key.clone(),
+        key,
+        names,
+    })
+}
+
+fn parse_python(flat: &str) -> Option<Parsed> {
+    if let Some(rest) = flat.strip_prefix("from ") {
+        let (module, names) = rest.split_once(" import ")?;
+        let names = strip_line_comments(names, "#");
+        let names = names.trim().trim_start_matches('(').trim_end_matches(')');
+        return Some(Parsed {
+            head: "from".to_string(),
+            key: module.trim().to_string(),
+            sort_key: module.trim().to_string(),
+            names: Some(split_items(names)),
+        });
+    }
+
+    let rest = flat.strip_prefix("import ")?;
+    let rest = rest.split('#').next().unwrap_or(rest).trim();
+    Some(Parsed {
+        head: "import".to_string(),
+        key: rest.to_string(),
+        sort_key: rest.split([' ', ',']).next().unwrap_or(rest).to_string(),
+        names: None,
+    })
+}
+
+fn parse_rust(flat: &str) -> Option<Parsed> {
+    let use_pos = if flat.starts_with("use ") { 0 } else { flat.find(" use ")? + 1 };
+    let head = flat[..use_pos + 3].to_string();
+    let path = flat[use_pos + 4..].split(';').next()?.trim();
+    let path = strip_line_comments(path, "//");
+    let path = path.trim();
+
+    let (key, names) = match top_level_list(path) {
+        Some(open) => {
+            let inner = path[open + 1..].strip_suffix('}')?;
+            (path[..open - 2].to_string(), Some(split_items(inner)))
+        }
+        None => match path.rfind("::") {
+            Some(pos) => (path[..pos].to_string(), Some(vec![path[pos + 2..].to_string()])),
+            None => (path.to_string(), None),
+        },
+    };
+
+    Some(Parsed {
+        head,
+        key,
+        sort_key: path.replace(' ', ""),
+        names,
+    })
+}
+
+// Offset of the `{` that opens a Rust use-list (`a::b::{c, d}`)
+fn top_level_list(path: &str) -> Option<usize> {
+    path.find("::{").map(|pos| pos + 2).filter(|_| path.ends_with('}'))
+}
+
+// NOTE(angeldev)
+// Finds the import statements at the top of the file.
+// Leading comments, shebangs, docstrings, directives ('use strict', #![attr],
+// package clauses) are skipped; the block ends at the first other code line.
+fn scan(language: Language, lines: &[&str]) -> Block {
+    let comment = match language {
+        Language::Python => "#",
+        _ => "//",
+    };
+    let is_comment = |t: &str| t.starts_with(comment) || t.starts_with("/*") || t.starts_with('*');
+
+    // Prolog: only comment groups followed by a blank line belong to it, so a
+    // doc comment sitting on the first function is never split from it
+    let mut i = 0;
+    let mut prolog_end = 0;
+    while i < lines.len() {
+        let t = lines[i].trim();
+        if t.is_empty() {
+            i += 1;
+            continue;
+        }
+        if is_comment(t) {
+            let start = i;
+            while i < lines.len() && is_comment(lines[i].trim()) {
+                i += 1;
+            }
+            if i == lines.len() || lines[i].trim().is_empty() || language == Language::Go {
+                prolog_end = i;
+            } else {
+                i = start;
+                break;
+            }
+            continue;
+        }
+        if let Some(end) = directive_end(language, lines, i) {
+            i = end;
+            prolog_end = end;
+            continue;
+        }
+        break;
+    }
+
+    let mut stmts = Vec::new();
+    let mut insert_at = prolog_end;
+    let mut j = i;
+    while j < lines.len() {
+        let t = lines[j].trim();
+        if t.is_empty() || is_comment(t) {
+            j += 1;
+            continue;
+        }
+
+        if language == Language::Go && t == "import (" {
+            let close = (j + 1..lines.len()).find(|&k| lines[k].trim() == ")").unwrap_or(lines.len());
+            for (k, line) in lines.iter().enumerate().take(close).skip(j + 1) {
+                let item = line.trim();
+                if item.is_empty() || is_comment(item) {
+                    continue;
+                }
+                if let Some(parsed) = parse(language, item) {
+                    stmts.push(Stmt {
+                        start: k,
+                        end: k + 1,
+                        parsed,
+                        in_block: true,
+                    });
+                }
+            }
+            j = close + 1;
+            continue;
+        }
+
+        if is_import_start(language, t) {
+            let end = statement_end(language, lines, j);
+            match parse(language, &lines[j..end].join("\n")) {
+                Some(parsed) => {
+                    stmts.push(Stmt {
+                        start: j,
+                        end,
+                        parsed,
+                        in_block: false,
+                    });
+                    j = end;
+                    continue;
+                }
+                None => break,
+            }
+        }
+
+        // Module declarations usually sit above the use block
+        let rust_item = language == Language::Rust
+            && (t.starts_with("#[")
+                || t.starts_with("extern crate ")
+                || ((t.starts_with("mod ") || t.starts_with("pub mod ") || t.starts_with("pub(crate) mod "))
+                    && t.ends_with(';')));
+        if rust_item {
+            j += 1;
+            if stmts.is_empty() {
+                insert_at = j;
+            }
+            continue;
+        }
+        break;
+    }
+
+    Block { stmts, insert_at }
+}
+
+// Returns the line after a prolog directive starting at line i, if there is one
+fn directive_end(language: Language, lines: &[&str], i: usize) -> Option<usize> {
+    let t = lines[i].trim();
+    match language {
+        _ if i == 0 && t.starts_with("#!") && !t.starts_with("#![") => Some(1),
+        Language::Go if t.starts_with("package ") => Some(i + 1),
+        Language::Rust if t.starts_with("#![") => Some(i + 1),
+        Language::JavaScript | Language::TypeScript => {
+            let literal = t.trim_end_matches(';');
+            let quoted = literal.len() >= 2
+                && (literal.starts_with('\'') || literal.starts_with('"'))
+                && literal.ends_with(&literal[..1]);
+            quoted.then_some(i + 1)
+        }
+        Language::Python => {
+            let delim = ["\"\"\"", "'''"].into_iter().find(|d| t.trim_start_matches(['r', 'u']).starts_with(d))?;
+            let body = &t[t.find(delim)? + 3..];
+            if body.contains(delim) {
+                return Some(i + 1);
+            }
+            (i + 1..lines.len()).find(|&k| lines[k].contains(delim)).map(|k| k + 1)
+        }
+        _ => None,
+    }
+}
+
+fn is_import_start(language: Language, t: &str) -> bool {
+    match language {
+        Language::JavaScript | Language::TypeScript => {
+            t.starts_with("import ")
+                || t.starts_with("import{")
+                || t.starts_with("import'")
+                || t.starts_with("import\"")
+                || (t.starts_with("export ") && t.contains(" from "))
+                || ((t.starts_with("const ") || t.starts_with("let ") || t.starts_with("var ")) && t.contains("require("))
+        }
+        Language::Python => t.starts_with("import ") || t.starts_with("from "),
+        Language::Rust => t.starts_with("use ") || (t.starts_with("pub") && t.contains(" use ")),
+        Language::Go => t.starts_with("import "),
+        _ => false,
+    }
+}
+
+// Line after the end of a statement starting at line j (brackets balanced)
+fn statement_end(language: Language, lines: &[&str], j: usize) -> usize {
+    let mut depth = 0i32;
+    for (k, line) in lines.iter().enumerate().skip(j) {
+        for c in line.chars() {
+            match c {
+                '{' | '(' => depth += 1,
+                '}' | ')' => depth -= 1,
+                _ => {}
+            }
+        }
+        let t = line.trim_end();
+        let complete = match language {
+            Language::Rust => t.ends_with(';'),
+            Language::Python => !t.ends_with('\\'),
+            _ => true,
+        };
+        if depth <= 0 && complete {
+            return k + 1;
+        }
+    }
+    lines.len()
+}
+
+// NOTE(angeldev)
+// Adds names to the list of an existing statement, keeping the list sorted if
+// it was and keeping its layout (one line, or one name per line).
+// Returns None when the statement has no list to extend or the list has comments.
+fn merge_names(language: Language, text: &str, missing: &[String]) -> Option<String> {
+    let (open, close) = match language {
+        Language::JavaScript | Language::TypeScript => {
+            let open = text.find('{')? + 1;
+            (open, text[open..].find('}')? + open)
+        }
+        Language::Rust => {
+            let use_list = text.find("::{")? + 3;
+            (use_list, text.rfind('}')?)
+        }
+        Language::Python => {
+            let after = text.find(" import ")? + 8;
+            let rest = &text[after..];
+            match rest.trim_start().strip_prefix('(') {
+                Some(_) => {
+                    let open = after + rest.find('(')? + 1;
+                    (open, text.rfind(')')?)
+                }
+                None if !text.contains('\n') && !text.contains('#') => (after, text.len()),
+                None => return None,
+            }
+        }
+        _ => return None,
+    };
+    if close < open {
+        return None;
+    }
+
+    let inner = &text[open..close];
+    if inner.contains("//") || inner.contains('#') || inner.contains("/*") {
+        return None;
+    }
+
+    let mut items = split_items(inner);
+    let sorted = is_sorted(&items);
+    for name in missing {
+        let at = if sorted {
+            items.iter().position(|i| i.to_lowercase() > name.to_lowercase()).unwrap_or(items.len())
+        } else {
+            items.len()
+        };
+        items.insert(at, name.clone());
+    }
+
+    let rendered = if inner.contains('\n') {
+        let indent: String = inner
+            .lines()
+            .skip(1)
+            .find(|l| !l.trim().is_empty())
+            .map(|l| l[..l.len() - l.trim_start().len()].to_string())
+            .unwrap_or_else(|| "    ".to_string());
+        let close_indent = inner.rsplit('\n').next().unwrap_or("");
+        let trailing = if inner.trim_end().ends_with(',') { "," } else { "" };
+        let body: Vec<String> = items.iter().map(|i| format!("{}{}", indent, i)).collect();
+        format!("\n{}{}\n{}", body.join(",\n"), trailing, close_indent)
+    } else {
+        let pad = if inner.starts_with(' ') { " " } else { "" };
+        format!("{}{}{}", pad, items.join(", "), pad)
+    };
+
+    Some(format!("{}{}{}", &text[..open], rendered, &text[close..]))
+}
+
+// NOTE(angeldev)
+// Renders a statement importing only the missing names (the rest are already imported).
+fn render(language: Language, wanted: &Parsed, import: &str, names: &[String]) -> String {
+    let list = names.join(", ");
+    match language {
+        Language::Python => format!("from {} import {}", wanted.key, list),
+        Language::Rust if names.len() == 1 => format!("{} {}::{};", wanted.head, wanted.key, list),
+        Language::Rust => format!("{} {}::{{{}}};", wanted.head, wanted.key, list),
+        _ => {
+            let quote = if import.contains('\'') { '\'' } else { '"' };
+            let semi = if import.trim_end().ends_with(';') { ";" } else { "" };
+            if import.contains("require(") {
+                format!("{} {{ {} }} = require({q}{}{q}){}", wanted.head, list, wanted.key, semi, q = quote)
+            } else {
+                format!("{} {{ {} }} from {q}{}{q}{}", wanted.head, list, wanted.key, semi, q = quote)
+            }
+        }
+    }
+}
+
+// NOTE(angeldev)
+// Adjusts a requested statement to the file's style: semicolons and quote
+// character for JS/TS, a terminating semicolon for Rust.
+fn restyle(language: Language, import: &str, stmts: &[Stmt], lines: &[&str]) -> String {
+    match language {
+        Language::Rust if !import.ends_with(';') => format!("{};", import),
+        Language::JavaScript | Language::TypeScript if !stmts.is_empty() => {
+            let mut text = import.to_string();
+            let last_lines: Vec<&str> = stmts.iter().map(|s| lines[s.end - 1].trim_end()).collect();
+            let semicolons = last_lines.iter().filter(|l| l.ends_with(';')).count() * 2 >= last_lines.len();
+            if semicolons && !text.ends_with(';') {
+                text.push(';');
+            } else if !semicolons {
+                text = text.trim_end_matches(';').to_string();
+            }
+
+            let first = lines[stmts[0].start..stmts[0].end].join("\n");
+            let preferred = first.chars().find(|c| *c == '\'' || *c == '"');
+            if let Some(quote) = preferred {
+                let other = if quote == '\'' { '"' } else { '\'' };
+                if text.matches(other).count() == 2 && !text.contains(quote) {
+                    text = text.replace(other, &quote.to_string());
+                }
+            }
+            text
+        }
+        _ => import.to_string(),
+    }
+}
+
+// NOTE(angeldev)
+// Picks where a new statement goes and renders its lines.
+// Blank lines split the block into groups; the statement joins the group whose
+// keys share the longest prefix with it, at its sorted position if the group is sorted.
+fn placement(language: Language, block: &Block, lines: &[&str], wanted: &Parsed, statement: &str) -> (usize, Vec<String>) {
+    let go_line = |in_block: bool, indent: &str| -> String {
+        if in_block {
+            format!("{}{}", indent, statement)
+        } else {
+            format!("import {}", statement)
+        }
+    };
+
+    if block.stmts.is_empty() {
+        let at = block.insert_at;
+        let mut new_lines = Vec::new();
+        if at > 0 && !lines[at - 1].trim().is_empty() {
+            new_lines.push(String::new());
+        }
+        if language == Language::Go {
+            new_lines.push(go_line(false, ""));
+        } else {
+            new_lines.extend(statement.lines().map(|l| l.to_string()));
+        }
+        if at < lines.len() && !lines[at].trim().is_empty() {
+            new_lines.push(String::new());
+        }
+        return (at, new_lines);
+    }
+
+    // Split statements into groups of consecutive lines
+    let mut groups: Vec<Vec<&Stmt>> = Vec::new();
+    for stmt in &block.stmts {
+        let joined = groups.last().and_then(|g| g.last()).is_some_and(|prev| {
+            prev.in_block == stmt.in_block
+                && lines[prev.end..stmt.start]
+                    .iter()
+                    .all(|l| l.trim().starts_with("//") || l.trim().starts_with('#'))
+        });
+        if joined {
+            groups.last_mut().expect("joined implies a group").push(stmt);
+        } else {
+            groups.push(vec![stmt]);
+        }
+    }
+
+    let wanted_key = wanted.sort_key.to_lowercase();
+    let prefix_len = |s: &Stmt| common_prefix(&s.parsed.sort_key.to_lowercase(), &wanted_key);
+    // Ties go to the group with more statements of the same kind (`import` vs
+    // `from`), then to the later group (max_by_key returns the last maximum)
+    let kind = |head: &str| head.split(' ').next().unwrap_or("").to_string();
+    let group = groups
+        .iter()
+        .max_by_key(|g| {
+            let prefix = g.iter().map(|s| prefix_len(s)).max().unwrap_or(0);
+            let same_kind = g.iter().filter(|s| kind(&s.parsed.head) == kind(&wanted.head)).count();
+            (prefix, same_kind)
+        })
+        .expect("at least one group");
+
+    let keys: Vec<String> = group.iter().map(|s| s.parsed.sort_key.clone()).collect();
+    let before = if is_sorted(&keys) {
+        group.iter().find(|s| s.parsed.sort_key.to_lowercase() > wanted_key)
+    } else {
+        None
+    };
+
+    let at = match before {
+        Some(stmt) => {
+            // Keep comments that belong to the following statement above it
+            let mut at = stmt.start;
+            while at > 0 && lines[at - 1].trim().starts_with("//") && !group.iter().any(|s| s.end == at) {
+                at -= 1;
+            }
+            at
+        }
+        None => group.last().expect("groups are never empty").end,
+    };
+
+    let anchor = before.unwrap_or_else(|| group.last().expect("groups are never empty"));
+    let new_lines = if language == Language::Go {
+        let indent = &lines[anchor.start][..lines[anchor.start].len() - lines[anchor.start].trim_start().len()];
+        vec![go_line(anchor.in_block, indent)]
+    } else {
+        statement.lines().map(|l| l.to_string()).collect()
+    };
+    (at, new_lines)
+}
+
+fn common_prefix(a: &str, b: &str) -> usize {
+    a.chars().zip(b.chars()).take_while(|(x, y)| x == y).count()
+}
+
+fn is_sorted(items: &[String]) -> bool {
+    items.windows(2).all(|w| w[0].to_lowercase() <= w[1].to_lowercase())
+}
+
+// Splits a name list on commas outside nested braces
+fn split_items(inner: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut depth = 0;
+    let mut current = String::new();
+    for c in inner.chars() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ',' if depth == 0 => {
+                items.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    items.push(current);
+    items
+        .iter()
+        .map(|i| i.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|i| !i.is_empty())
+        .collect()
+}
+
+fn strip_line_comments(text: &str, marker: &str) -> String {
+    text.lines()
+        .map(|l| l.split(marker).next().unwrap_or(l))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// The last quoted string in a statement (the module specifier)
+fn last_quoted(flat: &str) -> Option<String> {
+    let close = flat.rfind(['\'', '"'])?;
+    let quote = flat[close..].chars().next()?;
+    let open = flat[..close].rfind(quote)?;
+    Some(flat[open + 1..close].to_string())
+}
+
+fn join_lines(original: &str, lines: Vec<String>) -> String {
+    let mut out = lines.join("\n");
+    if original.ends_with('\n') || original.is_empty() {
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn added(content: &str, language: Language, import: &str) -> String {
+        match ensure(content, language, import).unwrap() {
+            Ensured::Inserted { content, .. } | Ensured::Merged { content, .. } => content,
+            Ensured::Present { line } => panic!("unexpectedly present at line {}", line),
+        }
+    }
+
+    #[test]
+    fn test_javascript() {
+        let src = "'use strict';\nimport a from 'a';\nimport { x, z } from 'c';\n\nconst v = 1;\n";
+        assert_eq!(
+            added(src, Language::JavaScript, "import b from \"b\""),
+            "'use strict';\nimport a from 'a';\nimport b from 'b';\nimport { x, z } from 'c';\n\nconst v = 1;\n"
+        );
+        assert_eq!(
+            added(src, Language::JavaScript, "import { y } from 'c';"),
+            "'use strict';\nimport a from 'a';\nimport { x, y, z } from 'c';\n\nconst v = 1;\n"
+        );
+        assert!(matches!(
+            ensure(src, Language::JavaScript, "import { z } from \"c\""),
+            Ok(Ensured::Present { line: 3 })
+        ));
+    }
+
+    #[test]
+    fn test_python() {
+        let src = "\"\"\"Module docs.\"\"\"\nimport os\nimport sys\n\nfrom app.models import (\n    User,\n)\n\n\ndef main():\n    pass\n";
+        assert!(added(src, Language::Python, "import re").contains("import os\nimport re\nimport sys\n"));
+        assert!(added(src, Language::Python, "from app.models import Group")
+            .contains("from app.models import (\n    Group,\n    User,\n)\n"));
+        assert!(matches!(ensure(src, Language::Python, "import sys"), Ok(Ensured::Present { line: 3 })));
+
+        let empty = "\"\"\"Docs.\"\"\"\n\ndef main():\n    pass\n";
+        assert_eq!(added(empty, Language::Python, "import os"), "\"\"\"Docs.\"\"\"\n\nimport os\n\ndef main():\n    pass\n");
+    }
+
+    #[test]
+    fn test_rust() {
+        let src = "// NOTE: header\n\npub mod a;\n\nuse crate::error::{EditError, EditResult};\nuse std::path::Path;\n\nfn f() {}\n";
+        assert!(added(src, Language::Rust, "use std::fs")
+            .contains("use crate::error::{EditError, EditResult};\nuse std::fs;\nuse std::path::Path;\n"));
+        assert!(added(src, Language::Rust, "use crate::error::ClosestMatch;")
+            .contains("use crate::error::{ClosestMatch, EditError, EditResult};\n"));
+        assert!(matches!(ensure(src, Language::Rust, "use std::path::Path;"), Ok(Ensured::Present { .. })));
+    }
+
+    #[test]
+    fn test_go() {
+        let src = "package main\n\nimport (\n\t\"fmt\"\n\t\"strings\"\n)\n\nfunc main() {}\n";
+        assert!(added(src, Language::Go, "os").contains("\t\"fmt\"\n\t\"os\"\n\t\"strings\"\n"));
+        assert!(matches!(ensure(src, Language::Go, "import \"fmt\""), Ok(Ensured::Present { line: 4 })));
+
+        let none = "package main\n\nfunc main() {}\n";
+        assert_eq!(added(none, Language::Go, "\"fmt\""), "package main\n\nimport \"fmt\"\n\nfunc main() {}\n");
+    }
+}
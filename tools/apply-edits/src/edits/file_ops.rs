@@ -1,39 +1,88 @@
 // NOTE(jimmylee)
 // File operations for edits.
 // Handles create, append, and prepend operations.
+// NOTE(angeldev): Also handles rename and copy, which move whole files between paths.
 
-use crate::edits::{read_file, write_file};
+use crate::edits::{read_file, write_file, CreateMode};
 use crate::error::{EditError, EditResult};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 // NOTE(jimmylee)
 // Applies a create operation.
 // Creates a new file with the given content.
 // Creates parent directories if they don't exist.
-pub fn apply_create(workdir: &Path, path: &str, content: &str) -> EditResult<String> {
+// NOTE(angeldev): mode controls existing files; executable/permissions set the mode bits.
+pub fn apply_create(
+    workdir: &Path,
+    path: &str,
+    content: &str,
+    mode: CreateMode,
+    executable: bool,
+    permissions: Option<u32>,
+) -> EditResult<String> {
     let file_path = workdir.join(path);
+    let existed = file_path.exists();
 
-    // Create parent directories if needed
-    if let Some(parent) = file_path.parent() {
-        if !parent.exists() {
-            fs::create_dir_all(parent).map_err(|e| EditError::DirectoryError {
-                path: parent.display().to_string(),
-                reason: e.to_string(),
-            })?;
+    if existed {
+        match mode {
+            CreateMode::CreateNew => {
+                return Err(EditError::FileExists {
+                    path: path.to_string(),
+                })
+            }
+            CreateMode::IfMissing => return Ok("File already exists (left unchanged)".to_string()),
+            CreateMode::Overwrite => {}
         }
     }
 
+    // Create parent directories if needed
+    create_parent_dirs(&file_path)?;
+
     // Write the file
     fs::write(&file_path, content).map_err(|e| EditError::WriteError {
         path: path.to_string(),
         reason: e.to_string(),
     })?;
 
+    if executable || permissions.is_some() {
+        set_mode_bits(&file_path, path, executable, permissions)?;
+    }
+
     let lines = content.lines().count();
     let bytes = content.len();
 
-    Ok(format!("Created file ({} lines, {} bytes)", lines, bytes))
+    if existed {
+        Ok(format!("Overwrote file ({} lines, {} bytes)", lines, bytes))
+    } else {
+        Ok(format!("Created file ({} lines, {} bytes)", lines, bytes))
+    }
+}
+
+// NOTE(angeldev)
+// Sets a file's permission bits: explicit permissions replace the current mode,
+// executable adds the execute bits on top. No-op on platforms without Unix modes.
+#[cfg(unix)]
+fn set_mode_bits(file_path: &Path, path: &str, executable: bool, permissions: Option<u32>) -> EditResult<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let write_error = |e: std::io::Error| EditError::WriteError {
+        path: path.to_string(),
+        reason: format!("Failed to set permissions: {}", e),
+    };
+
+    let current = fs::metadata(file_path).map_err(write_error)?.permissions().mode() & 0o7777;
+    let mut mode = permissions.unwrap_or(current);
+    if executable {
+        mode |= 0o111;
+    }
+
+    fs::set_permissions(file_path, fs::Permissions::from_mode(mode)).map_err(write_error)
+}
+
+#[cfg(not(unix))]
+fn set_mode_bits(_file_path: &Path, _path: &str, _executable: bool, _permissions: Option<u32>) -> EditResult<()> {
+    Ok(())
 }
 
 // NOTE(jimmylee)
@@ -74,6 +123,76 @@ pub fn apply_prepend(workdir: &Path, path: &str, content: &str) -> EditResult<St
     Ok(format!("Prepended {} line(s)", prepended_lines))
 }
 
+// NOTE(angeldev)
+// Applies a rename operation.
+// Moves a file to a new path, creating parent directories as needed.
+// An existing destination is only replaced when overwrite is set.
+pub fn apply_rename(workdir: &Path, from: &str, to: &str, overwrite: bool) -> EditResult<String> {
+    let (source, destination) = prepare_move(workdir, from, to, overwrite)?;
+
+    fs::rename(&source, &destination).map_err(|e| EditError::WriteError {
+        path: to.to_string(),
+        reason: e.to_string(),
+    })?;
+
+    Ok(format!("Renamed to {}", to))
+}
+
+// NOTE(angeldev)
+// Applies a copy operation.
+// Copies a file to a new path, creating parent directories as needed.
+pub fn apply_copy(workdir: &Path, from: &str, to: &str) -> EditResult<String> {
+    let (source, destination) = prepare_move(workdir, from, to, false)?;
+
+    let bytes = fs::copy(&source, &destination).map_err(|e| EditError::WriteError {
+        path: to.to_string(),
+        reason: e.to_string(),
+    })?;
+
+    Ok(format!("Copied to {} ({} bytes)", to, bytes))
+}
+
+// NOTE(angeldev)
+// Validates the source and destination of a rename or copy.
+// Returns both full paths once the destination's parent directory exists.
+fn prepare_move(workdir: &Path, from: &str, to: &str, overwrite: bool) -> EditResult<(PathBuf, PathBuf)> {
+    let source = workdir.join(from);
+    let destination = workdir.join(to);
+
+    if !source.is_file() {
+        return Err(EditError::FileNotFound {
+            path: from.to_string(),
+        });
+    }
+    if source == destination {
+        return Err(EditError::InvalidEdit {
+            reason: format!("Source and destination are the same path: {}", from),
+        });
+    }
+    if destination.exists() && !overwrite {
+        return Err(EditError::FileExists {
+            path: to.to_string(),
+        });
+    }
+
+    create_parent_dirs(&destination)?;
+    Ok((source, destination))
+}
+
+// NOTE(angeldev)
+// Creates the parent directories of a file path if they don't exist.
+fn create_parent_dirs(file_path: &Path) -> EditResult<()> {
+    if let Some(parent) = file_path.parent() {
+        if !parent.exists() {
+            fs::create_dir_all(parent).map_err(|e| EditError::DirectoryError {
+                path: parent.display().to_string(),
+                reason: e.to_string(),
+            })?;
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -84,7 +203,7 @@ mod tests {
         let dir = tempdir().unwrap();
         let path = "test.txt";
 
-        let result = apply_create(dir.path(), path, "hello world");
+        let result = apply_create(dir.path(), path, "hello world", CreateMode::Overwrite, false, None);
         assert!(result.is_ok());
 
         let content = fs::read_to_string(dir.path().join(path)).unwrap();
@@ -96,11 +215,44 @@ mod tests {
         let dir = tempdir().unwrap();
         let path = "foo/bar/test.txt";
 
-        let result = apply_create(dir.path(), path, "content");
+        let result = apply_create(dir.path(), path, "content", CreateMode::Overwrite, false, None);
         assert!(result.is_ok());
         assert!(dir.path().join(path).exists());
     }
 
+    #[test]
+    fn test_create_modes() {
+        let dir = tempdir().unwrap();
+        let path = "test.txt";
+        fs::write(dir.path().join(path), "original").unwrap();
+
+        let result = apply_create(dir.path(), path, "new", CreateMode::CreateNew, false, None);
+        assert!(matches!(result, Err(EditError::FileExists { .. })));
+
+        let result = apply_create(dir.path(), path, "new", CreateMode::IfMissing, false, None);
+        assert!(result.is_ok());
+        assert_eq!(fs::read_to_string(dir.path().join(path)).unwrap(), "original");
+
+        let result = apply_create(dir.path(), "other.txt", "new", CreateMode::IfMissing, false, None);
+        assert!(result.is_ok());
+        assert_eq!(fs::read_to_string(dir.path().join("other.txt")).unwrap(), "new");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_create_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let mode_of = |p: &str| fs::metadata(dir.path().join(p)).unwrap().permissions().mode() & 0o777;
+
+        apply_create(dir.path(), "run.sh", "#!/bin/sh\n", CreateMode::Overwrite, false, Some(0o640)).unwrap();
+        assert_eq!(mode_of("run.sh"), 0o640);
+
+        apply_create(dir.path(), "run.sh", "#!/bin/sh\n", CreateMode::Overwrite, true, Some(0o640)).unwrap();
+        assert_eq!(mode_of("run.sh"), 0o751);
+    }
+
     #[test]
     fn test_append() {
         let dir = tempdir().unwrap();
@@ -140,4 +292,42 @@ mod tests {
         assert!(content.starts_with("line1"));
         assert!(content.contains("line2"));
     }
+
+    #[test]
+    fn test_rename() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("old.txt"), "content").unwrap();
+
+        let result = apply_rename(dir.path(), "old.txt", "moved/new.txt", false);
+        assert!(result.is_ok());
+        assert!(!dir.path().join("old.txt").exists());
+
+        let content = fs::read_to_string(dir.path().join("moved/new.txt")).unwrap();
+        assert_eq!(content, "content");
+    }
+
+    #[test]
+    fn test_rename_existing_destination() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), "a").unwrap();
+        fs::write(dir.path().join("b.txt"), "b").unwrap();
+
+        let result = apply_rename(dir.path(), "a.txt", "b.txt", false);
+        assert!(matches!(result, Err(EditError::FileExists { .. })));
+
+        let result = apply_rename(dir.path(), "a.txt", "b.txt", true);
+        assert!(result.is_ok());
+        assert_eq!(fs::read_to_string(dir.path().join("b.txt")).unwrap(), "a");
+    }
+
+    #[test]
+    fn test_copy() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), "a").unwrap();
+
+        let result = apply_copy(dir.path(), "a.txt", "sub/b.txt");
+        assert!(result.is_ok());
+        assert_eq!(fs::read_to_string(dir.path().join("a.txt")).unwrap(), "a");
+        assert_eq!(fs::read_to_string(dir.path().join("sub/b.txt")).unwrap(), "a");
+    }
 }
@@ -0,0 +1,444 @@
+// NOTE(angeldev)
+// Already-applied detection.
+// After an edit fails, checks whether that is because the file is already in the
+// state the edit would leave it in: the replacement is there and the search text
+// is gone, the file was already moved, the key is already absent, and so on.
+// Re-running a request then reports `already_applied` warnings instead of failing
+// (and rolling back an atomic batch).
+// Inserts, appends and prepends never fail, and content already next to their
+// anchor may be meant twice. They are only skipped when an earlier run in the same
+// workdir applied the very same edit and its content is still in place; every run
+// records the inserts it applied for this.
+
+use crate::edits::between::locate_between;
+use crate::edits::block::locate_block;
+use crate::edits::ensure::{ensure_import, ensure_line, Ensured};
+use crate::edits::structured::{edit_config, lookup, KeyOp};
+use crate::edits::{check_expected, Edit};
+use crate::error::{EditError, EditResult};
+use crate::matcher::{byte_pos_to_line, count_occurrences, find_with_normalization, FindResult};
+use crate::metadata::content_hash;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// NOTE(angeldev)
+// Warning code reported for edits whose effect is already in the file.
+pub const ALREADY_APPLIED: &str = "already_applied";
+
+// NOTE(angeldev)
+// Most insert records kept per workdir; older ones are dropped.
+const MAX_RECORDED_INSERTS: usize = 1000;
+
+// NOTE(angeldev)
+// Returns a message if the failed edit's effect is already present, None if the
+// failure stands.
+// `read` and `exists` see files as the caller does, so dry-run can pass its
+// simulated view of renamed paths.
+pub fn already_applied(
+    edit: &Edit,
+    read: &dyn Fn(&str) -> EditResult<String>,
+    exists: &dyn Fn(&str) -> bool,
+) -> Option<String> {
+    let path = edit.path();
+
+    // File operations only need to know what exists
+    match edit {
+        Edit::DeleteFile { .. } if !exists(path) => return Some("File is already deleted".to_string()),
+        Edit::Rename { from, to, .. } if !exists(from) && exists(to) => {
+            return Some(format!("File was already moved to {}", to))
+        }
+        Edit::Copy { from, to } => {
+            let same = exists(to) && read(from).ok()? == read(to).ok()?;
+            return same.then(|| format!("{} already has the same content", to));
+        }
+        Edit::Create {
+            content,
+            executable: false,
+            permissions: None,
+            ..
+        } => {
+            let same = exists(path) && read(path).ok().as_deref() == Some(content.as_str());
+            return same.then(|| "File already has this content".to_string());
+        }
+        _ => {}
+    }
+
+    let file = read(path).ok()?;
+    match edit {
+        Edit::Replace { search, replace, .. } | Edit::ReplaceAll { search, replace, .. } => {
+            replacement_present(&file, search, replace)
+        }
+
+        Edit::ReplaceLines {
+            start_line,
+            end_line,
+            content,
+            expected,
+            ..
+        } => {
+            let lines: Vec<&str> = file.lines().collect();
+            let block: Vec<&str> = content.lines().collect();
+            let start = start_line.checked_sub(1)?;
+            let same_size = (end_line + 1).checked_sub(*start_line) == Some(block.len());
+            let drifted = check_expected(&file, path, *start_line, *end_line, expected.as_deref()).is_err();
+            (lines_equal(&lines, start, &block) && (same_size || drifted))
+                .then(|| format!("Lines {}-{} already contain the content", start_line, start_line + block.len().max(1) - 1))
+        }
+
+        Edit::DeleteLines {
+            start_line,
+            end_line,
+            expected: Some(expected),
+            ..
+        } => {
+            let drifted = check_expected(&file, path, *start_line, *end_line, Some(expected)).is_err();
+            let gone = matches!(find_with_normalization(&file, expected), FindResult::NotFound);
+            (drifted && gone).then(|| "The expected lines are no longer in the file".to_string())
+        }
+
+        Edit::ReplaceBetween {
+            start,
+            end,
+            content,
+            inclusive,
+            ..
+        } => match locate_between(&file, path, start, end, *inclusive) {
+            Ok(region) => {
+                let lines: Vec<&str> = file.lines().collect();
+                let block: Vec<&str> = content.lines().collect();
+                (region.len() == block.len() && lines_equal(&lines, region.first_line - 1, &block))
+                    .then(|| "The markers already enclose the content".to_string())
+            }
+            Err(EditError::MarkerNotFound { .. }) if *inclusive => {
+                let block = content_lines(content)?;
+                let found = find_with_normalization(&file, &block.join("\n"));
+                (!matches!(found, FindResult::NotFound)).then(|| "Replacement content already present".to_string())
+            }
+            Err(_) => None,
+        },
+
+        Edit::DeleteBetween {
+            start,
+            end,
+            inclusive: false,
+            ..
+        } => {
+            let region = locate_between(&file, path, start, end, false).ok()?;
+            region.is_empty().then(|| "Nothing left between the markers".to_string())
+        }
+
+        Edit::ReplaceBlock { header, content, .. } => {
+            let region = locate_block(&file, path, header).ok()?;
+            let lines: Vec<&str> = file.lines().collect();
+            let block: Vec<&str> = content.trim_end_matches('\n').lines().collect();
+            (region.suffix.is_none()
+                && region.last_line + 1 - region.first_line == block.len()
+                && lines_equal(&lines, region.first_line - 1, &block))
+            .then(|| format!("Block at line {} already has this content", region.first_line))
+        }
+
+        Edit::SetKey { key_path, value, .. } => {
+            (lookup(&file, path, key_path).as_ref() == Some(value)).then(|| format!("{} already has this value", key_path))
+        }
+
+        Edit::DeleteKey { key_path, .. } => {
+            let missing = matches!(edit_config(&file, path, key_path, KeyOp::Delete), Err(EditError::KeyNotFound { .. }));
+            missing.then(|| format!("{} is already absent", key_path))
+        }
+
+        Edit::AppendToArray { key_path, value, .. } => match lookup(&file, path, key_path) {
+            Some(serde_json::Value::Array(items)) if items.contains(value) => {
+                Some(format!("{} already contains this value", key_path))
+            }
+            _ => None,
+        },
+
+        Edit::EnsureLine { line, after, section, .. } => {
+            match ensure_line(&file, path, line, after.as_deref(), section.as_deref()).ok()? {
+                Ensured::Present { line } => Some(format!("Line already present at line {}", line)),
+                _ => None,
+            }
+        }
+
+        Edit::EnsureImport { import, .. } => match ensure_import(&file, path, import).ok()? {
+            Ensured::Present { line } => Some(format!("Import already present at line {}", line)),
+            _ => None,
+        },
+
+        Edit::DeleteMatch { search, .. } => {
+            (!search.is_empty() && !file.contains(search.as_str())).then(|| "No lines contain the search string".to_string())
+        }
+
+        _ => None,
+    }
+}
+
+// NOTE(angeldev)
+// Returns a message if edit is an insert, append or prepend that an earlier run in
+// workdir applied and whose content is still where it put it, None if it should run.
+pub fn resent_insert(workdir: &Path, edit: &Edit, read: &dyn Fn(&str) -> EditResult<String>) -> Option<String> {
+    if !is_insert(edit) || !recorded_inserts(workdir).contains(&fingerprint(edit)) {
+        return None;
+    }
+    insert_present(edit, &read(edit.path()).ok()?)
+}
+
+// NOTE(angeldev)
+// Records inserts, appends and prepends a run applied, for resent_insert.
+// Recording is best effort: without the record re-sent inserts just run again.
+pub fn record_inserts<'a>(workdir: &Path, edits: impl IntoIterator<Item = &'a Edit>) {
+    let added: Vec<String> = edits.into_iter().filter(|e| is_insert(e)).map(fingerprint).collect();
+    if added.is_empty() {
+        return;
+    }
+    let log = insert_log(workdir);
+    let existing = fs::read_to_string(&log).unwrap_or_default();
+    let mut lines: Vec<&str> = existing.lines().collect();
+    lines.extend(added.iter().map(String::as_str));
+    let keep = &lines[lines.len().saturating_sub(MAX_RECORDED_INSERTS)..];
+    if let Some(parent) = log.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    let _ = fs::write(&log, keep.join("\n") + "\n");
+}
+
+fn is_insert(edit: &Edit) -> bool {
+    matches!(
+        edit,
+        Edit::InsertAfter { .. } | Edit::InsertBefore { .. } | Edit::InsertAtLine { .. } | Edit::Append { .. } | Edit::Prepend { .. }
+    )
+}
+
+// Identifies an edit (including its path) across runs
+fn fingerprint(edit: &Edit) -> String {
+    content_hash(format!("{:?}", edit).as_bytes())
+}
+
+// Where a workdir's applied inserts are recorded: outside the workdir, so the
+// record never shows up as a change in it
+fn insert_log(workdir: &Path) -> PathBuf {
+    let workdir = workdir.canonicalize().unwrap_or_else(|_| workdir.to_path_buf());
+    let key = content_hash(workdir.to_string_lossy().as_bytes());
+    std::env::temp_dir().join("apply-edits").join(format!("{}.inserts", &key["sha256:".len()..]))
+}
+
+fn recorded_inserts(workdir: &Path) -> HashSet<String> {
+    let log = fs::read_to_string(insert_log(workdir)).unwrap_or_default();
+    log.lines().map(str::to_string).collect()
+}
+
+// Whether an insert's content sits where the edit would put it
+fn insert_present(edit: &Edit, file: &str) -> Option<String> {
+    match edit {
+        Edit::InsertAfter { anchor, content, .. } | Edit::InsertBefore { anchor, content, .. } => {
+            let lines: Vec<&str> = file.lines().collect();
+            let block = content_lines(content)?;
+            let anchor_idx = lines.iter().position(|l| !anchor.is_empty() && l.contains(anchor.as_str()))?;
+            let start = if matches!(edit, Edit::InsertAfter { .. }) {
+                anchor_idx + 1
+            } else {
+                anchor_idx.checked_sub(block.len())?
+            };
+            lines_equal(&lines, start, &block).then(|| format!("Content already present at line {}", start + 1))
+        }
+
+        Edit::InsertAtLine { line, content, .. } => {
+            let lines: Vec<&str> = file.lines().collect();
+            let block = content_lines(content)?;
+            lines_equal(&lines, line.checked_sub(1)?, &block).then(|| format!("Content already present at line {}", line))
+        }
+
+        Edit::Append { content, .. } => {
+            let tail = content.trim_end();
+            (!tail.trim().is_empty() && file.trim_end().ends_with(tail)).then(|| "File already ends with this content".to_string())
+        }
+
+        Edit::Prepend { content, .. } => {
+            let head = content.trim();
+            (!head.is_empty() && file.trim_start().starts_with(head)).then(|| "File already starts with this content".to_string())
+        }
+
+        _ => None,
+    }
+}
+
+// NOTE(angeldev)
+// A replace is applied when the replacement is in the file and the search text
+// is gone, or (when the replacement contains the search text, e.g. wrapping a
+// call) every remaining occurrence of the search text is inside a replacement.
+fn replacement_present(file: &str, search: &str, replace: &str) -> Option<String> {
+    if search.is_empty() || replace.trim().is_empty() || search == replace {
+        return None;
+    }
+
+    let line = match find_with_normalization(file, replace) {
+        FindResult::Exact(pos) => byte_pos_to_line(file, pos),
+        FindResult::NormalizedMatch { line_number, .. } => line_number,
+        FindResult::NotFound => return None,
+    };
+
+    let applied = if replace.contains(search) {
+        let replacements = count_occurrences(file, replace);
+        replacements > 0 && count_occurrences(file, search) == replacements * count_occurrences(replace, search)
+    } else {
+        matches!(find_with_normalization(file, search), FindResult::NotFound)
+    };
+
+    applied.then(|| format!("Replacement already present at line {}", line))
+}
+
+// Content lines without trailing blank lines; None if there is nothing to compare
+fn content_lines(content: &str) -> Option<Vec<&str>> {
+    let lines: Vec<&str> = content.trim_end().lines().collect();
+    if lines.iter().all(|l| l.trim().is_empty()) {
+        None
+    } else {
+        Some(lines)
+    }
+}
+
+// Compares lines[start..] with block, ignoring surrounding whitespace per line
+fn lines_equal(lines: &[&str], start: usize, block: &[&str]) -> bool {
+    start + block.len() <= lines.len()
+        && lines[start..start + block.len()]
+            .iter()
+            .zip(block)
+            .all(|(a, b)| a.trim() == b.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apply_edits;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn parse(json: &str) -> Vec<Edit> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_rerun_is_idempotent() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("a.js"), "import x from 'x';\n\nfoo();\n").unwrap();
+        fs::write(dir.path().join("old.txt"), "bye\n").unwrap();
+        fs::write(dir.path().join("package.json"), "{\"scripts\": {\"old\": \"x\"}}\n").unwrap();
+
+        let edits = parse(
+            r#"[
+                {"type": "replace", "path": "a.js", "search": "foo();", "replace": "bar(foo());"},
+                {"type": "delete_key", "path": "package.json", "key_path": "scripts.old"},
+                {"type": "rename", "from": "old.txt", "to": "new.txt"}
+            ]"#,
+        );
+
+        let first = apply_edits(dir.path(), &edits);
+        assert!(first.success);
+        let after_first = fs::read_to_string(dir.path().join("a.js")).unwrap();
+
+        let second = apply_edits(dir.path(), &edits);
+        assert!(second.success);
+        assert_eq!(second.applied, 3);
+        for outcome in &second.edits {
+            assert!(matches!(outcome, crate::EditOutcome::Warning { warning, .. } if warning == ALREADY_APPLIED), "{:?}", outcome);
+        }
+        assert_eq!(fs::read_to_string(dir.path().join("a.js")).unwrap(), after_first);
+    }
+
+    #[test]
+    fn test_resent_inserts_are_already_applied() {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("a.js"), "import x from 'x';\n\nfoo();\n").unwrap();
+        fs::write(dir.path().join("b.py"), "import os\n\ndef f():\n    pass\n").unwrap();
+        fs::write(dir.path().join("src/one.ts"), "export const one = 1;\n").unwrap();
+        fs::write(dir.path().join("src/two.ts"), "export const two = 2;\n").unwrap();
+
+        // Batched (several edits to a.js), then through the transaction
+        let requests = [
+            parse(
+                r#"[
+                    {"type": "insert_after", "path": "a.js", "anchor": "import x", "content": "import y from 'y';"},
+                    {"type": "append", "path": "a.js", "content": "done();\n"},
+                    {"type": "prepend", "path": "a.js", "content": "'use strict';"}
+                ]"#,
+            ),
+            parse(
+                r#"[
+                    {"type": "insert_before", "path": "b.py", "anchor": "def f", "content": "@cached"},
+                    {"type": "insert_at_line", "path": "b.py", "line": 2, "content": "import sys"},
+                    {"type": "prepend", "paths": ["src/*.ts"], "content": "// License MIT"},
+                    {"type": "replace_all", "paths": ["src/*.ts"], "search": "export const", "replace": "export let"}
+                ]"#,
+            ),
+        ];
+        for edits in &requests {
+            let dry_run = crate::apply_edits_with_options(dir.path(), edits, true, false);
+            assert!(dry_run.edits.iter().all(|o| matches!(o, crate::EditOutcome::Ok { .. })), "{:?}", dry_run.edits);
+
+            let first = apply_edits(dir.path(), edits);
+            assert!(first.edits.iter().all(|o| matches!(o, crate::EditOutcome::Ok { .. })), "{:?}", first.edits);
+            let snapshot: Vec<String> = ["a.js", "b.py", "src/one.ts", "src/two.ts"]
+                .iter()
+                .map(|p| fs::read_to_string(dir.path().join(p)).unwrap())
+                .collect();
+
+            for dry_run in [true, false] {
+                let again = crate::apply_edits_with_options(dir.path(), edits, dry_run, false);
+                assert!(again.success);
+                assert_eq!(again.edits.len(), edits.len());
+                for outcome in &again.edits {
+                    assert!(matches!(outcome, crate::EditOutcome::Warning { warning, .. } if warning == ALREADY_APPLIED), "{:?}", outcome);
+                }
+            }
+            let after: Vec<String> = ["a.js", "b.py", "src/one.ts", "src/two.ts"]
+                .iter()
+                .map(|p| fs::read_to_string(dir.path().join(p)).unwrap())
+                .collect();
+            assert_eq!(after, snapshot);
+        }
+        assert_eq!(fs::read_to_string(dir.path().join("src/one.ts")).unwrap(), "// License MIT\nexport let one = 1;\n");
+        assert_eq!(
+            fs::read_to_string(dir.path().join("b.py")).unwrap(),
+            "import os\nimport sys\n\n@cached\ndef f():\n    pass\n"
+        );
+    }
+
+    #[test]
+    fn test_replacement_present() {
+        assert!(replacement_present("let x = 2;\n", "let x = 1;", "let x = 2;").is_some());
+        assert!(replacement_present("let x = 1;\nlet x = 2;\n", "let x = 1;", "let x = 2;").is_none());
+        assert!(replacement_present("bar(foo());\nfoo();\n", "foo();", "bar(foo());").is_none());
+        assert!(replacement_present("let y = 3;\n", "let x = 1;", "").is_none());
+    }
+
+    #[test]
+    fn test_first_time_edits_are_not_skipped() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("main.rs"), "fn main() {\n    if ok {\n    }\n").unwrap();
+        fs::write(dir.path().join("list.html"), "<ul>\n<li>item</li>\n</ul>\n").unwrap();
+        fs::write(dir.path().join("conf.py"), "x = 1\nx = 2\n").unwrap();
+
+        let edits = parse(
+            r#"[
+                {"type": "append", "path": "main.rs", "content": "}\n"},
+                {"type": "insert_after", "path": "list.html", "anchor": "<ul>", "content": "<li>item</li>"},
+                {"type": "replace", "path": "conf.py", "search": "x = 2", "replace": "x = 1"}
+            ]"#,
+        );
+
+        // Atomic and partial dry runs, then the real run
+        for (dry_run, partial) in [(true, false), (true, true), (false, false)] {
+            let result = crate::apply_edits_with_options(dir.path(), &edits, dry_run, partial);
+            assert!(result.success);
+            for outcome in &result.edits {
+                assert!(matches!(outcome, crate::EditOutcome::Ok { .. }), "{:?}", outcome);
+            }
+        }
+        let read = |name: &str| fs::read_to_string(dir.path().join(name)).unwrap();
+        assert_eq!(read("main.rs"), "fn main() {\n    if ok {\n    }\n}\n");
+        assert_eq!(read("list.html"), "<ul>\n<li>item</li>\n<li>item</li>\n</ul>\n");
+        assert_eq!(read("conf.py"), "x = 1\nx = 1\n");
+    }
+}
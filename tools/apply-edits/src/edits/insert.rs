@@ -2,7 +2,7 @@
 // Insert edit operations.
 // Handles insert_after, insert_before, and insert_at_line.
 
-use crate::edits::{read_file, write_file};
+use crate::edits::{check_expected, read_file, write_file};
 use crate::error::{EditError, EditResult};
 use crate::matcher::{
     find_closest_matches, insert_after_line, insert_at_line, insert_before_line, truncate_preview,
@@ -96,11 +96,13 @@ pub fn apply_insert_before(
 // NOTE(jimmylee)
 // Applies an insert_at_line operation.
 // Inserts content at the specified line number (1-indexed).
+// NOTE(angeldev): When expected is given, it must match the line currently at that position.
 pub fn apply_insert_at_line(
     workdir: &Path,
     path: &str,
     line: usize,
     content: &str,
+    expected: Option<&str>,
 ) -> EditResult<String> {
     let file_content = read_file(workdir, path)?;
     let total_lines = file_content.lines().count();
@@ -120,6 +122,8 @@ pub fn apply_insert_at_line(
         });
     }
 
+    check_expected(&file_content, path, line, line, expected)?;
+
     // Insert at line
     match insert_at_line(&file_content, line, content) {
         Some(new_content) => {
@@ -172,7 +176,7 @@ mod tests {
         let path = "test.txt";
         fs::write(dir.path().join(path), "line1\nline2\nline3\n").unwrap();
 
-        let result = apply_insert_at_line(dir.path(), path, 2, "inserted");
+        let result = apply_insert_at_line(dir.path(), path, 2, "inserted", None);
         assert!(result.is_ok());
 
         let content = fs::read_to_string(dir.path().join(path)).unwrap();
@@ -200,7 +204,7 @@ mod tests {
         let path = "test.txt";
         fs::write(dir.path().join(path), "line1\nline2\n").unwrap();
 
-        let result = apply_insert_at_line(dir.path(), path, 100, "inserted");
+        let result = apply_insert_at_line(dir.path(), path, 100, "inserted", None);
         assert!(result.is_err());
 
         match result.unwrap_err() {
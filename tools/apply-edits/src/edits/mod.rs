@@ -2,12 +2,20 @@
 // Edit operations module.
 // Defines the Edit enum and provides apply functions for each edit type.
 
+pub mod between;
+pub mod block;
 pub mod delete;
+pub mod ensure;
 pub mod file_ops;
+pub mod idempotent;
 pub mod insert;
 pub mod replace;
+pub mod structured;
+pub mod symbol;
 
-use crate::error::{EditError, EditOutcome, EditResult};
+use crate::error::{EditError, EditOutcome, EditResult, FileChange};
+use crate::matcher::{extract_lines, find_with_normalization, normalize_indentation, truncate_preview, FindResult};
+use crate::walk::{expand_globs, MAX_GLOB_FILES};
 use serde::Deserialize;
 use std::path::Path;
 
@@ -24,8 +32,12 @@ pub enum Edit {
         replace: String,
     },
     // Replace all occurrences of search with replace
+    // NOTE(angeldev): This and the other content edits below take 'paths' globs in place of 'path'
     ReplaceAll {
+        #[serde(default)]
         path: String,
+        #[serde(default)]
+        paths: Vec<String>,
         search: String,
         replace: String,
     },
@@ -46,27 +58,165 @@ pub enum Edit {
         content: String,
     },
     // Insert content at specific line number (1-indexed)
+    // NOTE(angeldev): Line numbers refer to the file as it was when the request started
     InsertAtLine {
         path: String,
         line: usize,
         content: String,
+        #[serde(default)]
+        expected: Option<String>,
     },
     // Create a new file with content
-    Create { path: String, content: String },
+    // NOTE(angeldev): mode decides what happens when the file already exists
+    Create {
+        path: String,
+        content: String,
+        #[serde(default)]
+        mode: CreateMode,
+        #[serde(default)]
+        executable: bool,
+        #[serde(default, deserialize_with = "deserialize_permissions")]
+        permissions: Option<u32>,
+    },
     // Delete a file
     DeleteFile { path: String },
+    // Move a file to a new path (fails if the destination exists unless overwrite)
+    Rename {
+        #[serde(alias = "path")]
+        from: String,
+        to: String,
+        #[serde(default)]
+        overwrite: bool,
+    },
+    // Copy a file to a new path (fails if the destination exists)
+    Copy {
+        #[serde(alias = "path")]
+        from: String,
+        to: String,
+    },
     // Delete lines from start_line to end_line (1-indexed, inclusive)
     DeleteLines {
         path: String,
         start_line: usize,
         end_line: usize,
+        #[serde(default)]
+        expected: Option<String>,
+    },
+    // Replace lines from start_line to end_line (1-indexed, inclusive) with content
+    ReplaceLines {
+        path: String,
+        start_line: usize,
+        end_line: usize,
+        #[serde(alias = "replace")]
+        content: String,
+        #[serde(default)]
+        expected: Option<String>,
+    },
+    // Replace the lines between a start and end marker (markers kept unless inclusive)
+    ReplaceBetween {
+        path: String,
+        #[serde(alias = "start_marker")]
+        start: String,
+        #[serde(alias = "end_marker")]
+        end: String,
+        #[serde(alias = "replace")]
+        content: String,
+        #[serde(default)]
+        inclusive: bool,
+    },
+    // Delete the lines between a start and end marker (markers kept unless inclusive)
+    DeleteBetween {
+        path: String,
+        #[serde(alias = "start_marker")]
+        start: String,
+        #[serde(alias = "end_marker")]
+        end: String,
+        #[serde(default)]
+        inclusive: bool,
+    },
+    // Replace the block opened by a header line (through its closing brace or dedent)
+    ReplaceBlock {
+        path: String,
+        #[serde(alias = "anchor")]
+        header: String,
+        #[serde(alias = "replace")]
+        content: String,
+    },
+    // Set the value at a key path in a JSON/TOML/YAML file, creating missing parents
+    // NOTE(angeldev): key_path is dotted ("scripts.build") or a JSON pointer ("/scripts/build")
+    SetKey {
+        path: String,
+        #[serde(alias = "key")]
+        key_path: String,
+        value: serde_json::Value,
+    },
+    // Remove the key (or array element) at a key path in a JSON/TOML/YAML file
+    DeleteKey {
+        path: String,
+        #[serde(alias = "key")]
+        key_path: String,
+    },
+    // Push a value onto the array at a key path, creating the array if missing
+    AppendToArray {
+        path: String,
+        #[serde(alias = "key")]
+        key_path: String,
+        value: serde_json::Value,
+    },
+    // Insert a line unless an equivalent line (ignoring surrounding whitespace) exists
+    // NOTE(angeldev): Goes after the 'after' anchor, at the end of 'section', or at the end of the file
+    EnsureLine {
+        #[serde(default)]
+        path: String,
+        #[serde(default)]
+        paths: Vec<String>,
+        line: String,
+        #[serde(default)]
+        after: Option<String>,
+        #[serde(default)]
+        section: Option<String>,
+    },
+    // Add an import/use/require statement to the import block unless already imported
+    EnsureImport {
+        path: String,
+        #[serde(alias = "statement")]
+        import: String,
+    },
+    // Rename an identifier in every file matching the globs in 'paths'
+    // NOTE(angeldev): Word-boundary matching by default; .gitignore'd files are skipped
+    RenameSymbol {
+        from: String,
+        to: String,
+        paths: Vec<String>,
+        #[serde(default = "default_true")]
+        word_boundary: bool,
+        #[serde(default)]
+        skip_strings_and_comments: bool,
     },
     // Delete all lines containing search string
-    DeleteMatch { path: String, search: String },
+    DeleteMatch {
+        #[serde(default)]
+        path: String,
+        #[serde(default)]
+        paths: Vec<String>,
+        search: String,
+    },
     // Append content to end of file
-    Append { path: String, content: String },
+    Append {
+        #[serde(default)]
+        path: String,
+        #[serde(default)]
+        paths: Vec<String>,
+        content: String,
+    },
     // Prepend content to beginning of file
-    Prepend { path: String, content: String },
+    Prepend {
+        #[serde(default)]
+        path: String,
+        #[serde(default)]
+        paths: Vec<String>,
+        content: String,
+    },
 }
 
 impl Edit {
@@ -81,7 +231,19 @@ impl Edit {
             Edit::InsertAtLine { path, .. } => path,
             Edit::Create { path, .. } => path,
             Edit::DeleteFile { path } => path,
+            Edit::Rename { from, .. } => from,
+            Edit::Copy { from, .. } => from,
             Edit::DeleteLines { path, .. } => path,
+            Edit::ReplaceLines { path, .. } => path,
+            Edit::ReplaceBetween { path, .. } => path,
+            Edit::DeleteBetween { path, .. } => path,
+            Edit::ReplaceBlock { path, .. } => path,
+            Edit::SetKey { path, .. } => path,
+            Edit::DeleteKey { path, .. } => path,
+            Edit::AppendToArray { path, .. } => path,
+            Edit::EnsureLine { path, .. } => path,
+            Edit::EnsureImport { path, .. } => path,
+            Edit::RenameSymbol { paths, .. } => paths.first().map(String::as_str).unwrap_or("."),
             Edit::DeleteMatch { path, .. } => path,
             Edit::Append { path, .. } => path,
             Edit::Prepend { path, .. } => path,
@@ -99,13 +261,99 @@ impl Edit {
             Edit::InsertAtLine { .. } => "insert_at_line",
             Edit::Create { .. } => "create",
             Edit::DeleteFile { .. } => "delete_file",
+            Edit::Rename { .. } => "rename",
+            Edit::Copy { .. } => "copy",
             Edit::DeleteLines { .. } => "delete_lines",
+            Edit::ReplaceLines { .. } => "replace_lines",
+            Edit::ReplaceBetween { .. } => "replace_between",
+            Edit::DeleteBetween { .. } => "delete_between",
+            Edit::ReplaceBlock { .. } => "replace_block",
+            Edit::SetKey { .. } => "set_key",
+            Edit::DeleteKey { .. } => "delete_key",
+            Edit::AppendToArray { .. } => "append_to_array",
+            Edit::EnsureLine { .. } => "ensure_line",
+            Edit::EnsureImport { .. } => "ensure_import",
+            Edit::RenameSymbol { .. } => "rename_symbol",
             Edit::DeleteMatch { .. } => "delete_match",
             Edit::Append { .. } => "append",
             Edit::Prepend { .. } => "prepend",
         }
     }
 
+    // NOTE(angeldev)
+    // Returns the second path written by rename and copy (the destination).
+    // path() is the source, so every other caller keeps working on one path.
+    pub fn destination(&self) -> Option<&str> {
+        match self {
+            Edit::Rename { to, .. } | Edit::Copy { to, .. } => Some(to),
+            _ => None,
+        }
+    }
+
+    // NOTE(angeldev)
+    // Returns the globs of an edit that spans several files.
+    // path() is then just the first pattern; label() names them all.
+    pub fn glob_paths(&self) -> Option<&[String]> {
+        match self {
+            Edit::RenameSymbol { paths, .. } => Some(paths),
+            _ => None,
+        }
+    }
+
+    // NOTE(angeldev)
+    // The path to name in outcomes and errors: all globs of a multi-file edit.
+    pub fn label(&self) -> String {
+        match self.glob_paths() {
+            Some(patterns) => patterns.join(", "),
+            None => self.path().to_string(),
+        }
+    }
+
+    // NOTE(angeldev)
+    // Returns the 'paths' globs of a content edit that fans out to one edit per file.
+    // Also Some (possibly empty) when 'path' is missing, so expansion can report it.
+    pub fn path_patterns(&self) -> Option<&[String]> {
+        match self {
+            Edit::ReplaceAll { path, paths, .. }
+            | Edit::EnsureLine { path, paths, .. }
+            | Edit::DeleteMatch { path, paths, .. }
+            | Edit::Append { path, paths, .. }
+            | Edit::Prepend { path, paths, .. }
+                if path.is_empty() || !paths.is_empty() =>
+            {
+                Some(paths)
+            }
+            _ => None,
+        }
+    }
+
+    // NOTE(angeldev)
+    // Returns a copy of a fanned-out content edit targeting a single file.
+    pub fn for_file(&self, file: &str) -> Edit {
+        let mut edit = self.clone();
+        match &mut edit {
+            Edit::ReplaceAll { path, paths, .. }
+            | Edit::EnsureLine { path, paths, .. }
+            | Edit::DeleteMatch { path, paths, .. }
+            | Edit::Append { path, paths, .. }
+            | Edit::Prepend { path, paths, .. } => {
+                *path = file.to_string();
+                paths.clear();
+            }
+            _ => {}
+        }
+        edit
+    }
+
+    // NOTE(angeldev)
+    // Returns true if this edit addresses its target by line number.
+    pub fn uses_line_numbers(&self) -> bool {
+        matches!(
+            self,
+            Edit::InsertAtLine { .. } | Edit::DeleteLines { .. } | Edit::ReplaceLines { .. }
+        )
+    }
+
     // NOTE(jimmylee)
     // Applies this edit operation to the filesystem.
     // Returns an EditOutcome indicating success or failure.
@@ -113,9 +361,29 @@ impl Edit {
         let path = self.path();
         let edit_type = self.type_name();
 
+        // NOTE(angeldev): Multi-file edits report how many changes each file got
+        if let Some(patterns) = self.glob_paths() {
+            let label = self.label();
+            return match self.apply_to_files(workdir, patterns) {
+                Ok((message, files)) => EditOutcome::ok_with_files(index, &label, edit_type, message, files),
+                Err(e) => match self.files_already_applied(workdir, patterns) {
+                    Some(message) => EditOutcome::warning(index, &label, edit_type, idempotent::ALREADY_APPLIED, &message),
+                    None => EditOutcome::from_error(index, &label, edit_type, &e),
+                },
+            };
+        }
+
         match self.apply_inner(workdir) {
             Ok(message) => EditOutcome::ok_with_details(index, path, edit_type, None, Some(message)),
-            Err(e) => EditOutcome::from_error(index, path, edit_type, &e),
+            // NOTE(angeldev): A re-sent edit fails because its effect is already in place; that's a no-op
+            Err(e) => {
+                let read = |p: &str| read_file(workdir, p);
+                let exists = |p: &str| workdir.join(p).exists();
+                match idempotent::already_applied(self, &read, &exists) {
+                    Some(message) => EditOutcome::warning(index, path, edit_type, idempotent::ALREADY_APPLIED, &message),
+                    None => EditOutcome::from_error(index, path, edit_type, &e),
+                }
+            }
         }
     }
 
@@ -133,6 +401,7 @@ impl Edit {
                 path,
                 search,
                 replace,
+                ..
             } => replace::apply_replace_all(workdir, path, search, replace),
 
             Edit::InsertAfter {
@@ -151,38 +420,368 @@ impl Edit {
                 path,
                 line,
                 content,
-            } => insert::apply_insert_at_line(workdir, path, *line, content),
+                expected,
+            } => insert::apply_insert_at_line(workdir, path, *line, content, expected.as_deref()),
 
-            Edit::Create { path, content } => file_ops::apply_create(workdir, path, content),
+            Edit::Create {
+                path,
+                content,
+                mode,
+                executable,
+                permissions,
+            } => file_ops::apply_create(workdir, path, content, *mode, *executable, *permissions),
 
             Edit::DeleteFile { path } => delete::apply_delete_file(workdir, path),
 
+            Edit::Rename { from, to, overwrite } => file_ops::apply_rename(workdir, from, to, *overwrite),
+
+            Edit::Copy { from, to } => file_ops::apply_copy(workdir, from, to),
+
             Edit::DeleteLines {
                 path,
                 start_line,
                 end_line,
-            } => delete::apply_delete_lines(workdir, path, *start_line, *end_line),
+                expected,
+            } => delete::apply_delete_lines(
+                workdir,
+                path,
+                *start_line,
+                *end_line,
+                expected.as_deref(),
+            ),
 
-            Edit::DeleteMatch { path, search } => delete::apply_delete_match(workdir, path, search),
+            Edit::ReplaceLines {
+                path,
+                start_line,
+                end_line,
+                content,
+                expected,
+            } => replace::apply_replace_lines(
+                workdir,
+                path,
+                *start_line,
+                *end_line,
+                content,
+                expected.as_deref(),
+            ),
 
-            Edit::Append { path, content } => file_ops::apply_append(workdir, path, content),
+            Edit::ReplaceBetween {
+                path,
+                start,
+                end,
+                content,
+                inclusive,
+            } => between::apply_replace_between(workdir, path, start, end, content, *inclusive),
 
-            Edit::Prepend { path, content } => file_ops::apply_prepend(workdir, path, content),
+            Edit::DeleteBetween {
+                path,
+                start,
+                end,
+                inclusive,
+            } => between::apply_delete_between(workdir, path, start, end, *inclusive),
+
+            Edit::ReplaceBlock {
+                path,
+                header,
+                content,
+            } => block::apply_replace_block(workdir, path, header, content),
+
+            Edit::SetKey {
+                path,
+                key_path,
+                value,
+            } => structured::apply_set_key(workdir, path, key_path, value),
+
+            Edit::DeleteKey { path, key_path } => structured::apply_delete_key(workdir, path, key_path),
+
+            Edit::AppendToArray {
+                path,
+                key_path,
+                value,
+            } => structured::apply_append_to_array(workdir, path, key_path, value),
+
+            Edit::EnsureLine {
+                path,
+                line,
+                after,
+                section,
+                ..
+            } => ensure::apply_ensure_line(workdir, path, line, after.as_deref(), section.as_deref()),
+
+            Edit::EnsureImport { path, import } => ensure::apply_ensure_import(workdir, path, import),
+
+            // NOTE(angeldev): Edit::apply sends multi-file edits to apply_to_files
+            Edit::RenameSymbol { .. } => unreachable!("rename_symbol is applied through apply_to_files"),
+
+            Edit::DeleteMatch { path, search, .. } => delete::apply_delete_match(workdir, path, search),
+
+            Edit::Append { path, content, .. } => file_ops::apply_append(workdir, path, content),
+
+            Edit::Prepend { path, content, .. } => file_ops::apply_prepend(workdir, path, content),
+        }
+    }
+
+    // NOTE(angeldev)
+    // Already-applied check for multi-file edits, which need the workdir to expand
+    // their globs (idempotent::already_applied only sees single paths).
+    pub fn files_already_applied(&self, workdir: &Path, patterns: &[String]) -> Option<String> {
+        match self {
+            Edit::RenameSymbol {
+                from,
+                to,
+                word_boundary,
+                skip_strings_and_comments,
+                ..
+            } => {
+                let options = symbol::SymbolOptions {
+                    word_boundary: *word_boundary,
+                    skip_strings_and_comments: *skip_strings_and_comments,
+                };
+                symbol::already_renamed(workdir, patterns, from, to, options)
+            }
+            _ => None,
+        }
+    }
+
+    // NOTE(angeldev)
+    // Applies a multi-file edit to the files matching patterns.
+    // Returns the summary message and the per-file change counts.
+    fn apply_to_files(&self, workdir: &Path, patterns: &[String]) -> EditResult<(String, Vec<FileChange>)> {
+        match self {
+            Edit::RenameSymbol {
+                from,
+                to,
+                word_boundary,
+                skip_strings_and_comments,
+                ..
+            } => {
+                let options = symbol::SymbolOptions {
+                    word_boundary: *word_boundary,
+                    skip_strings_and_comments: *skip_strings_and_comments,
+                };
+                symbol::apply_rename_symbol(workdir, patterns, from, to, options)
+            }
+            _ => Err(EditError::InvalidEdit {
+                reason: format!("{} does not support 'paths'", self.type_name()),
+            }),
+        }
+    }
+}
+
+// NOTE(angeldev)
+// A request after `paths` expansion: single-file edits, each with the index of
+// the request edit it came from so outcomes can be reported against it, and the
+// request edits no file needs because they are already applied (index, message).
+pub struct ExpandedEdits {
+    pub edits: Vec<Edit>,
+    pub origins: Vec<usize>,
+    pub already_applied: Vec<(usize, String)>,
+}
+
+// The files a `paths` edit runs on, or why it needs none
+enum Selection {
+    Files(Vec<String>),
+    AlreadyApplied(String),
+}
+
+// NOTE(angeldev)
+// Expands content edits that use `paths` into one edit per matching file.
+// Fails with the request index of the first edit that can't be expanded.
+pub fn expand_edits(workdir: &Path, edits: &[Edit]) -> Result<ExpandedEdits, (usize, EditError)> {
+    let mut expanded = ExpandedEdits {
+        edits: Vec::with_capacity(edits.len()),
+        origins: Vec::with_capacity(edits.len()),
+        already_applied: Vec::new(),
+    };
+
+    let read = |p: &str| read_file(workdir, p);
+    for (index, edit) in edits.iter().enumerate() {
+        let Some(patterns) = edit.path_patterns() else {
+            // NOTE(angeldev): Inserts don't fail when re-sent, so they are checked up front
+            match idempotent::resent_insert(workdir, edit, &read) {
+                Some(message) => expanded.already_applied.push((index, message)),
+                None => {
+                    expanded.edits.push(edit.clone());
+                    expanded.origins.push(index);
+                }
+            }
+            continue;
+        };
+        match select_files(workdir, edit, patterns).map_err(|e| (index, e))? {
+            Selection::Files(files) => {
+                for file in files {
+                    expanded.edits.push(edit.for_file(&file));
+                    expanded.origins.push(index);
+                }
+            }
+            Selection::AlreadyApplied(message) => expanded.already_applied.push((index, message)),
         }
     }
+    Ok(expanded)
+}
+
+// NOTE(angeldev)
+// Picks the files a `paths` edit should run on.
+// replace_all and delete_match only go to files containing the search text, and
+// re-sent inserts skip the files they were already applied to; when no file needs
+// the edit but one shows it already applied, the edit is reported as already
+// applied. Unreadable and binary files are skipped.
+fn select_files(workdir: &Path, edit: &Edit, patterns: &[String]) -> EditResult<Selection> {
+    if patterns.is_empty() {
+        return Err(EditError::InvalidEdit {
+            reason: format!("{} needs 'path' or 'paths'", edit.type_name()),
+        });
+    }
+    if !edit.path().is_empty() {
+        return Err(EditError::InvalidEdit {
+            reason: format!("{} takes either 'path' or 'paths', not both", edit.type_name()),
+        });
+    }
+
+    let label = patterns.join(", ");
+    let read = |p: &str| read_file(workdir, p);
+    let exists = |p: &str| workdir.join(p).exists();
+
+    let mut readable = 0;
+    let mut selected = Vec::new();
+    let mut applied = None;
+    for file in expand_globs(workdir, patterns)? {
+        let Ok(content) = read(&file) else { continue };
+        readable += 1;
+
+        let single = edit.for_file(&file);
+        let resent = idempotent::resent_insert(workdir, &single, &|_| Ok(content.clone()));
+        let needed = match edit {
+            Edit::ReplaceAll { search, .. } => !matches!(find_with_normalization(&content, search), FindResult::NotFound),
+            Edit::DeleteMatch { search, .. } => content.lines().any(|l| l.contains(search.as_str())),
+            _ => resent.is_none(),
+        };
+        if needed {
+            selected.push(file);
+        } else if applied.is_none() {
+            applied = resent
+                .or_else(|| idempotent::already_applied(&single, &read, &exists))
+                .map(|message| format!("{} ({})", message, file));
+        }
+    }
+
+    if readable == 0 {
+        return Err(EditError::NoFilesMatched { pattern: label });
+    }
+    if selected.is_empty() {
+        return match (applied, edit) {
+            (Some(message), _) => Ok(Selection::AlreadyApplied(message)),
+            (None, Edit::ReplaceAll { search, .. }) | (None, Edit::DeleteMatch { search, .. }) => {
+                Err(EditError::SearchNotFound {
+                    path: label,
+                    search_preview: truncate_preview(search, 200),
+                    closest_matches: Vec::new(),
+                })
+            }
+            (None, _) => Err(EditError::NoFilesMatched { pattern: label }),
+        };
+    }
+    if selected.len() > MAX_GLOB_FILES {
+        return Err(EditError::TooManyFiles {
+            pattern: label,
+            count: selected.len(),
+            limit: MAX_GLOB_FILES,
+        });
+    }
+    Ok(Selection::Files(selected))
+}
+
+// NOTE(angeldev)
+// Serde default for boolean fields that are on unless disabled.
+fn default_true() -> bool {
+    true
+}
+
+// NOTE(angeldev)
+// How a create edit treats an existing file.
+// Overwrite is the default so existing requests keep working.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CreateMode {
+    // Fail with file_exists if the file is already there
+    CreateNew,
+    // Replace any existing file
+    #[default]
+    Overwrite,
+    // Leave an existing file untouched
+    IfMissing,
+}
+
+// NOTE(angeldev)
+// Parses octal permission bits given as a string ("755", "0o644") or a number
+// whose digits are read as octal (755).
+fn deserialize_permissions<'de, D>(deserializer: D) -> Result<Option<u32>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Number(u64),
+        Text(String),
+    }
+
+    let digits = match Option::<Raw>::deserialize(deserializer)? {
+        None => return Ok(None),
+        Some(Raw::Number(n)) => n.to_string(),
+        Some(Raw::Text(s)) => s,
+    };
+
+    let trimmed = digits.trim();
+    let octal = trimmed.strip_prefix("0o").unwrap_or(trimmed);
+    match u32::from_str_radix(octal, 8) {
+        Ok(bits) if bits <= 0o7777 => Ok(Some(bits)),
+        _ => Err(serde::de::Error::custom(format!(
+            "invalid permissions '{}': expected octal like \"755\"",
+            digits
+        ))),
+    }
 }
 
 // NOTE(jimmylee)
 // Request structure for apply command JSON input.
 #[derive(Debug, Deserialize)]
 pub struct EditRequest {
+    #[serde(default)]
     pub edits: Vec<Edit>,
+    // NOTE(angeldev): Named groups run after the ungrouped edits
+    #[serde(default)]
+    pub groups: Vec<EditGroup>,
     #[serde(default)]
     pub commit_message: Option<String>,
     #[serde(default)]
     pub summary: Option<String>,
 }
 
+// NOTE(angeldev)
+// A logical change (e.g. a component and its import) whose edits succeed or roll
+// back together in partial mode, independently of other groups.
+#[derive(Debug, Deserialize)]
+pub struct EditGroup {
+    pub name: String,
+    pub edits: Vec<Edit>,
+}
+
+impl EditRequest {
+    // NOTE(angeldev)
+    // Returns every edit in request order (ungrouped first, then each group's) and,
+    // for each, the index of its group in `groups` (None if ungrouped).
+    pub fn flatten(&self) -> (Vec<Edit>, Vec<Option<usize>>) {
+        let mut edits = self.edits.clone();
+        let mut membership = vec![None; edits.len()];
+        for (group, edit_group) in self.groups.iter().enumerate() {
+            edits.extend(edit_group.edits.iter().cloned());
+            membership.resize(edits.len(), Some(group));
+        }
+        (edits, membership)
+    }
+}
+
 // NOTE(angeldev)
 // Default threshold for large file handling (100KB)
 pub const LARGE_FILE_THRESHOLD: u64 = 100 * 1024;
@@ -260,6 +859,38 @@ fn read_file_mmap(workdir: &Path, path: &str) -> EditResult<String> {
         })
 }
 
+// NOTE(angeldev)
+// Verifies that lines start_line..=end_line match the optional expected text.
+// Comparison ignores indentation and trailing whitespace so only real drift fails.
+pub fn check_expected(
+    content: &str,
+    path: &str,
+    start_line: usize,
+    end_line: usize,
+    expected: Option<&str>,
+) -> EditResult<()> {
+    let expected = match expected {
+        Some(e) => e,
+        None => return Ok(()),
+    };
+
+    let actual = extract_lines(content, start_line, end_line + 1 - start_line);
+    let norm_expected = normalize_indentation(expected);
+    let norm_actual = normalize_indentation(&actual);
+
+    if norm_expected.trim_end_matches('\n') == norm_actual.trim_end_matches('\n') {
+        return Ok(());
+    }
+
+    Err(EditError::ExpectedMismatch {
+        path: path.to_string(),
+        start_line,
+        end_line,
+        expected_preview: truncate_preview(expected, 200),
+        actual_preview: truncate_preview(&actual, 200),
+    })
+}
+
 // NOTE(jimmylee)
 // Helper function to write file content, returning appropriate error.
 pub fn write_file(workdir: &Path, path: &str, content: &str) -> EditResult<()> {
@@ -270,3 +901,75 @@ pub fn write_file(workdir: &Path, path: &str, content: &str) -> EditResult<()> {
         reason: e.to_string(),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_expand_edits() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/a.tsx"), "import old from 'old';\n").unwrap();
+        fs::write(dir.path().join("src/b.tsx"), "export {};\n").unwrap();
+        fs::write(dir.path().join("src/c.tsx"), "import old from 'old';\n").unwrap();
+
+        let edits: Vec<Edit> = serde_json::from_str(
+            r#"[
+                {"type": "append", "path": "README.md", "content": "x"},
+                {"type": "replace_all", "paths": ["src/*.tsx"], "search": "'old'", "replace": "'new'"},
+                {"type": "prepend", "paths": ["*.tsx"], "content": "// License\n"}
+            ]"#,
+        )
+        .unwrap();
+        let expanded = expand_edits(dir.path(), &edits).unwrap();
+        assert_eq!(expanded.origins, vec![0, 1, 1, 2, 2, 2]);
+        let paths: Vec<&str> = expanded.edits.iter().map(|e| e.path()).collect();
+        assert_eq!(paths, vec!["README.md", "src/a.tsx", "src/c.tsx", "src/a.tsx", "src/b.tsx", "src/c.tsx"]);
+
+        let result = crate::apply_edits(dir.path(), &edits[1..]);
+        assert!(result.success);
+        let indexes: Vec<usize> = result.edits.iter().map(|o| o.index()).collect();
+        assert_eq!(indexes, vec![0, 0, 1, 1, 1]);
+        let content = fs::read_to_string(dir.path().join("src/c.tsx")).unwrap();
+        assert_eq!(content, "// License\nimport old from 'new';\n");
+
+        let missing: Vec<Edit> =
+            serde_json::from_str(r#"[{"type": "delete_match", "paths": ["lib/**"], "search": "x"}]"#).unwrap();
+        let (index, err) = expand_edits(dir.path(), &missing).err().unwrap();
+        assert_eq!(index, 0);
+        assert!(matches!(err, EditError::NoFilesMatched { .. }));
+    }
+
+    #[test]
+    fn test_paths_edit_already_applied_everywhere() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/a.ts"), "import x from 'new';\n").unwrap();
+        fs::write(dir.path().join("src/b.ts"), "export {};\n").unwrap();
+
+        let edits: Vec<Edit> = serde_json::from_str(
+            r#"[
+                {"type": "replace_all", "paths": ["src/*.ts"], "search": "'old'", "replace": "'new'"},
+                {"type": "append", "path": "src/b.ts", "content": "// end\n"}
+            ]"#,
+        )
+        .unwrap();
+        let expanded = expand_edits(dir.path(), &edits).unwrap();
+        assert_eq!(expanded.origins, vec![1]);
+        assert_eq!(expanded.already_applied[0].0, 0);
+
+        let result = crate::apply_edits(dir.path(), &edits);
+        assert!(result.success);
+        assert_eq!(result.edits.len(), 2);
+        match &result.edits[0] {
+            EditOutcome::Warning { path, message, .. } => {
+                assert_eq!(path, "src/*.ts");
+                assert!(message.ends_with("(src/a.ts)"), "{}", message);
+            }
+            other => panic!("{:?}", other),
+        }
+    }
+}
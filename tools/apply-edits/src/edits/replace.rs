@@ -2,11 +2,11 @@
 // Replace edit operations.
 // Handles replace (first occurrence) and replace_all (all occurrences).
 
-use crate::edits::{read_file, write_file};
+use crate::edits::{check_expected, read_file, write_file};
 use crate::error::{EditError, EditResult};
 use crate::matcher::{
     count_occurrences, find_closest_matches, find_literal, get_affected_lines, replace_all,
-    replace_first, replace_with_normalization, truncate_preview,
+    replace_first, replace_line_range, replace_with_normalization, truncate_preview,
 };
 use std::path::Path;
 
@@ -129,6 +129,66 @@ pub fn apply_replace_all(
     Ok(format!("Replaced {} occurrence(s)", occurrences))
 }
 
+// NOTE(angeldev)
+// Applies a replace_lines operation.
+// Replaces lines from start_line to end_line (1-indexed, inclusive) with content.
+// When expected is given, the range must still contain that text.
+pub fn apply_replace_lines(
+    workdir: &Path,
+    path: &str,
+    start_line: usize,
+    end_line: usize,
+    content: &str,
+    expected: Option<&str>,
+) -> EditResult<String> {
+    let file_content = read_file(workdir, path)?;
+    let total_lines = file_content.lines().count();
+
+    // Validate line numbers
+    if start_line == 0 || end_line == 0 {
+        return Err(EditError::InvalidEdit {
+            reason: "Line numbers must be >= 1".to_string(),
+        });
+    }
+
+    if start_line > end_line {
+        return Err(EditError::InvalidEdit {
+            reason: format!(
+                "Start line ({}) must be <= end line ({})",
+                start_line, end_line
+            ),
+        });
+    }
+
+    if end_line > total_lines {
+        return Err(EditError::InvalidLineRange {
+            path: path.to_string(),
+            start_line,
+            end_line,
+            total_lines,
+        });
+    }
+
+    check_expected(&file_content, path, start_line, end_line, expected)?;
+
+    let new_content = replace_line_range(&file_content, start_line, end_line, content)
+        .ok_or_else(|| EditError::InvalidLineRange {
+            path: path.to_string(),
+            start_line,
+            end_line,
+            total_lines,
+        })?;
+
+    write_file(workdir, path, &new_content)?;
+
+    Ok(format!(
+        "Replaced lines {}-{} with {} line(s)",
+        start_line,
+        end_line,
+        content.lines().count()
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -200,6 +260,35 @@ mod tests {
         assert_eq!(content, "qux bar qux baz qux");
     }
 
+    #[test]
+    fn test_apply_replace_lines() {
+        let dir = tempdir().unwrap();
+        let path = "test.txt";
+        fs::write(dir.path().join(path), "line1\nline2\nline3\nline4\n").unwrap();
+
+        let result = apply_replace_lines(dir.path(), path, 2, 3, "new2\nnew3\nnew4", None);
+        assert!(result.is_ok());
+
+        let content = fs::read_to_string(dir.path().join(path)).unwrap();
+        assert_eq!(content, "line1\nnew2\nnew3\nnew4\nline4\n");
+    }
+
+    #[test]
+    fn test_apply_replace_lines_expected_mismatch() {
+        let dir = tempdir().unwrap();
+        let path = "test.txt";
+        fs::write(dir.path().join(path), "line1\nline2\nline3\n").unwrap();
+
+        let result = apply_replace_lines(dir.path(), path, 2, 2, "new", Some("line3"));
+        match result.unwrap_err() {
+            EditError::ExpectedMismatch { .. } => (),
+            e => panic!("Expected ExpectedMismatch, got {:?}", e),
+        }
+
+        let content = fs::read_to_string(dir.path().join(path)).unwrap();
+        assert_eq!(content, "line1\nline2\nline3\n");
+    }
+
     // NOTE(angeldev)
     // Test that indentation-normalized matching works when LLM uses wrong indentation
     #[test]
@@ -0,0 +1,249 @@
+// NOTE(angeldev)
+// Structured key-path edits for config files.
+// Handles set_key, delete_key and append_to_array on JSON, TOML and YAML files.
+// Each format edits the original text in place rather than re-serializing, so key
+// order, indentation and comments outside the touched value survive the edit.
+
+mod json;
+mod toml;
+mod yaml;
+
+use crate::edits::{read_file, write_file};
+use crate::error::{EditError, EditResult};
+use crate::indent::detect_indent_style;
+use crate::language::Language;
+use serde_json::Value;
+use std::path::Path;
+
+// NOTE(angeldev)
+// Config formats that support key-path edits, detected by extension.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConfigFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl ConfigFormat {
+    // NOTE(angeldev)
+    // Detects the config format of a path.
+    // JSONC-style files (tsconfig.json, .vscode/*.json) are Json; comments are kept.
+    pub fn from_path(path: &str) -> Option<ConfigFormat> {
+        match Language::from_path(path) {
+            Language::Json => Some(ConfigFormat::Json),
+            Language::Toml => Some(ConfigFormat::Toml),
+            Language::Yaml => Some(ConfigFormat::Yaml),
+            _ if path.ends_with(".jsonc") || path.ends_with(".json5") => Some(ConfigFormat::Json),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ConfigFormat::Json => "JSON",
+            ConfigFormat::Toml => "TOML",
+            ConfigFormat::Yaml => "YAML",
+        }
+    }
+}
+
+// NOTE(angeldev)
+// The change to make at a key path.
+#[derive(Debug, Clone, Copy)]
+pub enum KeyOp<'a> {
+    // Set the value, creating missing parent objects
+    Set(&'a Value),
+    // Remove the key (or array element)
+    Delete,
+    // Push onto the array, creating it if the key is missing
+    Append(&'a Value),
+}
+
+// NOTE(angeldev)
+// Failures inside a format editor, turned into EditErrors by edit_config
+// once the path and key path are known.
+#[derive(Debug)]
+enum KeyError {
+    Parse(String),
+    NotFound,
+    Invalid(String),
+}
+
+// NOTE(angeldev)
+// Splits a key path into segments.
+// Accepts dotted paths ("compilerOptions.paths", "bin.0") and JSON pointers
+// ("/exports/.~1utils"), which can address keys containing dots or slashes.
+pub fn parse_key_path(key_path: &str) -> Option<Vec<String>> {
+    let segments: Vec<String> = if let Some(pointer) = key_path.strip_prefix('/') {
+        pointer
+            .split('/')
+            .map(|s| s.replace("~1", "/").replace("~0", "~"))
+            .collect()
+    } else {
+        key_path.split('.').map(|s| s.to_string()).collect()
+    };
+
+    if segments.iter().any(|s| s.is_empty()) {
+        None
+    } else {
+        Some(segments)
+    }
+}
+
+// NOTE(angeldev)
+// Applies a key-path operation to config content and returns the new content.
+// Used by the apply functions below and by dry-run simulation.
+pub fn edit_config(content: &str, path: &str, key_path: &str, op: KeyOp) -> EditResult<String> {
+    let invalid = |reason: String| EditError::InvalidKeyPath {
+        path: path.to_string(),
+        key_path: key_path.to_string(),
+        reason,
+    };
+
+    let format = ConfigFormat::from_path(path)
+        .ok_or_else(|| invalid("file is not JSON, TOML or YAML".to_string()))?;
+    let keys = parse_key_path(key_path).ok_or_else(|| invalid("empty key segment".to_string()))?;
+
+    let extension = match format {
+        ConfigFormat::Json => "json",
+        ConfigFormat::Toml => "toml",
+        ConfigFormat::Yaml => "yaml",
+    };
+    let unit = detect_indent_style(content, extension).unit();
+
+    let result = match format {
+        ConfigFormat::Json => json::apply(content, &keys, op, &unit),
+        ConfigFormat::Toml => toml::apply(content, &keys, op),
+        ConfigFormat::Yaml => yaml::apply(content, &keys, op, unit.len()),
+    };
+
+    result.map_err(|e| match e {
+        KeyError::Parse(reason) => EditError::ParseError {
+            path: path.to_string(),
+            format: format.name().to_string(),
+            reason,
+        },
+        KeyError::NotFound => EditError::KeyNotFound {
+            path: path.to_string(),
+            key_path: key_path.to_string(),
+        },
+        KeyError::Invalid(reason) => invalid(reason),
+    })
+}
+
+// NOTE(angeldev)
+// Reads the value at a key path, or None if the file doesn't parse or the key is missing.
+pub fn lookup(content: &str, path: &str, key_path: &str) -> Option<Value> {
+    let keys = parse_key_path(key_path)?;
+    let root = match ConfigFormat::from_path(path)? {
+        ConfigFormat::Json => json::read(content),
+        ConfigFormat::Toml => toml::read(content),
+        ConfigFormat::Yaml => yaml::read(content),
+    }
+    .ok()?;
+
+    keys.iter()
+        .try_fold(&root, |value, key| match value {
+            Value::Object(map) => map.get(key),
+            Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => None,
+        })
+        .cloned()
+}
+
+// NOTE(angeldev)
+// Wraps a value in objects for the keys that don't exist yet:
+// setting a.b.c where only a exists inserts b = { c = value } under a.
+fn nest_value(keys: &[String], value: Value) -> Value {
+    keys.iter().rev().fold(value, |inner, key| {
+        let mut map = serde_json::Map::new();
+        map.insert(key.clone(), inner);
+        Value::Object(map)
+    })
+}
+
+// NOTE(angeldev)
+// Parses an array index segment.
+fn parse_index(key: &str) -> Result<usize, KeyError> {
+    key.parse::<usize>()
+        .map_err(|_| KeyError::Invalid(format!("'{}' is not an array index", key)))
+}
+
+fn apply_op(workdir: &Path, path: &str, key_path: &str, op: KeyOp) -> EditResult<()> {
+    let content = read_file(workdir, path)?;
+    let new_content = edit_config(&content, path, key_path, op)?;
+    write_file(workdir, path, &new_content)
+}
+
+// NOTE(angeldev)
+// Applies a set_key operation.
+pub fn apply_set_key(workdir: &Path, path: &str, key_path: &str, value: &Value) -> EditResult<String> {
+    apply_op(workdir, path, key_path, KeyOp::Set(value))?;
+    Ok(format!("Set {}", key_path))
+}
+
+// NOTE(angeldev)
+// Applies a delete_key operation.
+pub fn apply_delete_key(workdir: &Path, path: &str, key_path: &str) -> EditResult<String> {
+    apply_op(workdir, path, key_path, KeyOp::Delete)?;
+    Ok(format!("Deleted {}", key_path))
+}
+
+// NOTE(angeldev)
+// Applies an append_to_array operation.
+pub fn apply_append_to_array(workdir: &Path, path: &str, key_path: &str, value: &Value) -> EditResult<String> {
+    apply_op(workdir, path, key_path, KeyOp::Append(value))?;
+    Ok(format!("Appended to {}", key_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse_key_path() {
+        assert_eq!(parse_key_path("a.b.0").unwrap(), vec!["a", "b", "0"]);
+        assert_eq!(parse_key_path("/exports/.~1utils").unwrap(), vec!["exports", "./utils"]);
+        assert!(parse_key_path("a..b").is_none());
+    }
+
+    #[test]
+    fn test_edit_config_errors() {
+        let err = edit_config("{ \"a\": ", "package.json", "a", KeyOp::Delete).unwrap_err();
+        assert!(matches!(err, EditError::ParseError { .. }));
+
+        let err = edit_config("{ \"a\": 1 }", "package.json", "b", KeyOp::Delete).unwrap_err();
+        assert!(matches!(err, EditError::KeyNotFound { .. }));
+
+        let err = edit_config("a = 1\n", "Cargo.toml", "a.b", KeyOp::Set(&json!(2))).unwrap_err();
+        assert!(matches!(err, EditError::InvalidKeyPath { .. }));
+    }
+
+    #[test]
+    fn test_lookup() {
+        let jsonc = "{\n  // deps\n  \"deps\": [\"a\", \"b\",],\n}\n";
+        assert_eq!(lookup(jsonc, "tsconfig.json", "deps.1"), Some(json!("b")));
+        assert_eq!(lookup("[server]\nport = 80\n", "app.toml", "server"), Some(json!({"port": 80})));
+        assert_eq!(lookup("a:\n  b: true\n", "ci.yml", "/a/b"), Some(json!(true)));
+        assert_eq!(lookup("a: 1\n", "ci.yml", "b"), None);
+    }
+
+    #[test]
+    fn test_apply_set_key() {
+        let dir = tempdir().unwrap();
+        let path = "package.json";
+        fs::write(dir.path().join(path), "{\n  \"name\": \"app\",\n  \"version\": \"1.0.0\"\n}\n").unwrap();
+
+        let result = apply_set_key(dir.path(), path, "scripts.build", &json!("tsc"));
+        assert!(result.is_ok(), "{:?}", result);
+
+        let content = fs::read_to_string(dir.path().join(path)).unwrap();
+        assert_eq!(
+            content,
+            "{\n  \"name\": \"app\",\n  \"version\": \"1.0.0\",\n  \"scripts\": {\n    \"build\": \"tsc\"\n  }\n}\n"
+        );
+    }
+}
@@ -0,0 +1,532 @@
+// NOTE(angeldev)
+// Span-preserving JSON editor.
+// Parses the document into a tree of byte spans (tolerating JSONC comments and
+// trailing commas) and splices new text into the original, so everything outside
+// the edited value is byte-for-byte unchanged.
+
+use super::{nest_value, parse_index, KeyError, KeyOp};
+use serde_json::Value;
+
+// NOTE(angeldev)
+// A parsed JSON value and where it sits in the source.
+#[derive(Debug)]
+enum Node {
+    Object { start: usize, end: usize, members: Vec<Member> },
+    Array { start: usize, end: usize, items: Vec<Node> },
+    Scalar { start: usize, end: usize },
+}
+
+#[derive(Debug)]
+struct Member {
+    key: String,
+    key_start: usize,
+    value: Node,
+}
+
+impl Node {
+    fn start(&self) -> usize {
+        match self {
+            Node::Object { start, .. } | Node::Array { start, .. } | Node::Scalar { start, .. } => *start,
+        }
+    }
+
+    fn end(&self) -> usize {
+        match self {
+            Node::Object { end, .. } | Node::Array { end, .. } | Node::Scalar { end, .. } => *end,
+        }
+    }
+
+    // Looks up a direct child by key (objects) or index (arrays)
+    fn child(&self, key: &str) -> Option<&Node> {
+        match self {
+            Node::Object { members, .. } => members.iter().rev().find(|m| m.key == key).map(|m| &m.value),
+            Node::Array { items, .. } => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+            Node::Scalar { .. } => None,
+        }
+    }
+
+    // Spans of each entry (key through value for objects)
+    fn entries(&self) -> Vec<(usize, usize)> {
+        match self {
+            Node::Object { members, .. } => members.iter().map(|m| (m.key_start, m.value.end())).collect(),
+            Node::Array { items, .. } => items.iter().map(|i| (i.start(), i.end())).collect(),
+            Node::Scalar { .. } => Vec::new(),
+        }
+    }
+}
+
+// NOTE(angeldev)
+// Parses JSON (with comments and trailing commas) into a value.
+pub(super) fn read(content: &str) -> Result<Value, KeyError> {
+    let root = Parser::new(content).parse_document().map_err(KeyError::Parse)?;
+    Ok(to_value(content, &root))
+}
+
+fn to_value(content: &str, node: &Node) -> Value {
+    match node {
+        Node::Object { members, .. } => Value::Object(
+            members
+                .iter()
+                .map(|m| (m.key.clone(), to_value(content, &m.value)))
+                .collect(),
+        ),
+        Node::Array { items, .. } => Value::Array(items.iter().map(|i| to_value(content, i)).collect()),
+        Node::Scalar { start, end } => serde_json::from_str(&content[*start..*end]).unwrap_or(Value::Null),
+    }
+}
+
+// NOTE(angeldev)
+// Applies a key-path operation to JSON content.
+pub(super) fn apply(content: &str, keys: &[String], op: KeyOp, unit: &str) -> Result<String, KeyError> {
+    let root = Parser::new(content).parse_document().map_err(KeyError::Parse)?;
+
+    // Walk as far down the path as it exists
+    let mut node = &root;
+    let mut parents: Vec<&Node> = Vec::new();
+    let mut found = 0;
+    for key in keys {
+        match node.child(key) {
+            Some(child) => {
+                parents.push(node);
+                node = child;
+                found += 1;
+            }
+            None => break,
+        }
+    }
+
+    if found == keys.len() {
+        let parent = parents.last().copied().unwrap_or(&root);
+        return match op {
+            KeyOp::Set(value) => {
+                let rendered = if is_inline(content, parent) {
+                    render_inline(value)
+                } else {
+                    render(value, &line_indent(content, node.start()), unit)
+                };
+                Ok(splice(content, node.start(), node.end(), &rendered))
+            }
+            KeyOp::Delete => {
+                // Which duplicate a reader honours varies, so removing one would be a guess
+                if let (Node::Object { members, .. }, Some(key)) = (parent, keys.last()) {
+                    if members.iter().filter(|m| &m.key == key).count() > 1 {
+                        return Err(KeyError::Invalid(format!("'{}' appears more than once", keys.join("."))));
+                    }
+                }
+                Ok(delete_entry(content, parent, node))
+            }
+            KeyOp::Append(value) => match node {
+                Node::Array { .. } => Ok(insert_entry(content, node, None, value, unit)),
+                _ => Err(KeyError::Invalid(format!("'{}' is not an array", keys.join(".")))),
+            },
+        };
+    }
+
+    // The key at `found` is missing from `node`
+    let missing = &keys[found];
+    let value = match op {
+        KeyOp::Set(value) => nest_value(&keys[found + 1..], value.clone()),
+        KeyOp::Append(value) => nest_value(&keys[found + 1..], Value::Array(vec![value.clone()])),
+        KeyOp::Delete => return Err(KeyError::NotFound),
+    };
+
+    match node {
+        Node::Object { .. } => Ok(insert_entry(content, node, Some(missing), &value, unit)),
+        Node::Array { items, .. } if parse_index(missing)? == items.len() => {
+            Ok(insert_entry(content, node, None, &value, unit))
+        }
+        Node::Array { items, .. } => Err(KeyError::Invalid(format!(
+            "index {} is out of range (array has {} items)",
+            missing,
+            items.len()
+        ))),
+        Node::Scalar { .. } => Err(KeyError::Invalid(format!(
+            "'{}' is not an object",
+            keys[..found].join(".")
+        ))),
+    }
+}
+
+// NOTE(angeldev)
+// Adds an entry at the end of an object or array, matching the layout of the
+// existing entries (one per line or all on one line).
+fn insert_entry(content: &str, container: &Node, key: Option<&str>, value: &Value, unit: &str) -> String {
+    let open = container.start();
+    let close = container.end() - 1;
+    let entries = container.entries();
+    let format_entry = |base: &str| {
+        let rendered = render(value, base, unit);
+        match key {
+            Some(k) => format!("{}: {}", Value::String(k.to_string()), rendered),
+            None => rendered,
+        }
+    };
+
+    let (last_start, last_end) = match entries.last() {
+        Some(last) => *last,
+        None => {
+            // Empty container: short arrays of scalars stay inline
+            if key.is_none() && !value.is_object() && !value.is_array() {
+                return splice(content, open + 1, close, &format_entry(""));
+            }
+            let outer = line_indent(content, open);
+            let inner = format!("{}{}", outer, unit);
+            let text = format!("\n{}{}\n{}", inner, format_entry(&inner), outer);
+            return splice(content, open + 1, close, &text);
+        }
+    };
+
+    if is_inline(content, container) {
+        let rendered = render_inline(value);
+        let entry = match key {
+            Some(k) => format!("{}: {}", Value::String(k.to_string()), rendered),
+            None => rendered,
+        };
+        return splice(content, last_end, last_end, &format!(", {}", entry));
+    }
+
+    // Keep a trailing comment on the last entry's line with that entry
+    let indent = line_indent(content, last_start);
+    let next = next_code_offset(content, last_end);
+    let trailing_comma = content.as_bytes().get(next) == Some(&b',');
+    let after = if trailing_comma { next + 1 } else { last_end };
+    let line_end = content[after..close].find('\n').map(|p| after + p).unwrap_or(after);
+    let entry = format!("\n{}{}", indent, format_entry(&indent));
+
+    if trailing_comma {
+        splice(content, line_end, line_end, &entry)
+    } else {
+        let with_entry = splice(content, line_end, line_end, &entry);
+        splice(&with_entry, last_end, last_end, ",")
+    }
+}
+
+// NOTE(angeldev)
+// Removes an entry and the comma that separates it from its neighbours.
+fn delete_entry(content: &str, parent: &Node, node: &Node) -> String {
+    let entries = parent.entries();
+    let idx = entries
+        .iter()
+        .position(|(_, end)| *end == node.end())
+        .unwrap_or(0);
+
+    if entries.len() == 1 {
+        return splice(content, parent.start() + 1, parent.end() - 1, "");
+    }
+    if idx + 1 < entries.len() {
+        // The next entry moves up into this one's place, keeping its indentation
+        splice(content, entries[idx].0, entries[idx + 1].0, "")
+    } else {
+        splice(content, entries[idx - 1].1, entries[idx].1, "")
+    }
+}
+
+// NOTE(angeldev)
+// Renders a value for insertion at a line indented by `base`.
+fn render(value: &Value, base: &str, unit: &str) -> String {
+    use serde::Serialize;
+
+    let is_empty = match value {
+        Value::Object(map) => map.is_empty(),
+        Value::Array(items) => items.is_empty(),
+        _ => true,
+    };
+    if is_empty {
+        return value.to_string();
+    }
+
+    let mut out = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(unit.as_bytes());
+    let mut serializer = serde_json::Serializer::with_formatter(&mut out, formatter);
+    value
+        .serialize(&mut serializer)
+        .expect("serializing a JSON value cannot fail");
+
+    String::from_utf8(out)
+        .unwrap_or_default()
+        .replace('\n', &format!("\n{}", base))
+}
+
+// NOTE(angeldev)
+// Renders a value on one line, for objects and arrays laid out on a single line.
+fn render_inline(value: &Value) -> String {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            let members: Vec<String> = map
+                .iter()
+                .map(|(k, v)| format!("{}: {}", Value::String(k.clone()), render_inline(v)))
+                .collect();
+            format!("{{{}}}", members.join(", "))
+        }
+        Value::Array(items) if !items.is_empty() => {
+            let items: Vec<String> = items.iter().map(render_inline).collect();
+            format!("[{}]", items.join(", "))
+        }
+        _ => value.to_string(),
+    }
+}
+
+// True if a container's entries start on its opening line
+fn is_inline(content: &str, container: &Node) -> bool {
+    match container.entries().first() {
+        Some((first, _)) => !content[container.start()..*first].contains('\n'),
+        None => false,
+    }
+}
+
+fn splice(content: &str, start: usize, end: usize, text: &str) -> String {
+    format!("{}{}{}", &content[..start], text, &content[end..])
+}
+
+// Leading whitespace of the line containing pos
+fn line_indent(content: &str, pos: usize) -> String {
+    let line_start = content[..pos].rfind('\n').map(|p| p + 1).unwrap_or(0);
+    content[line_start..]
+        .chars()
+        .take_while(|c| *c == ' ' || *c == '\t')
+        .collect()
+}
+
+// Offset of the next byte that is not whitespace or a comment
+fn next_code_offset(content: &str, from: usize) -> usize {
+    let mut parser = Parser::new(content);
+    parser.pos = from;
+    parser.skip_trivia();
+    parser.pos
+}
+
+// NOTE(angeldev)
+// Minimal JSON parser that records spans.
+// Accepts // and /* */ comments and trailing commas, as tsconfig.json does.
+struct Parser<'a> {
+    src: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(src: &'a str) -> Self {
+        Parser {
+            src,
+            bytes: src.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    fn parse_document(&mut self) -> Result<Node, String> {
+        // Skip a UTF-8 byte order mark
+        if self.src.starts_with('\u{feff}') {
+            self.pos = 3;
+        }
+        self.skip_trivia();
+        let root = self.parse_value()?;
+        self.skip_trivia();
+        if self.pos < self.bytes.len() {
+            return Err(self.error("unexpected content after the top-level value"));
+        }
+        Ok(root)
+    }
+
+    fn error(&self, message: &str) -> String {
+        let line = self.src[..self.pos.min(self.src.len())].matches('\n').count() + 1;
+        format!("{} at line {}", message, line)
+    }
+
+    fn skip_trivia(&mut self) {
+        while self.pos < self.bytes.len() {
+            let rest = &self.bytes[self.pos..];
+            if rest[0].is_ascii_whitespace() {
+                self.pos += 1;
+            } else if rest.starts_with(b"//") {
+                self.pos = self.src[self.pos..]
+                    .find('\n')
+                    .map(|p| self.pos + p)
+                    .unwrap_or(self.bytes.len());
+            } else if rest.starts_with(b"/*") {
+                self.pos = self.src[self.pos + 2..]
+                    .find("*/")
+                    .map(|p| self.pos + 2 + p + 2)
+                    .unwrap_or(self.bytes.len());
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Node, String> {
+        match self.bytes.get(self.pos) {
+            Some(b'{') => self.parse_object(),
+            Some(b'[') => self.parse_array(),
+            Some(b'"') => {
+                let start = self.pos;
+                self.parse_string()?;
+                Ok(Node::Scalar { start, end: self.pos })
+            }
+            Some(_) => self.parse_literal(),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<Node, String> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut members = Vec::new();
+
+        loop {
+            self.skip_trivia();
+            match self.bytes.get(self.pos) {
+                Some(b'}') => break,
+                Some(b'"') => {}
+                _ => return Err(self.error("expected a string key or '}'")),
+            }
+
+            let key_start = self.pos;
+            let key = self.parse_string()?;
+            self.skip_trivia();
+            if self.bytes.get(self.pos) != Some(&b':') {
+                return Err(self.error("expected ':' after key"));
+            }
+            self.pos += 1;
+            self.skip_trivia();
+            let value = self.parse_value()?;
+            members.push(Member { key, key_start, value });
+
+            self.skip_trivia();
+            match self.bytes.get(self.pos) {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => break,
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+
+        self.pos += 1;
+        Ok(Node::Object {
+            start,
+            end: self.pos,
+            members,
+        })
+    }
+
+    fn parse_array(&mut self) -> Result<Node, String> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut items = Vec::new();
+
+        loop {
+            self.skip_trivia();
+            if self.bytes.get(self.pos) == Some(&b']') {
+                break;
+            }
+            items.push(self.parse_value()?);
+
+            self.skip_trivia();
+            match self.bytes.get(self.pos) {
+                Some(b',') => self.pos += 1,
+                Some(b']') => break,
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+
+        self.pos += 1;
+        Ok(Node::Array {
+            start,
+            end: self.pos,
+            items,
+        })
+    }
+
+    // Parses a string literal and returns its decoded value
+    fn parse_string(&mut self) -> Result<String, String> {
+        let start = self.pos;
+        self.pos += 1;
+        while self.pos < self.bytes.len() {
+            match self.bytes[self.pos] {
+                b'\\' => self.pos += 2,
+                b'"' => {
+                    self.pos += 1;
+                    return serde_json::from_str(&self.src[start..self.pos])
+                        .map_err(|e| self.error(&format!("invalid string ({})", e)));
+                }
+                b'\n' => return Err(self.error("unterminated string")),
+                _ => self.pos += 1,
+            }
+        }
+        Err(self.error("unterminated string"))
+    }
+
+    // Parses true/false/null or a number
+    fn parse_literal(&mut self) -> Result<Node, String> {
+        let start = self.pos;
+        while self.pos < self.bytes.len()
+            && !matches!(self.bytes[self.pos], b',' | b'}' | b']' | b'/')
+            && !self.bytes[self.pos].is_ascii_whitespace()
+        {
+            self.pos += 1;
+        }
+
+        let token = &self.src[start..self.pos];
+        match serde_json::from_str::<Value>(token) {
+            Ok(value) if !value.is_object() && !value.is_array() && !value.is_string() => {
+                Ok(Node::Scalar { start, end: self.pos })
+            }
+            _ => {
+                self.pos = start;
+                Err(self.error(&format!("unexpected token '{}'", token)))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn keys(path: &str) -> Vec<String> {
+        super::super::parse_key_path(path).unwrap()
+    }
+
+    const TSCONFIG: &str = "{\n  // Compiler settings\n  \"compilerOptions\": {\n    \"strict\": true, // keep\n    \"paths\": {}\n  },\n  \"include\": [\"src\"],\n}\n";
+
+    #[test]
+    fn test_set_preserves_comments() {
+        let out = apply(TSCONFIG, &keys("compilerOptions.strict"), KeyOp::Set(&json!(false)), "  ").unwrap();
+        assert_eq!(out, TSCONFIG.replace("\"strict\": true", "\"strict\": false"));
+
+        let out = apply(TSCONFIG, &keys("compilerOptions.target"), KeyOp::Set(&json!("es2022")), "  ").unwrap();
+        assert!(out.contains("    \"paths\": {},\n    \"target\": \"es2022\"\n  },"), "{}", out);
+        assert!(out.contains("// Compiler settings"));
+    }
+
+    #[test]
+    fn test_append_and_delete() {
+        let out = apply(TSCONFIG, &keys("include"), KeyOp::Append(&json!("tests")), "  ").unwrap();
+        assert!(out.contains("\"include\": [\"src\", \"tests\"],"), "{}", out);
+
+        let out = apply(TSCONFIG, &keys("compilerOptions.strict"), KeyOp::Delete, "  ").unwrap();
+        assert!(out.contains("{\n    \"paths\": {}\n  }"), "{}", out);
+
+        let out = apply(TSCONFIG, &keys("include"), KeyOp::Delete, "  ").unwrap();
+        assert!(out.ends_with("    \"paths\": {}\n  },\n}\n"), "{}", out);
+    }
+
+    #[test]
+    fn test_inline_objects_stay_inline() {
+        let json = "{\"a\": 1, \"b\": 2}\n";
+        let out = apply(json, &keys("c"), KeyOp::Set(&json!({"d": [1, 2]})), "  ").unwrap();
+        assert_eq!(out, "{\"a\": 1, \"b\": 2, \"c\": {\"d\": [1, 2]}}\n");
+
+        let out = apply(json, &keys("b"), KeyOp::Set(&json!({"e": true})), "  ").unwrap();
+        assert_eq!(out, "{\"a\": 1, \"b\": {\"e\": true}}\n");
+
+        let err = apply("{\"a\": 1, \"a\": 2}", &keys("a"), KeyOp::Delete, "  ").unwrap_err();
+        assert!(matches!(err, KeyError::Invalid(_)));
+    }
+
+    #[test]
+    fn test_insert_after_trailing_comment() {
+        let json = "{\n  \"a\": 1 // one\n}";
+        let out = apply(json, &keys("b"), KeyOp::Set(&json!([1, 2])), "  ").unwrap();
+        assert_eq!(out, "{\n  \"a\": 1, // one\n  \"b\": [\n    1,\n    2\n  ]\n}");
+    }
+}
@@ -0,0 +1,282 @@
+// NOTE(angeldev)
+// TOML key-path editing on top of toml_edit, which keeps comments, key order
+// and whitespace of everything it doesn't touch.
+
+use super::{parse_index, KeyError, KeyOp};
+use serde_json::Value as Json;
+use toml_edit::{Array, ArrayOfTables, DocumentMut, InlineTable, Item, Table, TableLike, Value};
+
+// NOTE(angeldev)
+// Applies a key-path operation to TOML content.
+pub(super) fn apply(content: &str, keys: &[String], op: KeyOp) -> Result<String, KeyError> {
+    let mut doc: DocumentMut = content
+        .parse()
+        .map_err(|e: toml_edit::TomlError| KeyError::Parse(e.to_string().trim().to_string()))?;
+
+    edit_table(doc.as_table_mut(), false, keys, op)?;
+    Ok(doc.to_string())
+}
+
+// NOTE(angeldev)
+// Parses TOML into a JSON value (datetimes become strings).
+pub(super) fn read(content: &str) -> Result<Json, KeyError> {
+    let doc: DocumentMut = content
+        .parse()
+        .map_err(|e: toml_edit::TomlError| KeyError::Parse(e.to_string().trim().to_string()))?;
+    Ok(item_to_json(doc.as_item()))
+}
+
+fn item_to_json(item: &Item) -> Json {
+    match item {
+        Item::None => Json::Null,
+        Item::Value(value) => value_to_json(value),
+        Item::Table(table) => Json::Object(table.iter().map(|(k, v)| (k.to_string(), item_to_json(v))).collect()),
+        Item::ArrayOfTables(tables) => Json::Array(tables.iter().map(|t| item_to_json(&Item::Table(t.clone()))).collect()),
+    }
+}
+
+fn value_to_json(value: &Value) -> Json {
+    match value {
+        Value::String(s) => Json::from(s.value().as_str()),
+        Value::Integer(i) => Json::from(*i.value()),
+        Value::Float(f) => Json::from(*f.value()),
+        Value::Boolean(b) => Json::from(*b.value()),
+        Value::Datetime(d) => Json::from(d.value().to_string()),
+        Value::Array(array) => Json::Array(array.iter().map(value_to_json).collect()),
+        Value::InlineTable(table) => Json::Object(table.iter().map(|(k, v)| (k.to_string(), value_to_json(v))).collect()),
+    }
+}
+
+// NOTE(angeldev)
+// Dispatches on the kind of item found at the current path segment.
+fn edit_item(item: &mut Item, keys: &[String], op: KeyOp) -> Result<(), KeyError> {
+    match item {
+        Item::Table(table) => edit_table(table, false, keys, op),
+        Item::ArrayOfTables(tables) => edit_array_of_tables(tables, keys, op),
+        Item::Value(value) => edit_value(value, keys, op),
+        Item::None => Err(KeyError::NotFound),
+    }
+}
+
+fn edit_value(value: &mut Value, keys: &[String], op: KeyOp) -> Result<(), KeyError> {
+    match value {
+        Value::InlineTable(table) => edit_table(table, true, keys, op),
+        Value::Array(array) => edit_array(array, keys, op),
+        _ => Err(KeyError::Invalid(format!("'{}' is inside a scalar value", keys[0]))),
+    }
+}
+
+// NOTE(angeldev)
+// Edits a key of a standard or inline table, creating missing parents for set/append.
+fn edit_table(table: &mut dyn TableLike, inline: bool, keys: &[String], op: KeyOp) -> Result<(), KeyError> {
+    let (key, rest) = keys.split_first().expect("key paths are never empty");
+
+    if rest.is_empty() {
+        return match op {
+            KeyOp::Set(json) => {
+                let replace_table = matches!(table.get(key), Some(Item::Table(_)));
+                let mut item = if json.is_object() && replace_table && !inline {
+                    Item::Table(to_table(json)?)
+                } else {
+                    Item::Value(to_value(json)?)
+                };
+                // Keep the old value's surrounding whitespace and trailing comment
+                if let (Some(Item::Value(old)), Item::Value(new)) = (table.get(key), &mut item) {
+                    *new.decor_mut() = old.decor().clone();
+                }
+                table.insert(key, item);
+                Ok(())
+            }
+            KeyOp::Delete => table.remove(key).map(|_| ()).ok_or(KeyError::NotFound),
+            KeyOp::Append(json) => match table.get_mut(key) {
+                Some(Item::Value(Value::Array(array))) => {
+                    push_formatted(array, to_value(json)?);
+                    Ok(())
+                }
+                Some(Item::ArrayOfTables(tables)) => {
+                    tables.push(to_table(json)?);
+                    Ok(())
+                }
+                Some(_) => Err(KeyError::Invalid(format!("'{}' is not an array", key))),
+                None => {
+                    table.insert(key, Item::Value(Value::Array(Array::from_iter([to_value(json)?]))));
+                    Ok(())
+                }
+            },
+        };
+    }
+
+    if table.get(key).is_none() {
+        if let KeyOp::Delete = op {
+            return Err(KeyError::NotFound);
+        }
+        let parent = if inline {
+            Item::Value(Value::InlineTable(InlineTable::new()))
+        } else {
+            let mut new_table = Table::new();
+            new_table.set_implicit(true);
+            Item::Table(new_table)
+        };
+        table.insert(key, parent);
+    }
+
+    let child = table.get_mut(key).expect("key was just checked or inserted");
+    edit_item(child, rest, op)
+}
+
+// NOTE(angeldev)
+// Edits an element of an inline array by index (index == len appends on set).
+fn edit_array(array: &mut Array, keys: &[String], op: KeyOp) -> Result<(), KeyError> {
+    let (key, rest) = keys.split_first().expect("key paths are never empty");
+    let index = parse_index(key)?;
+
+    if rest.is_empty() {
+        return match op {
+            KeyOp::Set(json) if index < array.len() => {
+                array.replace(index, to_value(json)?);
+                Ok(())
+            }
+            KeyOp::Set(json) if index == array.len() => {
+                push_formatted(array, to_value(json)?);
+                Ok(())
+            }
+            KeyOp::Delete if index < array.len() => {
+                array.remove(index);
+                Ok(())
+            }
+            KeyOp::Append(json) => match array.get_mut(index) {
+                Some(Value::Array(inner)) => {
+                    push_formatted(inner, to_value(json)?);
+                    Ok(())
+                }
+                Some(_) => Err(KeyError::Invalid(format!("element {} is not an array", index))),
+                None => Err(KeyError::NotFound),
+            },
+            _ => Err(KeyError::NotFound),
+        };
+    }
+
+    match array.get_mut(index) {
+        Some(value) => edit_value(value, rest, op),
+        None => Err(KeyError::NotFound),
+    }
+}
+
+// NOTE(angeldev)
+// Edits a table of a [[header]] array by index.
+fn edit_array_of_tables(tables: &mut ArrayOfTables, keys: &[String], op: KeyOp) -> Result<(), KeyError> {
+    let (key, rest) = keys.split_first().expect("key paths are never empty");
+    let index = parse_index(key)?;
+
+    if rest.is_empty() {
+        return match op {
+            KeyOp::Set(json) if index < tables.len() => {
+                *tables.get_mut(index).expect("index was checked") = to_table(json)?;
+                Ok(())
+            }
+            KeyOp::Set(json) if index == tables.len() => {
+                tables.push(to_table(json)?);
+                Ok(())
+            }
+            KeyOp::Delete if index < tables.len() => {
+                tables.remove(index);
+                Ok(())
+            }
+            KeyOp::Append(_) => Err(KeyError::Invalid(format!("element {} is a table, not an array", index))),
+            _ => Err(KeyError::NotFound),
+        };
+    }
+
+    match tables.get_mut(index) {
+        Some(table) => edit_table(table, false, rest, op),
+        None => Err(KeyError::NotFound),
+    }
+}
+
+// NOTE(angeldev)
+// Pushes onto an array using the spacing of its last element, so multi-line
+// arrays stay one element per line.
+fn push_formatted(array: &mut Array, mut value: Value) {
+    let prefix = array
+        .iter()
+        .last()
+        .and_then(|last| last.decor().prefix())
+        .and_then(|p| p.as_str())
+        .filter(|p| !p.is_empty())
+        .unwrap_or(" ")
+        .to_string();
+
+    if array.is_empty() {
+        array.push(value);
+    } else {
+        value.decor_mut().set_prefix(prefix);
+        array.push_formatted(value);
+    }
+}
+
+// NOTE(angeldev)
+// Converts a JSON value from the edit into a TOML value.
+fn to_value(json: &Json) -> Result<Value, KeyError> {
+    Ok(match json {
+        Json::Null => return Err(KeyError::Invalid("TOML has no null value; use delete_key".to_string())),
+        Json::Bool(b) => Value::from(*b),
+        Json::Number(n) => match n.as_i64() {
+            Some(i) => Value::from(i),
+            None => Value::from(n.as_f64().unwrap_or_default()),
+        },
+        Json::String(s) => Value::from(s.as_str()),
+        Json::Array(items) => Value::Array(items.iter().map(to_value).collect::<Result<Array, _>>()?),
+        Json::Object(map) => {
+            let mut table = InlineTable::new();
+            for (k, v) in map {
+                table.insert(k.as_str(), to_value(v)?);
+            }
+            Value::InlineTable(table)
+        }
+    })
+}
+
+// NOTE(angeldev)
+// Converts a JSON object into a standard [table].
+fn to_table(json: &Json) -> Result<Table, KeyError> {
+    let map = json
+        .as_object()
+        .ok_or_else(|| KeyError::Invalid("array-of-tables elements must be objects".to_string()))?;
+
+    let mut table = Table::new();
+    for (k, v) in map {
+        table.insert(k, Item::Value(to_value(v)?));
+    }
+    Ok(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn keys(path: &str) -> Vec<String> {
+        super::super::parse_key_path(path).unwrap()
+    }
+
+    const CARGO: &str = "[package]\nname = \"app\" # the name\nversion = \"0.1.0\"\n\n[dependencies]\nserde = \"1.0\"\nfeatures = [\n    \"a\",\n]\n";
+
+    #[test]
+    fn test_set_keeps_comments() {
+        let out = apply(CARGO, &keys("package.name"), KeyOp::Set(&json!("web"))).unwrap();
+        assert!(out.starts_with("[package]\nname = \"web\" # the name\n"), "{}", out);
+
+        let out = apply(CARGO, &keys("dependencies.clap"), KeyOp::Set(&json!({"version": "4", "features": ["derive"]}))).unwrap();
+        assert!(out.contains("clap = { version = \"4\", features = [\"derive\"] }"), "{}", out);
+    }
+
+    #[test]
+    fn test_append_and_delete() {
+        let out = apply(CARGO, &keys("dependencies.features"), KeyOp::Append(&json!("b"))).unwrap();
+        assert!(out.contains("features = [\n    \"a\",\n    \"b\",\n]"), "{}", out);
+
+        let out = apply(CARGO, &keys("dependencies.serde"), KeyOp::Delete).unwrap();
+        assert!(!out.contains("serde"));
+        assert!(matches!(apply(CARGO, &keys("dependencies.nope"), KeyOp::Delete), Err(KeyError::NotFound)));
+    }
+}
@@ -0,0 +1,605 @@
+// NOTE(angeldev)
+// Line-based YAML key-path editor.
+// Navigates block mappings and sequences by indentation and rewrites only the
+// lines of the touched entry, so comments and formatting elsewhere are kept.
+// Flow collections ({a: 1}, [1, 2]) can be replaced whole or appended to but not
+// navigated into. The result is checked with serde_yaml before it is returned.
+
+use super::{nest_value, parse_index, KeyError, KeyOp};
+use serde_json::Value;
+
+// NOTE(angeldev)
+// A block collection: its first entry starts at (line, col); later entries
+// start at column `col` on following lines, up to (not including) line `end`.
+#[derive(Debug, Clone, Copy)]
+struct Block {
+    line: usize,
+    col: usize,
+    end: usize,
+}
+
+// NOTE(angeldev)
+// One entry of a collection: a `key: value` pair or a `- item`.
+// `value_col` is the column just after the `key:` or `-` marker.
+#[derive(Debug, Clone, Copy)]
+struct Slot {
+    line: usize,
+    col: usize,
+    value_col: usize,
+    end: usize,
+    seq: bool,
+}
+
+// NOTE(angeldev)
+// What an entry holds.
+#[derive(Debug, Clone, Copy)]
+enum Node {
+    Block(Block),
+    // A scalar or flow value starting on the slot's line
+    Inline,
+    // Nothing after the marker (null)
+    Empty,
+}
+
+// NOTE(angeldev)
+// Where a missing key would go.
+enum Parent {
+    Block(Block),
+    Slot(Slot),
+    Root,
+}
+
+struct Doc {
+    lines: Vec<String>,
+    unit: usize,
+}
+
+// NOTE(angeldev)
+// Parses YAML into a JSON value.
+pub(super) fn read(content: &str) -> Result<Value, KeyError> {
+    if content.trim().is_empty() {
+        return Ok(Value::Null);
+    }
+    serde_yaml::from_str(content).map_err(|e| KeyError::Parse(e.to_string()))
+}
+
+// NOTE(angeldev)
+// Applies a key-path operation to YAML content.
+pub(super) fn apply(content: &str, keys: &[String], op: KeyOp, unit: usize) -> Result<String, KeyError> {
+    serde_yaml::from_str::<serde_yaml::Value>(content).map_err(|e| KeyError::Parse(e.to_string()))?;
+
+    let mut doc = Doc {
+        lines: content.lines().map(|l| l.to_string()).collect(),
+        unit: unit.max(1),
+    };
+
+    match doc.find(keys)? {
+        Ok(slot) => match op {
+            KeyOp::Set(value) => doc.set_slot(slot, value),
+            KeyOp::Delete => doc.delete_slot(slot),
+            KeyOp::Append(value) => doc.append_to_slot(slot, value)?,
+        },
+        Err((parent, at)) => {
+            let value = match op {
+                KeyOp::Set(value) => value.clone(),
+                KeyOp::Append(value) => Value::Array(vec![value.clone()]),
+                KeyOp::Delete => return Err(KeyError::NotFound),
+            };
+            doc.insert_missing(parent, &keys[at..], value)?;
+        }
+    }
+
+    let mut output = doc.lines.join("\n");
+    if content.ends_with('\n') || content.is_empty() {
+        output.push('\n');
+    }
+
+    serde_yaml::from_str::<serde_yaml::Value>(&output)
+        .map_err(|e| KeyError::Invalid(format!("edit would produce invalid YAML ({})", e)))?;
+    Ok(output)
+}
+
+impl Doc {
+    // NOTE(angeldev)
+    // Resolves a key path to an existing slot, or to the parent a missing key
+    // belongs in along with the index of the first missing segment.
+    fn find(&self, keys: &[String]) -> Result<Result<Slot, (Parent, usize)>, KeyError> {
+        let mut node = self.root();
+        let mut parent_slot: Option<Slot> = None;
+
+        for (i, key) in keys.iter().enumerate() {
+            let slot = match node {
+                Node::Block(block) => {
+                    let children = self.children(block);
+                    let found = if self.is_seq(block) {
+                        children.get(parse_index(key)?).copied()
+                    } else {
+                        children.into_iter().find(|s| self.slot_key(*s).as_deref() == Some(key.as_str()))
+                    };
+                    match found {
+                        Some(slot) => slot,
+                        None => return Ok(Err((Parent::Block(block), i))),
+                    }
+                }
+                Node::Empty => return Ok(Err((parent_slot.map(Parent::Slot).unwrap_or(Parent::Root), i))),
+                Node::Inline => {
+                    let slot = parent_slot.expect("the root is never inline");
+                    if is_null(&self.inline_text(slot).0) {
+                        return Ok(Err((Parent::Slot(slot), i)));
+                    }
+                    return Err(KeyError::Invalid(format!(
+                        "'{}' holds a scalar or flow value; set the whole value instead",
+                        keys[..i].join(".")
+                    )));
+                }
+            };
+
+            if i + 1 == keys.len() {
+                return Ok(Ok(slot));
+            }
+            node = self.slot_value(slot);
+            parent_slot = Some(slot);
+        }
+
+        unreachable!("key paths are never empty")
+    }
+
+    // The top-level collection, skipping directives and the document marker
+    fn root(&self) -> Node {
+        self.lines
+            .iter()
+            .enumerate()
+            .find(|(_, l)| !is_ignorable(l) && !l.starts_with("---") && !l.starts_with('%'))
+            .map(|(line, l)| {
+                Node::Block(Block {
+                    line,
+                    col: indent_of(l),
+                    end: self.lines.len(),
+                })
+            })
+            .unwrap_or(Node::Empty)
+    }
+
+    fn is_seq(&self, block: Block) -> bool {
+        is_seq_item(&self.lines[block.line][block.col..])
+    }
+
+    // NOTE(angeldev)
+    // Lists the entries of a block. Trailing blank and comment lines are not part
+    // of an entry, so deletes and inserts leave them where they are.
+    fn children(&self, block: Block) -> Vec<Slot> {
+        let seq = self.is_seq(block);
+        let mut starts = vec![block.line];
+
+        for line in block.line + 1..block.end {
+            let text = &self.lines[line];
+            if is_ignorable(text) || indent_of(text) != block.col {
+                continue;
+            }
+            // A `- ` line at a mapping's indent continues the previous key's list
+            if is_seq_item(&text[block.col..]) == seq {
+                starts.push(line);
+            }
+        }
+
+        starts
+            .iter()
+            .enumerate()
+            .map(|(i, &line)| {
+                let limit = starts.get(i + 1).copied().unwrap_or(block.end);
+                let end = (line + 1..limit)
+                    .rev()
+                    .find(|l| !is_ignorable(&self.lines[*l]))
+                    .map(|l| l + 1)
+                    .unwrap_or(line + 1);
+                let marker = if seq {
+                    1
+                } else {
+                    split_key(&self.lines[line][block.col..]).map(|(_, len)| len).unwrap_or(0)
+                };
+                Slot {
+                    line,
+                    col: block.col,
+                    value_col: block.col + marker,
+                    end,
+                    seq,
+                }
+            })
+            .collect()
+    }
+
+    fn slot_key(&self, slot: Slot) -> Option<String> {
+        if slot.seq {
+            return None;
+        }
+        split_key(&self.lines[slot.line][slot.col..]).map(|(key, _)| key)
+    }
+
+    // NOTE(angeldev)
+    // Classifies the value of an entry.
+    fn slot_value(&self, slot: Slot) -> Node {
+        let rest = &self.lines[slot.line][slot.value_col..];
+        let trimmed = rest.trim_start();
+
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            return (slot.line + 1..slot.end)
+                .find(|l| !is_ignorable(&self.lines[*l]))
+                .map(|line| {
+                    Node::Block(Block {
+                        line,
+                        col: indent_of(&self.lines[line]),
+                        end: slot.end,
+                    })
+                })
+                .unwrap_or(Node::Empty);
+        }
+
+        // Compact collections inside a list item: `- name: x` or `- - x`
+        if slot.seq && (is_seq_item(trimmed) || split_key(trimmed).is_some()) {
+            return Node::Block(Block {
+                line: slot.line,
+                col: slot.value_col + rest.len() - trimmed.len(),
+                end: slot.end,
+            });
+        }
+
+        Node::Inline
+    }
+
+    // Inline value text of a slot and its trailing comment (with leading spaces)
+    fn inline_text(&self, slot: Slot) -> (String, String) {
+        let rest = &self.lines[slot.line][slot.value_col..];
+        let (value, comment) = split_comment(rest);
+        (value.trim().to_string(), comment.to_string())
+    }
+
+    // NOTE(angeldev)
+    // Rewrites an entry with a new value, keeping its key (or dash) and, for
+    // one-line values, its trailing comment.
+    fn set_slot(&mut self, slot: Slot, value: &Value) {
+        let prefix = self.lines[slot.line][..slot.value_col].to_string();
+        let nested = if slot.seq { slot.col + 2 } else { slot.col + self.unit };
+        let mut new_lines = self.render_entry(&prefix, value, nested, slot.seq);
+
+        if new_lines.len() == 1 && slot.end == slot.line + 1 {
+            if let Node::Inline = self.slot_value(slot) {
+                new_lines[0].push_str(&self.inline_text(slot).1);
+            }
+        }
+
+        self.lines.splice(slot.line..slot.end, new_lines);
+    }
+
+    // NOTE(angeldev)
+    // Removes an entry. The first key of a compact list item (`- a: 1`) hands
+    // the dash over to the next key.
+    fn delete_slot(&mut self, slot: Slot) {
+        let compact = indent_of(&self.lines[slot.line]) < slot.col;
+        if !compact {
+            self.lines.drain(slot.line..slot.end);
+            return;
+        }
+
+        let block = Block {
+            line: slot.line,
+            col: slot.col,
+            end: self.block_end(slot),
+        };
+        let dash = self.lines[slot.line][..slot.col].to_string();
+        match self.children(block).get(1) {
+            Some(next) => {
+                let merged = format!("{}{}", dash, &self.lines[next.line][next.col..]);
+                self.lines.splice(slot.line..next.line + 1, [merged]);
+            }
+            None => {
+                self.lines.splice(slot.line..slot.end, [format!("{}{{}}", dash)]);
+            }
+        }
+    }
+
+    // End of the compact block a slot belongs to: the next line indented less
+    fn block_end(&self, slot: Slot) -> usize {
+        (slot.line + 1..self.lines.len())
+            .find(|l| !is_ignorable(&self.lines[*l]) && indent_of(&self.lines[*l]) < slot.col)
+            .unwrap_or(self.lines.len())
+    }
+
+    // NOTE(angeldev)
+    // Appends to the list held by an entry.
+    fn append_to_slot(&mut self, slot: Slot, value: &Value) -> Result<(), KeyError> {
+        match self.slot_value(slot) {
+            Node::Block(block) if self.is_seq(block) => {
+                self.insert_item(block, value);
+                Ok(())
+            }
+            Node::Block(_) => Err(KeyError::Invalid("value is a mapping, not a list".to_string())),
+            Node::Empty => {
+                self.set_slot(slot, &Value::Array(vec![value.clone()]));
+                Ok(())
+            }
+            Node::Inline => {
+                let (text, comment) = self.inline_text(slot);
+                if is_null(&text) || text == "[]" {
+                    self.set_slot(slot, &Value::Array(vec![value.clone()]));
+                    return Ok(());
+                }
+                if !(text.starts_with('[') && text.ends_with(']')) || slot.end != slot.line + 1 {
+                    return Err(KeyError::Invalid("value is not a list".to_string()));
+                }
+
+                let inner = &text[1..text.len() - 1];
+                let separator = if inner.trim().is_empty() { "" } else { ", " };
+                let prefix = &self.lines[slot.line][..slot.value_col];
+                self.lines[slot.line] = format!(
+                    "{} [{}{}{}]{}",
+                    prefix,
+                    inner.trim_end(),
+                    separator,
+                    flow_scalar(value),
+                    comment
+                );
+                Ok(())
+            }
+        }
+    }
+
+    // NOTE(angeldev)
+    // Adds a value for keys that don't exist yet under `parent`.
+    fn insert_missing(&mut self, parent: Parent, keys: &[String], value: Value) -> Result<(), KeyError> {
+        let (key, rest) = keys.split_first().expect("key paths are never empty");
+        let nested_value = nest_value(rest, value);
+
+        match parent {
+            Parent::Block(block) if self.is_seq(block) => {
+                let len = self.children(block).len();
+                if parse_index(key)? != len {
+                    return Err(KeyError::Invalid(format!(
+                        "index {} is out of range (list has {} items)",
+                        key, len
+                    )));
+                }
+                self.insert_item(block, &nested_value);
+            }
+            Parent::Block(block) => {
+                let at = self.children(block).last().map(|s| s.end).unwrap_or(block.line + 1);
+                let prefix = format!("{}{}:", " ".repeat(block.col), plain_or_quoted(key));
+                let lines = self.render_entry(&prefix, &nested_value, block.col + self.unit, false);
+                self.lines.splice(at..at, lines);
+            }
+            Parent::Slot(slot) => {
+                let wrapped = if key == "0" {
+                    Value::Array(vec![nested_value])
+                } else {
+                    nest_value(std::slice::from_ref(key), nested_value)
+                };
+                self.set_slot(slot, &wrapped);
+            }
+            Parent::Root => {
+                let wrapped = nest_value(std::slice::from_ref(key), nested_value);
+                let lines = self.render_block(&wrapped, 0);
+                self.lines.extend(lines);
+            }
+        }
+        Ok(())
+    }
+
+    // Appends a `- item` after the last item of a block list
+    fn insert_item(&mut self, block: Block, value: &Value) {
+        let at = self.children(block).last().map(|s| s.end).unwrap_or(block.line + 1);
+        let dash = format!("{}-", " ".repeat(block.col));
+        let lines = self.render_entry(&dash, value, block.col + 2, true);
+        self.lines.splice(at..at, lines);
+    }
+
+    // NOTE(angeldev)
+    // Renders `prefix value` where prefix ends in `key:` or `-`.
+    // Collections go on the following lines at `nested` indent; in a list item
+    // (compact) the first of those lines joins the dash line.
+    fn render_entry(&self, prefix: &str, value: &Value, nested: usize, compact: bool) -> Vec<String> {
+        if is_scalar_like(value) {
+            return vec![format!("{} {}", prefix, flow_scalar(value))];
+        }
+
+        let mut block = self.render_block(value, nested);
+        if compact {
+            let first = block[0].trim_start().to_string();
+            block[0] = format!("{} {}", prefix, first);
+            block
+        } else {
+            let mut lines = vec![prefix.to_string()];
+            lines.append(&mut block);
+            lines
+        }
+    }
+
+    // Renders a non-empty collection as block YAML at the given indent
+    fn render_block(&self, value: &Value, indent: usize) -> Vec<String> {
+        let pad = " ".repeat(indent);
+        match value {
+            Value::Object(map) => map
+                .iter()
+                .flat_map(|(k, v)| {
+                    let prefix = format!("{}{}:", pad, plain_or_quoted(k));
+                    self.render_entry(&prefix, v, indent + self.unit, false)
+                })
+                .collect(),
+            Value::Array(items) => items
+                .iter()
+                .flat_map(|v| self.render_entry(&format!("{}-", pad), v, indent + 2, true))
+                .collect(),
+            _ => vec![format!("{}{}", pad, flow_scalar(value))],
+        }
+    }
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+fn is_ignorable(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.is_empty() || trimmed.starts_with('#')
+}
+
+fn is_seq_item(text: &str) -> bool {
+    text == "-" || text.starts_with("- ") || text.starts_with("-\t")
+}
+
+fn is_null(text: &str) -> bool {
+    matches!(text, "" | "~" | "null" | "Null" | "NULL")
+}
+
+fn is_scalar_like(value: &Value) -> bool {
+    match value {
+        Value::Object(map) => map.is_empty(),
+        Value::Array(items) => items.is_empty(),
+        _ => true,
+    }
+}
+
+// NOTE(angeldev)
+// Splits `key: rest` and returns the key and the length through the colon.
+// Handles quoted keys; returns None for list items and lines without a key.
+fn split_key(text: &str) -> Option<(String, usize)> {
+    if is_seq_item(text) || text.starts_with('#') || text.starts_with('[') || text.starts_with('{') {
+        return None;
+    }
+
+    let (key, key_len) = match text.chars().next()? {
+        '"' => {
+            let close = text[1..]
+                .char_indices()
+                .scan(false, |escaped, (i, c)| {
+                    let hit = !*escaped && c == '"';
+                    *escaped = !*escaped && c == '\\';
+                    Some((i, hit))
+                })
+                .find(|(_, hit)| *hit)
+                .map(|(i, _)| i + 1)?;
+            (serde_json::from_str(&text[..=close]).ok()?, close + 1)
+        }
+        '\'' => {
+            let close = text[1..].find('\'')? + 1;
+            (text[1..close].to_string(), close + 1)
+        }
+        _ => {
+            let colon = text
+                .char_indices()
+                .find(|(i, c)| *c == ':' && text[i + 1..].chars().next().is_none_or(|n| n == ' ' || n == '\t'))
+                .map(|(i, _)| i)?;
+            (text[..colon].trim_end().to_string(), colon)
+        }
+    };
+
+    let after = &text[key_len..];
+    let colon_offset = after.len() - after.trim_start().len();
+    after.trim_start().strip_prefix(':')?;
+    Some((key, key_len + colon_offset + 1))
+}
+
+// NOTE(angeldev)
+// Splits an inline value from a trailing `# comment`, ignoring # inside quotes.
+fn split_comment(text: &str) -> (&str, &str) {
+    let mut quote: Option<char> = None;
+    let mut prev = ' ';
+    for (i, c) in text.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '#' && (prev == ' ' || prev == '\t') => {
+                let value_end = text[..i].trim_end().len();
+                return (&text[..value_end], &text[value_end..]);
+            }
+            None => {}
+        }
+        prev = c;
+    }
+    (text, "")
+}
+
+// NOTE(angeldev)
+// Renders a scalar (or empty collection) the way YAML reads it back unchanged.
+fn flow_scalar(value: &Value) -> String {
+    match value {
+        Value::String(s) => plain_or_quoted(s),
+        Value::Null => "null".to_string(),
+        _ => value.to_string(),
+    }
+}
+
+// NOTE(angeldev)
+// Leaves strings plain when YAML would read them back as the same string,
+// otherwise double-quotes them (JSON string syntax is valid YAML).
+fn plain_or_quoted(s: &str) -> String {
+    let reserved = matches!(
+        s.to_lowercase().as_str(),
+        "" | "~" | "null" | "true" | "false" | "yes" | "no" | "on" | "off" | "y" | "n"
+    );
+    let special_start = s.starts_with(|c: char| "-?:,[]{}#&*!|>'\"%@` ".contains(c));
+    let ambiguous = s.contains(": ")
+        || s.contains(" #")
+        || s.ends_with(':')
+        || s.ends_with(' ')
+        || s.contains('\n')
+        || s.contains('\t')
+        || s.parse::<f64>().is_ok();
+
+    if reserved || special_start || ambiguous {
+        Value::String(s.to_string()).to_string()
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn keys(path: &str) -> Vec<String> {
+        super::super::parse_key_path(path).unwrap()
+    }
+
+    const WORKFLOW: &str = "# CI\nname: ci\non:\n  push:\n    branches: [main] # default\njobs:\n  build:\n    steps:\n      - uses: actions/checkout@v4\n      - name: Test\n        run: make test\n";
+
+    #[test]
+    fn test_set_keeps_comments() {
+        let out = apply(WORKFLOW, &keys("on.push.branches"), KeyOp::Set(&json!(["main", "dev"])), 2).unwrap();
+        assert!(out.contains("  push:\n    branches:\n      - main\n      - dev\njobs:"), "{}", out);
+        assert!(out.starts_with("# CI\n"));
+
+        let out = apply(WORKFLOW, &keys("name"), KeyOp::Set(&json!("build")), 2).unwrap();
+        assert!(out.starts_with("# CI\nname: build\n"), "{}", out);
+
+        let out = apply(WORKFLOW, &keys("jobs.build.runs-on"), KeyOp::Set(&json!("ubuntu-latest")), 2).unwrap();
+        assert!(out.ends_with("        run: make test\n    runs-on: ubuntu-latest\n"), "{}", out);
+    }
+
+    #[test]
+    fn test_append_to_lists() {
+        let out = apply(WORKFLOW, &keys("on.push.branches"), KeyOp::Append(&json!("dev")), 2).unwrap();
+        assert!(out.contains("branches: [main, dev] # default"), "{}", out);
+
+        let step = json!({"name": "Lint", "run": "make lint"});
+        let out = apply(WORKFLOW, &keys("jobs.build.steps"), KeyOp::Append(&step), 2).unwrap();
+        assert!(out.ends_with("        run: make test\n      - name: Lint\n        run: make lint\n"), "{}", out);
+    }
+
+    #[test]
+    fn test_delete() {
+        let out = apply(WORKFLOW, &keys("jobs.build.steps.1.name"), KeyOp::Delete, 2).unwrap();
+        assert!(out.ends_with("      - uses: actions/checkout@v4\n      - run: make test\n"), "{}", out);
+
+        let out = apply(WORKFLOW, &keys("on"), KeyOp::Delete, 2).unwrap();
+        assert_eq!(out.lines().nth(2), Some("jobs:"));
+    }
+
+    #[test]
+    fn test_plain_or_quoted() {
+        assert_eq!(plain_or_quoted("hello world"), "hello world");
+        assert_eq!(plain_or_quoted("yes"), "\"yes\"");
+        assert_eq!(plain_or_quoted("1.0"), "\"1.0\"");
+        assert_eq!(plain_or_quoted("a: b"), "\"a: b\"");
+    }
+}
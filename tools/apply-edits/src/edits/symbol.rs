@@ -0,0 +1,249 @@
+// NOTE(angeldev)
+// Cross-file symbol renaming.
+// Handles rename_symbol, which replaces an identifier in every file matching a
+// set of globs. Matches respect word boundaries (renaming Foo leaves FooBar alone)
+// and can skip strings and comments using the lexical scanner.
+
+use crate::edits::{read_file, write_file};
+use crate::error::{EditError, EditResult, FileChange};
+use crate::language::Language;
+use crate::scanner::code_mask;
+use crate::walk::expand_globs;
+use std::path::Path;
+
+// NOTE(angeldev)
+// Options for matching a symbol.
+#[derive(Debug, Clone, Copy)]
+pub struct SymbolOptions {
+    pub word_boundary: bool,
+    pub skip_strings_and_comments: bool,
+}
+
+// NOTE(angeldev)
+// A file's new content and how many occurrences changed.
+#[derive(Debug)]
+pub struct PlannedFile {
+    pub path: String,
+    pub content: String,
+    pub count: usize,
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+// NOTE(angeldev)
+// Replaces every occurrence of `from` that passes the options.
+// Boundaries are only enforced on sides where `from` itself starts or ends with a
+// word character, so symbols like `$store` or `foo.bar` still match.
+pub fn replace_symbol(content: &str, language: Language, from: &str, to: &str, options: SymbolOptions) -> (String, usize) {
+    let mask = options.skip_strings_and_comments.then(|| code_mask(content, language));
+    let check_start = options.word_boundary && from.starts_with(is_word_char);
+    let check_end = options.word_boundary && from.ends_with(is_word_char);
+
+    let mut output = String::with_capacity(content.len());
+    let mut last = 0;
+    let mut count = 0;
+
+    for (pos, _) in content.match_indices(from) {
+        let end = pos + from.len();
+        if check_start && content[..pos].chars().next_back().is_some_and(is_word_char) {
+            continue;
+        }
+        if check_end && content[end..].chars().next().is_some_and(is_word_char) {
+            continue;
+        }
+        if mask.as_ref().is_some_and(|m| !m[pos]) {
+            continue;
+        }
+
+        output.push_str(&content[last..pos]);
+        output.push_str(to);
+        last = end;
+        count += 1;
+    }
+
+    output.push_str(&content[last..]);
+    (output, count)
+}
+
+// NOTE(angeldev)
+// Computes the rename for every matching file without writing anything.
+// Files that can't be read as text are skipped; files without matches are left out.
+pub fn plan_rename_symbol(
+    workdir: &Path,
+    patterns: &[String],
+    from: &str,
+    to: &str,
+    options: SymbolOptions,
+) -> EditResult<Vec<PlannedFile>> {
+    if from.is_empty() {
+        return Err(EditError::InvalidEdit {
+            reason: "Symbol to rename cannot be empty".to_string(),
+        });
+    }
+    if to.contains('\n') || from.contains('\n') {
+        return Err(EditError::InvalidEdit {
+            reason: "Symbols cannot span lines".to_string(),
+        });
+    }
+    if patterns.is_empty() {
+        return Err(EditError::InvalidEdit {
+            reason: "rename_symbol needs at least one glob in 'paths'".to_string(),
+        });
+    }
+
+    let mut planned = Vec::new();
+    for path in expand_globs(workdir, patterns)? {
+        let content = match read_file(workdir, &path) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        let (new_content, count) = replace_symbol(&content, Language::from_path(&path), from, to, options);
+        if count > 0 {
+            planned.push(PlannedFile {
+                path,
+                content: new_content,
+                count,
+            });
+        }
+    }
+    Ok(planned)
+}
+
+// NOTE(angeldev)
+// Summarizes planned changes as a message and per-file counts.
+pub fn summarize(from: &str, to: &str, planned: &[PlannedFile]) -> (String, Vec<FileChange>) {
+    let total: usize = planned.iter().map(|p| p.count).sum();
+    let message = format!(
+        "Renamed {} to {}: {} occurrence(s) in {} file(s)",
+        from,
+        to,
+        total,
+        planned.len()
+    );
+    let files = planned
+        .iter()
+        .map(|p| FileChange {
+            path: p.path.clone(),
+            count: p.count,
+        })
+        .collect();
+    (message, files)
+}
+
+// NOTE(angeldev)
+// Returns a message if no file still has `from` but some already have `to`,
+// so a re-sent rename is reported as already applied rather than not found.
+pub fn already_renamed(workdir: &Path, patterns: &[String], from: &str, to: &str, options: SymbolOptions) -> Option<String> {
+    if !plan_rename_symbol(workdir, patterns, from, to, options).ok()?.is_empty() || to.is_empty() {
+        return None;
+    }
+    let renamed = plan_rename_symbol(workdir, patterns, to, to, options).ok()?;
+    let total: usize = renamed.iter().map(|p| p.count).sum();
+    (total > 0).then(|| format!("Already renamed: {} occurrence(s) of {} in {} file(s)", total, to, renamed.len()))
+}
+
+// NOTE(angeldev)
+// Applies a rename_symbol operation.
+// Every file is computed before any is written, so a bad glob or missing symbol
+// changes nothing; the transaction has backed up all matching files beforehand.
+pub fn apply_rename_symbol(
+    workdir: &Path,
+    patterns: &[String],
+    from: &str,
+    to: &str,
+    options: SymbolOptions,
+) -> EditResult<(String, Vec<FileChange>)> {
+    let planned = plan_rename_symbol(workdir, patterns, from, to, options)?;
+    if planned.is_empty() {
+        return Err(EditError::SearchNotFound {
+            path: patterns.join(", "),
+            search_preview: from.to_string(),
+            closest_matches: Vec::new(),
+        });
+    }
+
+    for file in &planned {
+        write_file(workdir, &file.path, &file.content)?;
+    }
+    Ok(summarize(from, to, &planned))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    const WORDS: SymbolOptions = SymbolOptions {
+        word_boundary: true,
+        skip_strings_and_comments: false,
+    };
+
+    #[test]
+    fn test_replace_symbol_word_boundary() {
+        let src = "let foo = Foo::new(); FooBar::x(); myFoo(Foo);";
+        let (out, count) = replace_symbol(src, Language::Rust, "Foo", "Baz", WORDS);
+        assert_eq!(out, "let foo = Baz::new(); FooBar::x(); myFoo(Baz);");
+        assert_eq!(count, 2);
+
+        let raw = SymbolOptions {
+            word_boundary: false,
+            ..WORDS
+        };
+        assert_eq!(replace_symbol(src, Language::Rust, "Foo", "Baz", raw).1, 4);
+    }
+
+    #[test]
+    fn test_replace_symbol_skips_strings_and_comments() {
+        let src = "// uses total\nconst total = 1; log(\"total\", total);\n";
+        let options = SymbolOptions {
+            skip_strings_and_comments: true,
+            ..WORDS
+        };
+        let (out, count) = replace_symbol(src, Language::JavaScript, "total", "sum", options);
+        assert_eq!(out, "// uses total\nconst sum = 1; log(\"total\", sum);\n");
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn test_apply_rename_symbol() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/a.ts"), "export class User {}\n").unwrap();
+        fs::write(dir.path().join("src/b.ts"), "import { User } from './a';\nconst u: User = new User();\n").unwrap();
+        fs::write(dir.path().join("src/c.ts"), "const UserList = [];\n").unwrap();
+
+        let patterns = vec!["src/**/*.ts".to_string()];
+        let (message, files) = apply_rename_symbol(dir.path(), &patterns, "User", "Account", WORDS).unwrap();
+        assert_eq!(message, "Renamed User to Account: 4 occurrence(s) in 2 file(s)");
+        assert_eq!(files[1], FileChange { path: "src/b.ts".to_string(), count: 3 });
+        assert_eq!(fs::read_to_string(dir.path().join("src/c.ts")).unwrap(), "const UserList = [];\n");
+
+        let err = apply_rename_symbol(dir.path(), &patterns, "User", "Account", WORDS).unwrap_err();
+        assert!(matches!(err, EditError::SearchNotFound { .. }));
+        let again = already_renamed(dir.path(), &patterns, "User", "Account", WORDS).unwrap();
+        assert_eq!(again, "Already renamed: 4 occurrence(s) of Account in 2 file(s)");
+        assert!(already_renamed(dir.path(), &patterns, "Missing", "Other", WORDS).is_none());
+    }
+
+    #[test]
+    fn test_outcomes_name_every_glob() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/a.ts"), "const x = 1;\n").unwrap();
+
+        let edit: crate::Edit = serde_json::from_str(
+            r#"{"type": "rename_symbol", "paths": ["src/*.ts", "lib/*.ts"], "from": "Missing", "to": "Other"}"#,
+        )
+        .unwrap();
+        for dry_run in [true, false] {
+            let result = crate::apply_edits_with_options(dir.path(), std::slice::from_ref(&edit), dry_run, false);
+            match &result.edits[0] {
+                crate::EditOutcome::Error { path, .. } => assert_eq!(path, "src/*.ts, lib/*.ts"),
+                other => panic!("{:?}", other),
+            }
+        }
+    }
+}
@@ -2,6 +2,7 @@
 // Error types for the apply-edits tool.
 // Uses thiserror for ergonomic error handling and display formatting.
 
+use crate::verify::VerifyOutcome;
 use serde::Serialize;
 use thiserror::Error;
 
@@ -13,6 +14,9 @@ pub enum EditError {
     #[error("File not found: {path}")]
     FileNotFound { path: String },
 
+    #[error("File already exists: {path}")]
+    FileExists { path: String },
+
     #[error("Search string not found in file: {path}")]
     SearchNotFound {
         path: String,
@@ -27,6 +31,41 @@ pub enum EditError {
         closest_matches: Vec<ClosestMatch>,
     },
 
+    #[error("{which} marker not found in file: {path}")]
+    MarkerNotFound {
+        path: String,
+        which: String,
+        marker_preview: String,
+        closest_matches: Vec<ClosestMatch>,
+    },
+
+    #[error("Invalid markers: {reason}: {path}")]
+    InvalidMarkers { path: String, reason: String },
+
+    #[error("Failed to parse {format}: {reason}: {path}")]
+    ParseError {
+        path: String,
+        format: String,
+        reason: String,
+    },
+
+    #[error("Key '{key_path}' not found: {path}")]
+    KeyNotFound { path: String, key_path: String },
+
+    #[error("Invalid key path '{key_path}': {reason}: {path}")]
+    InvalidKeyPath {
+        path: String,
+        key_path: String,
+        reason: String,
+    },
+
+    #[error("Block not found after header at line {line}: {reason}: {path}")]
+    BlockNotFound {
+        path: String,
+        line: usize,
+        reason: String,
+    },
+
     #[error("Line {line} out of range (file has {total_lines} lines): {path}")]
     LineOutOfRange {
         path: String,
@@ -63,6 +102,48 @@ pub enum EditError {
 
     #[error("Invalid edit: {reason}")]
     InvalidEdit { reason: String },
+
+    #[error("Edit {second} conflicts with edit {first}: {reason}: {path}")]
+    EditConflict {
+        path: String,
+        first: usize,
+        second: usize,
+        reason: String,
+    },
+
+    #[error("Syntax broken at line {line}, column {column}: {reason}: {path}")]
+    SyntaxRegression {
+        path: String,
+        line: usize,
+        column: usize,
+        reason: String,
+    },
+
+    #[error("No files match: {pattern}")]
+    NoFilesMatched { pattern: String },
+
+    #[error("{count} files match, more than the limit of {limit}: {pattern}")]
+    TooManyFiles {
+        pattern: String,
+        count: usize,
+        limit: usize,
+    },
+
+    #[error("Lines {start_line}-{end_line} were changed by an earlier edit in this request: {path}")]
+    LinesModified {
+        path: String,
+        start_line: usize,
+        end_line: usize,
+    },
+
+    #[error("Lines {start_line}-{end_line} do not match the expected content: {path}")]
+    ExpectedMismatch {
+        path: String,
+        start_line: usize,
+        end_line: usize,
+        expected_preview: String,
+        actual_preview: String,
+    },
 }
 
 // NOTE(jimmylee)
@@ -77,6 +158,14 @@ pub struct ClosestMatch {
     pub context_after: Vec<String>,
 }
 
+// NOTE(angeldev)
+// How many changes a multi-file edit made to one file.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FileChange {
+    pub path: String,
+    pub count: usize,
+}
+
 // NOTE(jimmylee)
 // Result type alias for edit operations.
 pub type EditResult<T> = Result<T, EditError>;
@@ -97,6 +186,8 @@ pub enum EditOutcome {
         lines_affected: Option<Vec<usize>>,
         #[serde(skip_serializing_if = "Option::is_none")]
         message: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        files: Option<Vec<FileChange>>,
     },
     #[serde(rename = "error")]
     Error {
@@ -134,6 +225,7 @@ impl EditOutcome {
             edit_type: edit_type.to_string(),
             lines_affected: None,
             message: None,
+            files: None,
         }
     }
 
@@ -152,6 +244,35 @@ impl EditOutcome {
             edit_type: edit_type.to_string(),
             lines_affected,
             message,
+            files: None,
+        }
+    }
+
+    // NOTE(angeldev)
+    // Creates a successful outcome for an edit that changed several files.
+    pub fn ok_with_files(index: usize, path: &str, edit_type: &str, message: String, files: Vec<FileChange>) -> Self {
+        EditOutcome::Ok {
+            index,
+            path: path.to_string(),
+            edit_type: edit_type.to_string(),
+            lines_affected: None,
+            message: Some(message),
+            files: Some(files),
+        }
+    }
+
+    // NOTE(angeldev)
+    // Creates an error outcome for an edit that succeeded but was undone.
+    pub fn rolled_back(index: usize, path: &str, edit_type: &str, reason: &str) -> Self {
+        EditOutcome::Error {
+            index,
+            path: path.to_string(),
+            edit_type: edit_type.to_string(),
+            error: "rolled_back".to_string(),
+            message: reason.to_string(),
+            search_preview: None,
+            closest_matches: None,
+            hint: None,
         }
     }
 
@@ -187,6 +308,60 @@ impl EditOutcome {
                 closest_matches: Some(closest_matches.clone()),
                 hint: Some(generate_hint_for_search_not_found(closest_matches)),
             },
+            EditError::MarkerNotFound {
+                marker_preview,
+                closest_matches,
+                ..
+            } => EditOutcome::Error {
+                index,
+                path: path.to_string(),
+                edit_type: edit_type.to_string(),
+                error: "marker_not_found".to_string(),
+                message: error.to_string(),
+                search_preview: Some(marker_preview.clone()),
+                closest_matches: Some(closest_matches.clone()),
+                hint: Some(generate_hint_for_search_not_found(closest_matches)),
+            },
+            EditError::ExpectedMismatch {
+                expected_preview,
+                actual_preview,
+                ..
+            } => EditOutcome::Error {
+                index,
+                path: path.to_string(),
+                edit_type: edit_type.to_string(),
+                error: error_code(error),
+                message: error.to_string(),
+                search_preview: Some(expected_preview.clone()),
+                closest_matches: None,
+                hint: Some(format!(
+                    "The file changed since it was read. The lines currently contain:\n{}",
+                    actual_preview
+                )),
+            },
+            EditError::SyntaxRegression { line, .. } => EditOutcome::Error {
+                index,
+                path: path.to_string(),
+                edit_type: edit_type.to_string(),
+                error: error_code(error),
+                message: error.to_string(),
+                search_preview: None,
+                closest_matches: None,
+                hint: Some(format!(
+                    "The file parsed before this request and does not after it. Check the edits near line {} for a missing or extra bracket, quote or indent.",
+                    line
+                )),
+            },
+            EditError::EditConflict { .. } => EditOutcome::Error {
+                index,
+                path: path.to_string(),
+                edit_type: edit_type.to_string(),
+                error: error_code(error),
+                message: error.to_string(),
+                search_preview: None,
+                closest_matches: None,
+                hint: Some("Combine the two edits into one, or make them target separate lines.".to_string()),
+            },
             _ => EditOutcome::Error {
                 index,
                 path: path.to_string(),
@@ -212,6 +387,24 @@ impl EditOutcome {
         }
     }
 
+    // NOTE(angeldev)
+    // Returns the request index of the edit this outcome belongs to.
+    pub fn index(&self) -> usize {
+        match self {
+            EditOutcome::Ok { index, .. } | EditOutcome::Error { index, .. } | EditOutcome::Warning { index, .. } => *index,
+        }
+    }
+
+    // NOTE(angeldev)
+    // Points the outcome at another request index (used after `paths` expansion).
+    pub fn set_index(&mut self, new_index: usize) {
+        match self {
+            EditOutcome::Ok { index, .. } | EditOutcome::Error { index, .. } | EditOutcome::Warning { index, .. } => {
+                *index = new_index
+            }
+        }
+    }
+
     // NOTE(jimmylee)
     // Returns true if this outcome represents a successful edit.
     pub fn is_success(&self) -> bool {
@@ -224,8 +417,15 @@ impl EditOutcome {
 fn error_code(error: &EditError) -> String {
     match error {
         EditError::FileNotFound { .. } => "file_not_found",
+        EditError::FileExists { .. } => "file_exists",
         EditError::SearchNotFound { .. } => "search_not_found",
         EditError::AnchorNotFound { .. } => "anchor_not_found",
+        EditError::MarkerNotFound { .. } => "marker_not_found",
+        EditError::InvalidMarkers { .. } => "invalid_markers",
+        EditError::BlockNotFound { .. } => "block_not_found",
+        EditError::ParseError { .. } => "parse_error",
+        EditError::KeyNotFound { .. } => "key_not_found",
+        EditError::InvalidKeyPath { .. } => "invalid_key_path",
         EditError::LineOutOfRange { .. } => "line_out_of_range",
         EditError::InvalidLineRange { .. } => "invalid_line_range",
         EditError::ReadError { .. } => "read_error",
@@ -234,6 +434,12 @@ fn error_code(error: &EditError) -> String {
         EditError::DeleteError { .. } => "delete_error",
         EditError::MultipleMatches { .. } => "multiple_matches",
         EditError::InvalidEdit { .. } => "invalid_edit",
+        EditError::EditConflict { .. } => "edit_conflict",
+        EditError::SyntaxRegression { .. } => "syntax_regression",
+        EditError::NoFilesMatched { .. } => "no_files_matched",
+        EditError::TooManyFiles { .. } => "too_many_files",
+        EditError::LinesModified { .. } => "lines_modified",
+        EditError::ExpectedMismatch { .. } => "expected_mismatch",
     }
     .to_string()
 }
@@ -273,6 +479,33 @@ pub struct ApplyResult {
     pub applied: usize,
     pub failed: usize,
     pub edits: Vec<EditOutcome>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<GroupOutcome>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub verify: Vec<VerifyOutcome>,
+}
+
+// NOTE(angeldev)
+// Result of one edit group. A group whose edit failed is rolled back on its own
+// in partial mode; its remaining edits are skipped and have no outcome.
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupOutcome {
+    pub name: String,
+    pub success: bool,
+    pub rolled_back: bool,
+    // Request indexes of the group's edits
+    pub edits: Vec<usize>,
+}
+
+impl GroupOutcome {
+    pub fn new(name: &str) -> Self {
+        GroupOutcome {
+            name: name.to_string(),
+            success: true,
+            rolled_back: false,
+            edits: Vec::new(),
+        }
+    }
 }
 
 impl ApplyResult {
@@ -282,6 +515,8 @@ impl ApplyResult {
             applied: 0,
             failed: 0,
             edits: Vec::new(),
+            groups: Vec::new(),
+            verify: Vec::new(),
         }
     }
 
@@ -294,6 +529,27 @@ impl ApplyResult {
         }
         self.edits.push(outcome);
     }
+
+    // NOTE(angeldev)
+    // Turns the successful outcomes of the given edits into rolled_back errors,
+    // so applied only counts changes that are still on disk.
+    pub fn mark_rolled_back(&mut self, indexes: &[usize], reason: &str) {
+        for outcome in &mut self.edits {
+            let (index, path, edit_type) = match outcome {
+                EditOutcome::Ok { index, path, edit_type, .. } | EditOutcome::Warning { index, path, edit_type, .. } => {
+                    (*index, path.clone(), edit_type.clone())
+                }
+                EditOutcome::Error { .. } => continue,
+            };
+            if !indexes.contains(&index) {
+                continue;
+            }
+            *outcome = EditOutcome::rolled_back(index, &path, &edit_type, reason);
+            self.applied -= 1;
+            self.failed += 1;
+            self.success = false;
+        }
+    }
 }
 
 impl Default for ApplyResult {
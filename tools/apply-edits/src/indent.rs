@@ -2,11 +2,15 @@
 // Language-aware indentation detection and normalization.
 // Handles different indentation styles (spaces vs tabs) across languages.
 
+use crate::language::Language;
+use serde::Serialize;
 use std::collections::HashMap;
 
 // NOTE(angeldev)
 // Represents the indentation style detected in a file.
-#[derive(Debug, Clone, Copy, PartialEq)]
+// Serializes as {"spaces": 2}, "tabs", "mixed" or "unknown".
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum IndentStyle {
     // Spaces with the detected width (e.g., 2, 4, 8)
     Spaces(usize),
@@ -24,54 +28,80 @@ impl Default for IndentStyle {
     }
 }
 
+impl IndentStyle {
+    // NOTE(angeldev)
+    // Returns one level of indentation as text.
+    // Mixed and Unknown fall back to the default style.
+    pub fn unit(&self) -> String {
+        match self {
+            IndentStyle::Spaces(width) => " ".repeat(*width),
+            IndentStyle::Tabs => "\t".to_string(),
+            IndentStyle::Mixed | IndentStyle::Unknown => IndentStyle::default().unit(),
+        }
+    }
+
+    // NOTE(jimmylee)
+    // Short human-readable form for prompts ("2 spaces", "tabs").
+    pub fn describe(&self) -> String {
+        match self {
+            IndentStyle::Spaces(1) => "1 space".to_string(),
+            IndentStyle::Spaces(width) => format!("{} spaces", width),
+            IndentStyle::Tabs => "tabs".to_string(),
+            IndentStyle::Mixed => "mixed tabs and spaces".to_string(),
+            IndentStyle::Unknown => "unknown".to_string(),
+        }
+    }
+}
+
 // NOTE(angeldev)
 // Language-specific indentation defaults and preferences.
+// Extensions are resolved through the shared table in language.rs.
 pub fn language_default_indent(extension: &str) -> IndentStyle {
-    match extension.to_lowercase().as_str() {
+    match Language::from_extension(extension) {
         // Go strongly prefers tabs
-        "go" => IndentStyle::Tabs,
+        Language::Go => IndentStyle::Tabs,
 
         // Python PEP 8 recommends 4 spaces
-        "py" | "pyw" => IndentStyle::Spaces(4),
+        Language::Python => IndentStyle::Spaces(4),
 
         // JavaScript/TypeScript ecosystem typically uses 2 spaces
-        "js" | "jsx" | "ts" | "tsx" | "mjs" | "cjs" => IndentStyle::Spaces(2),
+        Language::JavaScript | Language::TypeScript => IndentStyle::Spaces(2),
 
         // HTML/CSS/SCSS typically 2 spaces
-        "html" | "htm" | "css" | "scss" | "sass" | "less" => IndentStyle::Spaces(2),
+        Language::Html | Language::Css => IndentStyle::Spaces(2),
 
         // JSON/YAML typically 2 spaces
-        "json" | "yaml" | "yml" => IndentStyle::Spaces(2),
+        Language::Json | Language::Yaml => IndentStyle::Spaces(2),
 
         // Rust uses 4 spaces per rustfmt default
-        "rs" => IndentStyle::Spaces(4),
+        Language::Rust => IndentStyle::Spaces(4),
 
         // Ruby typically uses 2 spaces
-        "rb" => IndentStyle::Spaces(2),
+        Language::Ruby => IndentStyle::Spaces(2),
 
         // Java/Kotlin typically 4 spaces
-        "java" | "kt" | "kts" => IndentStyle::Spaces(4),
+        Language::Java | Language::Kotlin => IndentStyle::Spaces(4),
 
         // C/C++ varies but 4 is common
-        "c" | "cpp" | "cc" | "h" | "hpp" => IndentStyle::Spaces(4),
+        Language::C | Language::Cpp => IndentStyle::Spaces(4),
 
         // C# typically 4 spaces
-        "cs" => IndentStyle::Spaces(4),
+        Language::CSharp => IndentStyle::Spaces(4),
 
         // PHP varies, 4 is PSR standard
-        "php" => IndentStyle::Spaces(4),
+        Language::Php => IndentStyle::Spaces(4),
 
         // Shell scripts typically 2-4, we default to 4
-        "sh" | "bash" | "zsh" => IndentStyle::Spaces(4),
+        Language::Shell => IndentStyle::Spaces(4),
 
         // Makefile requires tabs
-        "makefile" | "mk" => IndentStyle::Tabs,
+        Language::Makefile => IndentStyle::Tabs,
 
         // Vue/Svelte typically 2 spaces
-        "vue" | "svelte" => IndentStyle::Spaces(2),
+        Language::Vue | Language::Svelte => IndentStyle::Spaces(2),
 
         // Default to 4 spaces
-        _ => IndentStyle::Spaces(4),
+        Language::Toml | Language::Markdown | Language::Unknown => IndentStyle::Spaces(4),
     }
 }
 
@@ -131,7 +161,7 @@ pub fn detect_indent_style(content: &str, file_ext: &str) -> IndentStyle {
     // Find the most common indentation width
     // Look for the GCD of common indentation levels
     let mut widths: Vec<(usize, usize)> = space_counts.into_iter().collect();
-    widths.sort_by(|a, b| b.1.cmp(&a.1));
+    widths.sort_by_key(|w| std::cmp::Reverse(w.1));
 
     if let Some((most_common_width, _)) = widths.first() {
         // Find the GCD of the most common widths to detect the base indent
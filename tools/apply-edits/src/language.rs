@@ -0,0 +1,226 @@
+// NOTE(angeldev)
+// Language detection by file extension.
+// A single extension table shared by indentation defaults and the lexical scanner,
+// so every language-aware feature agrees on what a file is.
+
+use std::path::Path;
+
+// NOTE(angeldev)
+// Languages the tool knows how to treat specially.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Language {
+    Go,
+    Python,
+    JavaScript,
+    TypeScript,
+    Html,
+    Css,
+    Json,
+    Yaml,
+    Toml,
+    Markdown,
+    Rust,
+    Ruby,
+    Java,
+    Kotlin,
+    C,
+    Cpp,
+    CSharp,
+    Php,
+    Shell,
+    Makefile,
+    Vue,
+    Svelte,
+    Unknown,
+}
+
+// NOTE(angeldev)
+// How a language delimits comments and strings.
+// Used by the scanner to tell code apart from text that merely looks like code.
+#[derive(Debug, Clone, Copy)]
+pub struct Syntax {
+    pub line_comments: &'static [&'static str],
+    pub block_comment: Option<(&'static str, &'static str)>,
+    pub quotes: &'static [char],
+    // Python-style """ and ''' strings
+    pub triple_quotes: bool,
+    // Rust-style 'c' char literals that must not be confused with 'lifetimes
+    pub char_literals: bool,
+    // Rust-style r"..." and r#"..."# raw strings
+    pub raw_strings: bool,
+    // Whether ordinary quoted strings may span lines
+    pub multiline_strings: bool,
+}
+
+impl Language {
+    // NOTE(angeldev)
+    // Maps a file extension (without the dot) to a language.
+    pub fn from_extension(extension: &str) -> Language {
+        match extension.to_lowercase().as_str() {
+            "go" => Language::Go,
+            "py" | "pyw" => Language::Python,
+            "js" | "jsx" | "mjs" | "cjs" => Language::JavaScript,
+            "ts" | "tsx" | "mts" | "cts" => Language::TypeScript,
+            "html" | "htm" => Language::Html,
+            "css" | "scss" | "sass" | "less" => Language::Css,
+            "json" => Language::Json,
+            "yaml" | "yml" => Language::Yaml,
+            "toml" => Language::Toml,
+            "md" | "markdown" | "mdx" => Language::Markdown,
+            "rs" => Language::Rust,
+            "rb" => Language::Ruby,
+            "java" => Language::Java,
+            "kt" | "kts" => Language::Kotlin,
+            "c" | "h" => Language::C,
+            "cpp" | "cc" | "hpp" => Language::Cpp,
+            "cs" => Language::CSharp,
+            "php" => Language::Php,
+            "sh" | "bash" | "zsh" => Language::Shell,
+            "makefile" | "mk" => Language::Makefile,
+            "vue" => Language::Vue,
+            "svelte" => Language::Svelte,
+            _ => Language::Unknown,
+        }
+    }
+
+    // NOTE(angeldev)
+    // Detects the language of a path, handling extensionless names like Makefile.
+    pub fn from_path(path: &str) -> Language {
+        let file_name = Path::new(path)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("");
+
+        match Path::new(file_name).extension().and_then(|e| e.to_str()) {
+            Some(ext) => Language::from_extension(ext),
+            None => Language::from_extension(file_name),
+        }
+    }
+
+    // NOTE(angeldev)
+    // Lowercase language name for JSON output and prompts.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::Go => "go",
+            Language::Python => "python",
+            Language::JavaScript => "javascript",
+            Language::TypeScript => "typescript",
+            Language::Html => "html",
+            Language::Css => "css",
+            Language::Json => "json",
+            Language::Yaml => "yaml",
+            Language::Toml => "toml",
+            Language::Markdown => "markdown",
+            Language::Rust => "rust",
+            Language::Ruby => "ruby",
+            Language::Java => "java",
+            Language::Kotlin => "kotlin",
+            Language::C => "c",
+            Language::Cpp => "cpp",
+            Language::CSharp => "csharp",
+            Language::Php => "php",
+            Language::Shell => "shell",
+            Language::Makefile => "makefile",
+            Language::Vue => "vue",
+            Language::Svelte => "svelte",
+            Language::Unknown => "unknown",
+        }
+    }
+
+    // NOTE(angeldev)
+    // Returns true for languages whose blocks are defined by indentation, not brackets.
+    pub fn uses_indentation_blocks(&self) -> bool {
+        matches!(self, Language::Python | Language::Yaml)
+    }
+
+    // NOTE(angeldev)
+    // Comment and string syntax for the scanner.
+    pub fn syntax(&self) -> Syntax {
+        const C_LIKE: Syntax = Syntax {
+            line_comments: &["//"],
+            block_comment: Some(("/*", "*/")),
+            quotes: &['"', '\''],
+            triple_quotes: false,
+            char_literals: false,
+            raw_strings: false,
+            multiline_strings: false,
+        };
+        const HASH: Syntax = Syntax {
+            line_comments: &["#"],
+            block_comment: None,
+            quotes: &['"', '\''],
+            triple_quotes: false,
+            char_literals: false,
+            raw_strings: false,
+            multiline_strings: false,
+        };
+
+        match self {
+            Language::JavaScript
+            | Language::TypeScript
+            | Language::Vue
+            | Language::Svelte
+            | Language::Go => Syntax {
+                quotes: &['"', '\'', '`'],
+                ..C_LIKE
+            },
+            Language::Rust => Syntax {
+                quotes: &['"'],
+                char_literals: true,
+                raw_strings: true,
+                multiline_strings: true,
+                ..C_LIKE
+            },
+            Language::Json => Syntax {
+                quotes: &['"'],
+                ..C_LIKE
+            },
+            Language::Php => Syntax {
+                line_comments: &["//", "#"],
+                ..C_LIKE
+            },
+            Language::Java
+            | Language::Kotlin
+            | Language::C
+            | Language::Cpp
+            | Language::CSharp
+            | Language::Css => C_LIKE,
+            Language::Python => Syntax {
+                triple_quotes: true,
+                ..HASH
+            },
+            Language::Yaml | Language::Toml | Language::Ruby | Language::Shell | Language::Makefile => HASH,
+            Language::Html | Language::Markdown => Syntax {
+                line_comments: &[],
+                block_comment: Some(("<!--", "-->")),
+                quotes: &[],
+                triple_quotes: false,
+                char_literals: false,
+                raw_strings: false,
+                multiline_strings: false,
+            },
+            Language::Unknown => Syntax {
+                line_comments: &[],
+                block_comment: None,
+                quotes: &[],
+                triple_quotes: false,
+                char_literals: false,
+                raw_strings: false,
+                multiline_strings: false,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_path() {
+        assert_eq!(Language::from_path("src/app.tsx"), Language::TypeScript);
+        assert_eq!(Language::from_path("Makefile"), Language::Makefile);
+        assert_eq!(Language::from_path("lib/main.RS"), Language::Rust);
+        assert_eq!(Language::from_path("README"), Language::Unknown);
+    }
+}
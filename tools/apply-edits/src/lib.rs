@@ -3,19 +3,33 @@
 // Exposes the public API for edit operations and file reading.
 
 pub mod autocorrect;
+pub mod budget;
+pub mod conflicts;
+pub mod detect;
 pub mod edits;
 pub mod error;
 pub mod indent;
+pub mod language;
+pub mod linemap;
 pub mod matcher;
+pub mod metadata;
+pub mod outline;
+pub mod rank;
 pub mod output;
 pub mod read;
+pub mod sanity;
+pub mod scanner;
+pub mod search;
 pub mod transaction;
+pub mod tree;
+pub mod verify;
+pub mod walk;
 
 // NOTE(jimmylee)
 // Re-export commonly used types for convenience.
-pub use edits::{Edit, EditRequest};
+pub use edits::{Edit, EditGroup, EditRequest};
 pub use error::{ApplyResult, EditError, EditOutcome, EditResult};
-pub use read::{FileReadResult, MultiFileReadResult};
+pub use read::{FileReadResult, LineRange, MultiFileReadResult, ReadSpec};
 
 use std::path::Path;
 
@@ -38,12 +52,107 @@ pub fn apply_edits_with_options(
     dry_run: bool,
     partial: bool,
 ) -> ApplyResult {
+    apply_grouped_edits(workdir, edits, &[], &[], dry_run, partial, &verify::VerifyPlan::default())
+}
+
+// NOTE(angeldev)
+// Applies a whole request: its ungrouped edits, then each group's edits.
+// In partial mode every group is atomic on its own (see apply_groups_with_transaction).
+// The verify plan's commands run before committing; any failure rolls everything back.
+pub fn apply_request(
+    workdir: &Path,
+    request: &EditRequest,
+    dry_run: bool,
+    partial: bool,
+    verify: &verify::VerifyPlan,
+) -> ApplyResult {
+    let (edits, groups) = request.flatten();
+    let names: Vec<String> = request.groups.iter().map(|g| g.name.clone()).collect();
+    apply_grouped_edits(workdir, &edits, &groups, &names, dry_run, partial, verify)
+}
+
+fn apply_grouped_edits(
+    workdir: &Path,
+    edits: &[Edit],
+    groups: &[Option<usize>],
+    names: &[String],
+    dry_run: bool,
+    partial: bool,
+    verify: &verify::VerifyPlan,
+) -> ApplyResult {
+    // NOTE(angeldev): Fan `paths` edits out to one edit per matching file first;
+    // a glob that can't be expanded fails the request before anything is written
+    let expanded = match crate::edits::expand_edits(workdir, edits) {
+        Ok(expanded) => expanded,
+        Err((index, error)) => {
+            let edit = &edits[index];
+            let path = edit.path_patterns().map(|p| p.join(", ")).unwrap_or_else(|| edit.path().to_string());
+            let mut result = ApplyResult::new();
+            result.add_outcome(EditOutcome::from_error(index, &path, edit.type_name(), &error));
+            return result;
+        }
+    };
+    let request_edits = edits;
+    let origins = &expanded.origins;
+    let edits = expanded.edits.as_slice();
+    let groups: Vec<Option<usize>> = origins.iter().map(|&i| groups.get(i).copied().flatten()).collect();
+
+    // NOTE(angeldev): Overlapping edits give order-dependent results; refuse them up front
+    let conflicts = conflicts::find_conflicts(workdir, edits);
+    if !conflicts.is_empty() {
+        let mut result = ApplyResult::new();
+        for conflict in conflicts {
+            let edit = &edits[conflict.second];
+            let error = EditError::EditConflict {
+                path: conflict.path,
+                first: origins[conflict.first] + 1,
+                second: origins[conflict.second] + 1,
+                reason: conflict.reason,
+            };
+            result.add_outcome(EditOutcome::from_error(origins[conflict.second], edit.path(), edit.type_name(), &error));
+        }
+        return result;
+    }
+
     // Use batch optimization when there are multiple edits to the same file
-    if should_use_batch_optimization(edits) {
-        apply_edits_batched(workdir, edits, dry_run, partial)
+    // (groups and verification need the transaction's rollback, and dry runs its
+    // per-edit simulation)
+    let batch = groups.iter().all(Option::is_none) && verify.commands.is_empty() && !dry_run;
+    let mut result = if batch && should_use_batch_optimization(edits) {
+        apply_edits_batched(workdir, edits, partial)
     } else {
-        transaction::apply_with_transaction(workdir, edits, dry_run, partial)
+        transaction::apply_groups_with_transaction(workdir, edits, &groups, names, dry_run, partial, verify)
+    };
+
+    // NOTE(angeldev): Remember the inserts that stayed applied so a re-sent request
+    // can tell them apart from new ones (see idempotent::resent_insert)
+    if !dry_run && (result.success || partial) {
+        let applied = result.edits.iter().filter(|o| matches!(o, EditOutcome::Ok { .. }));
+        crate::edits::idempotent::record_inserts(workdir, applied.map(|o| &edits[o.index()]));
     }
+
+    // Report per-file outcomes against the request edit they expanded from
+    for outcome in &mut result.edits {
+        outcome.set_index(origins[outcome.index()]);
+    }
+    for group in &mut result.groups {
+        group.edits = group.edits.iter().map(|&i| origins[i]).collect();
+        group.edits.dedup();
+    }
+
+    // NOTE(angeldev): A re-sent insert, or a `paths` edit no file needed, is one
+    // already_applied warning
+    if !expanded.already_applied.is_empty() {
+        use crate::edits::idempotent::ALREADY_APPLIED;
+        for (index, message) in &expanded.already_applied {
+            let edit = &request_edits[*index];
+            let label = edit.path_patterns().map(|p| p.join(", ")).unwrap_or_else(|| edit.path().to_string());
+            let message = if dry_run { format!("{} (dry-run)", message) } else { message.clone() };
+            result.add_outcome(EditOutcome::warning(*index, &label, edit.type_name(), ALREADY_APPLIED, &message));
+        }
+        result.edits.sort_by_key(EditOutcome::index);
+    }
+    result
 }
 
 // NOTE(angeldev)
@@ -56,6 +165,17 @@ fn should_use_batch_optimization(edits: &[Edit]) -> bool {
         return false;
     }
 
+    // NOTE(angeldev): Rename/copy make later edits depend on edits to other paths,
+    // which per-file grouping would reorder
+    if edits.iter().any(|e| e.destination().is_some()) {
+        return false;
+    }
+
+    // NOTE(angeldev): Multi-file edits touch paths other than path()
+    if edits.iter().any(|e| e.glob_paths().is_some()) {
+        return false;
+    }
+
     let mut seen_paths: HashSet<&str> = HashSet::new();
     for edit in edits {
         let path = edit.path();
@@ -86,19 +206,16 @@ fn group_edits_by_file(edits: &[Edit]) -> std::collections::HashMap<String, Vec<
 // NOTE(angeldev)
 // Applies edits with batch optimization.
 // Groups edits by file, reads each file once, applies all edits, writes once.
-fn apply_edits_batched(
-    workdir: &Path,
-    edits: &[Edit],
-    dry_run: bool,
-    partial: bool,
-) -> ApplyResult {
+fn apply_edits_batched(workdir: &Path, edits: &[Edit], partial: bool) -> ApplyResult {
     use std::collections::HashMap;
 
     let mut result = ApplyResult::new();
     let groups = group_edits_by_file(edits);
+    let mut lines = linemap::LineTracker::for_edits(edits);
 
-    // Track original file contents for rollback
-    let mut backups: HashMap<String, Option<String>> = HashMap::new();
+    // Track original file contents for rollback, and the directories new files may create
+    let mut backups: HashMap<String, Option<transaction::FileBackup>> = HashMap::new();
+    let mut created_dirs = Vec::new();
 
     // Pre-allocate result slots
     let mut outcomes: Vec<Option<EditOutcome>> = vec![None; edits.len()];
@@ -106,40 +223,27 @@ fn apply_edits_batched(
     for (path, file_edits) in &groups {
         let full_path = workdir.join(path);
 
-        // Backup original content if not dry-run
-        if !dry_run && !backups.contains_key(path) {
-            let content = std::fs::read_to_string(&full_path).ok();
-            backups.insert(path.clone(), content);
+        // Backup original content
+        if !backups.contains_key(path) {
+            let backup = transaction::FileBackup::capture(&full_path);
+            if backup.is_none() {
+                created_dirs.extend(transaction::missing_dirs(workdir, &full_path));
+            }
+            backups.insert(path.clone(), backup);
         }
 
         // Process each edit for this file
         for (index, edit) in file_edits {
-            let outcome = if dry_run {
-                // Simulate the edit
-                simulate_edit(workdir, edit, *index)
-            } else {
-                // Actually apply the edit
-                edit.apply(workdir, *index)
-            };
+            // Apply the edit, mapping original line numbers first
+            let outcome = linemap::apply_tracked(&mut lines, workdir, edit, *index);
 
             let is_success = outcome.is_success();
             outcomes[*index] = Some(outcome);
 
             // In atomic mode, fail fast and rollback on first error
-            if !is_success && !partial && !dry_run {
+            if !is_success && !partial {
                 eprintln!("❌ Edit {} failed - triggering rollback", index + 1);
-
-                // Restore backups
-                for (backup_path, content) in &backups {
-                    let backup_full_path = workdir.join(backup_path);
-                    if let Some(original) = content {
-                        let _ = std::fs::write(&backup_full_path, original);
-                        eprintln!("   Restored: {}", backup_path);
-                    } else if backup_full_path.exists() {
-                        let _ = std::fs::remove_file(&backup_full_path);
-                        eprintln!("   Removed: {}", backup_path);
-                    }
-                }
+                restore_batch_backups(workdir, &backups, created_dirs);
 
                 // Build partial result with outcomes so far
                 for outcome in outcomes.into_iter().flatten() {
@@ -155,145 +259,39 @@ fn apply_edits_batched(
         result.add_outcome(outcome);
     }
 
+    // NOTE(angeldev): Edits that each succeeded can still leave a file unparseable
+    let files = backups.iter().map(|(path, backup)| (workdir.join(path), backup.as_ref()));
+    let regressions = transaction::syntax_regressions(workdir, files);
+    if !regressions.is_empty() {
+        transaction::report_syntax_regressions(&mut result, edits, regressions, partial);
+        if !partial {
+            eprintln!("❌ Syntax check failed - triggering rollback");
+            restore_batch_backups(workdir, &backups, created_dirs);
+        }
+    }
+
     result
 }
 
 // NOTE(angeldev)
-// Simulates an edit without writing to disk (for dry-run mode in batched processing).
-fn simulate_edit(workdir: &Path, edit: &Edit, index: usize) -> EditOutcome {
-    use crate::edits::read_file;
-    use crate::matcher::{count_occurrences, find_line_with_anchor, find_with_normalization, FindResult};
-
-    let path = edit.path();
-    let edit_type = edit.type_name();
-
-    match edit {
-        Edit::Replace { search, .. } | Edit::ReplaceAll { search, .. } => {
-            match read_file(workdir, path) {
-                Ok(content) => {
-                    let count = count_occurrences(&content, search);
-                    if count > 0 {
-                        EditOutcome::ok_with_details(
-                            index,
-                            path,
-                            edit_type,
-                            None,
-                            Some(format!("Would replace {} occurrence(s) (dry-run)", count)),
-                        )
-                    } else {
-                        match find_with_normalization(&content, search) {
-                            FindResult::NormalizedMatch { line_number, .. } => {
-                                EditOutcome::ok_with_details(
-                                    index,
-                                    path,
-                                    edit_type,
-                                    None,
-                                    Some(format!("Would replace with indent adjust at line {} (dry-run)", line_number)),
-                                )
-                            }
-                            _ => {
-                                let closest = crate::matcher::find_closest_matches(&content, search, 0.5, 3);
-                                EditOutcome::from_error(
-                                    index,
-                                    path,
-                                    edit_type,
-                                    &EditError::SearchNotFound {
-                                        path: path.to_string(),
-                                        search_preview: search.chars().take(200).collect(),
-                                        closest_matches: closest,
-                                    },
-                                )
-                            }
-                        }
-                    }
-                }
-                Err(e) => EditOutcome::from_error(index, path, edit_type, &e),
-            }
-        }
-
-        Edit::InsertAfter { anchor, .. } | Edit::InsertBefore { anchor, .. } => {
-            match read_file(workdir, path) {
-                Ok(content) => {
-                    if find_line_with_anchor(&content, anchor).is_some() {
-                        EditOutcome::ok_with_details(
-                            index,
-                            path,
-                            edit_type,
-                            None,
-                            Some("Would insert content (dry-run)".to_string()),
-                        )
-                    } else {
-                        let closest = crate::matcher::find_closest_matches(&content, anchor, 0.5, 3);
-                        EditOutcome::from_error(
-                            index,
-                            path,
-                            edit_type,
-                            &EditError::AnchorNotFound {
-                                path: path.to_string(),
-                                anchor_preview: anchor.chars().take(200).collect(),
-                                closest_matches: closest,
-                            },
-                        )
-                    }
-                }
-                Err(e) => EditOutcome::from_error(index, path, edit_type, &e),
-            }
-        }
-
-        Edit::Create { .. } => {
-            let full_path = workdir.join(path);
-            if full_path.exists() {
-                EditOutcome::ok_with_details(
-                    index,
-                    path,
-                    edit_type,
-                    None,
-                    Some("Would overwrite file (dry-run)".to_string()),
-                )
-            } else {
-                EditOutcome::ok_with_details(
-                    index,
-                    path,
-                    edit_type,
-                    None,
-                    Some("Would create file (dry-run)".to_string()),
-                )
-            }
-        }
-
-        Edit::DeleteFile { .. } => {
-            let full_path = workdir.join(path);
-            if full_path.exists() {
-                EditOutcome::ok_with_details(
-                    index,
-                    path,
-                    edit_type,
-                    None,
-                    Some("Would delete file (dry-run)".to_string()),
-                )
-            } else {
-                EditOutcome::from_error(
-                    index,
-                    path,
-                    edit_type,
-                    &EditError::FileNotFound {
-                        path: path.to_string(),
-                    },
-                )
-            }
-        }
-
-        _ => {
-            // For other edit types, assume success in dry-run
-            EditOutcome::ok_with_details(
-                index,
-                path,
-                edit_type,
-                None,
-                Some(format!("Would apply {} (dry-run)", edit_type)),
-            )
+// Restores the batched path's backups (keyed by workdir-relative path) and removes
+// the directories created for new files.
+fn restore_batch_backups(
+    workdir: &Path,
+    backups: &std::collections::HashMap<String, Option<transaction::FileBackup>>,
+    created_dirs: Vec<std::path::PathBuf>,
+) {
+    for (backup_path, content) in backups {
+        let backup_full_path = workdir.join(backup_path);
+        if let Some(original) = content {
+            let _ = original.restore(&backup_full_path);
+            eprintln!("   Restored: {}", backup_path);
+        } else if backup_full_path.exists() {
+            let _ = std::fs::remove_file(&backup_full_path);
+            eprintln!("   Removed: {}", backup_path);
         }
     }
+    transaction::remove_created_dirs(created_dirs);
 }
 
 // NOTE(jimmylee)
@@ -0,0 +1,419 @@
+// NOTE(angeldev)
+// Line-number mapping for edits within a single request.
+// Line-based edits (insert_at_line, delete_lines, replace_lines) refer to the file
+// as it was when the request started. Earlier edits in the same request shift lines,
+// so this module records each change and maps original line numbers onto the
+// current file before a line-based edit runs.
+
+use crate::edits::{read_file, Edit};
+use crate::error::{EditError, EditOutcome, EditResult};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+// NOTE(angeldev)
+// A contiguous change to a file: `removed` lines starting at `start` (1-indexed,
+// in the file as it was before the change) were replaced by `added` lines.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Hunk {
+    start: usize,
+    removed: usize,
+    added: usize,
+}
+
+impl Hunk {
+    // NOTE(angeldev)
+    // Computes the single hunk covering every changed line between two versions.
+    // Edits that touch several places (replace_all, delete_match) produce one wide
+    // hunk, which conservatively marks everything in between as changed.
+    fn between(before: &str, after: &str) -> Option<Hunk> {
+        let old: Vec<&str> = before.lines().collect();
+        let new: Vec<&str> = after.lines().collect();
+
+        let prefix = old
+            .iter()
+            .zip(new.iter())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let max_suffix = old.len().min(new.len()) - prefix;
+        let suffix = old
+            .iter()
+            .rev()
+            .zip(new.iter().rev())
+            .take(max_suffix)
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        let removed = old.len() - prefix - suffix;
+        let added = new.len() - prefix - suffix;
+
+        if removed == 0 && added == 0 {
+            return None;
+        }
+
+        Some(Hunk {
+            start: prefix + 1,
+            removed,
+            added,
+        })
+    }
+
+    // NOTE(angeldev)
+    // Maps an existing line through this hunk.
+    // Returns None if the line was replaced or removed by the change.
+    fn map_line(&self, line: usize) -> Option<usize> {
+        if line >= self.start + self.removed {
+            Some(line + self.added - self.removed)
+        } else if line < self.start {
+            Some(line)
+        } else {
+            None
+        }
+    }
+
+    // NOTE(angeldev)
+    // Maps an insertion position (insert before this line) through this hunk.
+    // Positions on the boundary of the change stay valid; positions inside it do not.
+    fn map_position(&self, line: usize) -> Option<usize> {
+        if line >= self.start + self.removed {
+            Some(line + self.added - self.removed)
+        } else if line <= self.start {
+            Some(line)
+        } else {
+            None
+        }
+    }
+}
+
+// NOTE(angeldev)
+// Tracks the changes made to each file during a request.
+// Only files targeted by a line-based edit are tracked, so other requests pay
+// nothing for the extra reads.
+#[derive(Debug, Default, Clone)]
+pub struct LineTracker {
+    tracked: HashSet<String>,
+    hunks: HashMap<String, Vec<Hunk>>,
+}
+
+impl LineTracker {
+    // NOTE(angeldev)
+    // Creates a tracker for the files that line-based edits in this request target.
+    pub fn for_edits(edits: &[Edit]) -> Self {
+        let mut tracked: HashSet<String> = edits
+            .iter()
+            .filter(|e| e.uses_line_numbers())
+            .map(|e| e.path().to_string())
+            .collect();
+
+        // A renamed or copied file carries its history, so track its source too
+        for edit in edits.iter().rev() {
+            if edit.destination().is_some_and(|to| tracked.contains(to)) {
+                tracked.insert(edit.path().to_string());
+            }
+        }
+
+        LineTracker {
+            tracked,
+            hunks: HashMap::new(),
+        }
+    }
+
+    // NOTE(angeldev)
+    // Returns true if changes to this path need to be recorded.
+    pub fn is_tracked(&self, path: &str) -> bool {
+        self.tracked.contains(path)
+    }
+
+    // NOTE(angeldev)
+    // Records the change an edit made to a file.
+    pub fn record(&mut self, path: &str, before: &str, after: &str) {
+        if let Some(hunk) = Hunk::between(before, after) {
+            self.hunks.entry(path.to_string()).or_default().push(hunk);
+        }
+    }
+
+    // NOTE(angeldev)
+    // Carries the recorded changes of a renamed or copied file over to its new path,
+    // so line numbers on the new path refer to the source's original contents.
+    pub fn record_move(&mut self, from: &str, to: &str, keep_source: bool) {
+        let hunks = if keep_source {
+            self.hunks.get(from).cloned()
+        } else {
+            self.hunks.remove(from)
+        };
+
+        match hunks {
+            Some(hunks) => {
+                self.hunks.insert(to.to_string(), hunks);
+            }
+            None => {
+                self.hunks.remove(to);
+            }
+        }
+    }
+
+    // NOTE(angeldev)
+    // Maps an original line number onto the current file.
+    fn map_line(&self, path: &str, line: usize) -> Option<usize> {
+        self.hunks
+            .get(path)
+            .map(|hunks| hunks.iter().try_fold(line, |l, h| h.map_line(l)))
+            .unwrap_or(Some(line))
+    }
+
+    // NOTE(angeldev)
+    // Maps an original insertion position onto the current file.
+    fn map_position(&self, path: &str, line: usize) -> Option<usize> {
+        self.hunks
+            .get(path)
+            .map(|hunks| hunks.iter().try_fold(line, |l, h| h.map_position(l)))
+            .unwrap_or(Some(line))
+    }
+
+    // NOTE(angeldev)
+    // Maps an original line range onto the current file.
+    // Fails if any line in the range was touched by an earlier edit.
+    fn map_range(&self, path: &str, start_line: usize, end_line: usize) -> EditResult<(usize, usize)> {
+        let modified = || EditError::LinesModified {
+            path: path.to_string(),
+            start_line,
+            end_line,
+        };
+
+        let start = self.map_line(path, start_line).ok_or_else(modified)?;
+        let end = self.map_line(path, end_line).ok_or_else(modified)?;
+
+        // Lines inserted inside the range would silently widen it
+        if end < start || end - start != end_line.saturating_sub(start_line) {
+            return Err(modified());
+        }
+
+        Ok((start, end))
+    }
+
+    // NOTE(angeldev)
+    // Rewrites a line-based edit so its line numbers refer to the current file.
+    // Returns None when the edit needs no changes.
+    pub fn resolve(&self, edit: &Edit) -> EditResult<Option<Edit>> {
+        let path = edit.path();
+        if !self.hunks.contains_key(path) {
+            return Ok(None);
+        }
+
+        let resolved = match edit {
+            Edit::InsertAtLine { line, .. } => {
+                let mapped = self
+                    .map_position(path, *line)
+                    .ok_or_else(|| EditError::LinesModified {
+                        path: path.to_string(),
+                        start_line: *line,
+                        end_line: *line,
+                    })?;
+                if mapped == *line {
+                    return Ok(None);
+                }
+                let mut e = edit.clone();
+                if let Edit::InsertAtLine { line, .. } = &mut e {
+                    *line = mapped;
+                }
+                e
+            }
+            Edit::DeleteLines {
+                start_line,
+                end_line,
+                ..
+            }
+            | Edit::ReplaceLines {
+                start_line,
+                end_line,
+                ..
+            } => {
+                let (start, end) = self.map_range(path, *start_line, *end_line)?;
+                if start == *start_line {
+                    return Ok(None);
+                }
+                let mut e = edit.clone();
+                if let Edit::DeleteLines {
+                    start_line,
+                    end_line,
+                    ..
+                }
+                | Edit::ReplaceLines {
+                    start_line,
+                    end_line,
+                    ..
+                } = &mut e
+                {
+                    *start_line = start;
+                    *end_line = end;
+                }
+                e
+            }
+            _ => return Ok(None),
+        };
+
+        Ok(Some(resolved))
+    }
+}
+
+// NOTE(angeldev)
+// Applies an edit, translating original line numbers and recording the change.
+// Used by both the transaction and batched apply paths.
+pub fn apply_tracked(
+    tracker: &mut LineTracker,
+    workdir: &Path,
+    edit: &Edit,
+    index: usize,
+) -> EditOutcome {
+    let path = edit.path();
+
+    let resolved = match tracker.resolve(edit) {
+        Ok(resolved) => resolved,
+        Err(e) => return EditOutcome::from_error(index, path, edit.type_name(), &e),
+    };
+
+    // Rename and copy move the file's history instead of changing its lines
+    if let Some(to) = edit.destination() {
+        let outcome = edit.apply(workdir, index);
+        if outcome.is_success() {
+            tracker.record_move(path, to, matches!(edit, Edit::Copy { .. }));
+        }
+        return outcome;
+    }
+
+    let before = if tracker.is_tracked(path) {
+        read_file(workdir, path).ok()
+    } else {
+        None
+    };
+
+    let mut outcome = resolved.as_ref().unwrap_or(edit).apply(workdir, index);
+
+    if outcome.is_success() {
+        if let Some(before) = before {
+            let after = read_file(workdir, path).unwrap_or_default();
+            tracker.record(path, &before, &after);
+        }
+
+        // Note the shift so the caller can relate the message to the lines it sent
+        if let (Some(resolved), EditOutcome::Ok { message: Some(message), .. }) =
+            (&resolved, &mut outcome)
+        {
+            if let Some((original, current)) = first_line(edit).zip(first_line(resolved)) {
+                message.push_str(&format!(
+                    " (line {} in the original file is now line {})",
+                    original, current
+                ));
+            }
+        }
+    }
+
+    outcome
+}
+
+// NOTE(angeldev)
+// Returns the first line number a line-based edit refers to.
+fn first_line(edit: &Edit) -> Option<usize> {
+    match edit {
+        Edit::InsertAtLine { line, .. } => Some(*line),
+        Edit::DeleteLines { start_line, .. } | Edit::ReplaceLines { start_line, .. } => {
+            Some(*start_line)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_hunk_between() {
+        let hunk = Hunk::between("a\nb\nc\n", "a\nx\ny\nc\n").unwrap();
+        assert_eq!(
+            hunk,
+            Hunk {
+                start: 2,
+                removed: 1,
+                added: 2
+            }
+        );
+        assert_eq!(hunk.map_line(1), Some(1));
+        assert_eq!(hunk.map_line(2), None);
+        assert_eq!(hunk.map_line(3), Some(4));
+        assert!(Hunk::between("a\n", "a\n").is_none());
+    }
+
+    #[test]
+    fn test_line_edits_use_original_numbers() {
+        let dir = tempdir().unwrap();
+        let path = "test.txt";
+        fs::write(dir.path().join(path), "line1\nline2\nline3\nline4\nline5\n").unwrap();
+
+        let edits: Vec<Edit> = serde_json::from_str(
+            r#"[
+                {"type": "insert_at_line", "path": "test.txt", "line": 2, "content": "new1\nnew2"},
+                {"type": "delete_lines", "path": "test.txt", "start_line": 3, "end_line": 3},
+                {"type": "replace_lines", "path": "test.txt", "start_line": 5, "end_line": 5, "content": "last", "expected": "line5"}
+            ]"#,
+        )
+        .unwrap();
+
+        let mut tracker = LineTracker::for_edits(&edits);
+        for (i, edit) in edits.iter().enumerate() {
+            let outcome = apply_tracked(&mut tracker, dir.path(), edit, i);
+            assert!(outcome.is_success(), "{:?}", outcome);
+        }
+
+        let content = fs::read_to_string(dir.path().join(path)).unwrap();
+        assert_eq!(content, "line1\nnew1\nnew2\nline2\nline4\nlast\n");
+    }
+
+    #[test]
+    fn test_line_numbers_follow_rename() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("old.txt"), "line1\nline2\nline3\n").unwrap();
+
+        let edits: Vec<Edit> = serde_json::from_str(
+            r#"[
+                {"type": "insert_at_line", "path": "old.txt", "line": 1, "content": "header"},
+                {"type": "rename", "from": "old.txt", "to": "new.txt"},
+                {"type": "delete_lines", "path": "new.txt", "start_line": 2, "end_line": 2}
+            ]"#,
+        )
+        .unwrap();
+
+        let mut tracker = LineTracker::for_edits(&edits);
+        for (i, edit) in edits.iter().enumerate() {
+            let outcome = apply_tracked(&mut tracker, dir.path(), edit, i);
+            assert!(outcome.is_success(), "{:?}", outcome);
+        }
+
+        let content = fs::read_to_string(dir.path().join("new.txt")).unwrap();
+        assert_eq!(content, "header\nline1\nline3\n");
+    }
+
+    #[test]
+    fn test_line_edit_on_modified_lines_fails() {
+        let dir = tempdir().unwrap();
+        let path = "test.txt";
+        fs::write(dir.path().join(path), "line1\nline2\nline3\n").unwrap();
+
+        let edits: Vec<Edit> = serde_json::from_str(
+            r#"[
+                {"type": "replace", "path": "test.txt", "search": "line2", "replace": "changed"},
+                {"type": "delete_lines", "path": "test.txt", "start_line": 1, "end_line": 2}
+            ]"#,
+        )
+        .unwrap();
+
+        let mut tracker = LineTracker::for_edits(&edits);
+        assert!(apply_tracked(&mut tracker, dir.path(), &edits[0], 0).is_success());
+
+        match apply_tracked(&mut tracker, dir.path(), &edits[1], 1) {
+            EditOutcome::Error { error, .. } => assert_eq!(error, "lines_modified"),
+            outcome => panic!("Expected lines_modified error, got {:?}", outcome),
+        }
+    }
+}
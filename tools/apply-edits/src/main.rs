@@ -1,16 +1,24 @@
 // NOTE(jimmylee)
 // CLI entry point for apply-edits tool.
-// Provides subcommands for applying edits and reading files.
+// Provides subcommands for applying edits, reading files and detecting project tooling.
 
 use apply_edits::edits::EditRequest;
 use apply_edits::output::{
     flush_stderr, print_edit_outcome, print_error, print_header, print_json_result,
     print_processing_start, print_read_header, print_workdir,
 };
-use apply_edits::{read_files, format_files_for_prompt};
+use apply_edits::budget::pack;
+use apply_edits::read::{
+    format_files, glob_files, read_outlines, read_specs, LineRange, ReadSpec, TextFormat, Truncation,
+};
+use apply_edits::rank::rank;
+use apply_edits::search::{format_for_prompt as format_search_for_prompt, search, SearchOptions};
+use apply_edits::tree::{build_tree, render_prompt, render_text, TreeOptions};
+use apply_edits::verify::VerifyPlan;
 use clap::{Parser, Subcommand};
 use std::io::{self, Read};
 use std::path::PathBuf;
+use std::time::Duration;
 
 // NOTE(jimmylee)
 // CLI argument structure using clap derive macros.
@@ -50,6 +58,15 @@ enum Commands {
         /// By default, edits are atomic - any failure rolls back all changes
         #[arg(long)]
         partial: bool,
+
+        /// Command to run in the workdir after applying, before committing (repeatable).
+        /// A non-zero exit or timeout rolls back all changes
+        #[arg(long = "verify", value_name = "COMMAND")]
+        verify: Vec<String>,
+
+        /// Seconds each --verify command may run
+        #[arg(long, default_value_t = apply_edits::verify::DEFAULT_TIMEOUT_SECS)]
+        verify_timeout: u64,
     },
 
     /// Read files with line numbers
@@ -58,10 +75,28 @@ enum Commands {
         #[arg(long, conflicts_with = "files")]
         file: Option<String>,
 
-        /// Comma-separated list of files to read
+        /// Comma-separated list of files to read, each optionally with a line
+        /// range (src/app.ts:100-180)
         #[arg(long, conflicts_with = "file")]
         files: Option<String>,
 
+        /// Read files matching a glob (repeatable; "*.ts", "src/**/*.rs", "!**/*.test.ts").
+        /// Honors .gitignore/.ignore and skips node_modules, dist, .next and binary files
+        #[arg(long = "glob", value_name = "PATTERN")]
+        globs: Vec<String>,
+
+        /// Maximum number of files to read from --glob matches
+        #[arg(long, default_value_t = apply_edits::read::DEFAULT_MAX_FILES)]
+        max_files: usize,
+
+        /// Only read lines START-END (e.g. 2380-2450) of files without their own range
+        #[arg(long, conflicts_with = "around")]
+        range: Option<String>,
+
+        /// Only read RADIUS lines either side of LINE (LINE:RADIUS, e.g. 2400:30)
+        #[arg(long, conflicts_with = "range")]
+        around: Option<String>,
+
         /// Working directory (repository root)
         #[arg(long)]
         workdir: PathBuf,
@@ -70,34 +105,203 @@ enum Commands {
         #[arg(long, default_value = "500")]
         max_lines: usize,
 
-        /// Output format (json or prompt)
+        /// How to show files: full (outline only past --max-lines) or outline
+        #[arg(long, default_value = "full")]
+        view: String,
+
+        /// How files past --max-lines are cut: auto (outline, else head), head,
+        /// head_tail or relevant (head plus windows around --keywords)
+        #[arg(long, default_value = "auto")]
+        truncate: String,
+
+        /// Comma-separated keywords for --truncate relevant
+        #[arg(long, value_delimiter = ',')]
+        keywords: Vec<String>,
+
+        /// Pack the files into about this many tokens, in the order given: full content
+        /// while it fits, then head/tail views, then omitted. Replaces --max-lines
+        #[arg(long)]
+        budget_tokens: Option<usize>,
+
+        /// Output format: json, prompt, markdown, xml or numbered-plain
+        #[arg(long, default_value = "json")]
+        format: String,
+
+        /// Leave line numbers out of text formats
+        #[arg(long)]
+        no_line_numbers: bool,
+    },
+
+    /// Detect the package manager, languages and validation commands of a project
+    Detect {
+        /// Working directory (repository root)
+        #[arg(long)]
+        workdir: PathBuf,
+    },
+    /// Search the repository for a literal or regex, with numbered context
+    Search {
+        /// Working directory (repository root)
+        #[arg(long)]
+        workdir: PathBuf,
+
+        /// Text to search for (a regex with --regex)
+        #[arg(long)]
+        query: String,
+
+        /// Treat the query as a regular expression
+        #[arg(long)]
+        regex: bool,
+
+        /// Match case-insensitively
+        #[arg(long)]
+        ignore_case: bool,
+
+        /// Only search files matching this glob (repeatable, ! excludes)
+        #[arg(long = "glob")]
+        globs: Vec<String>,
+
+        /// Lines of context around each match
+        #[arg(long, default_value = "2")]
+        context: usize,
+
+        /// Maximum matching lines to return
+        #[arg(long, default_value_t = apply_edits::search::DEFAULT_MAX_RESULTS)]
+        max_results: usize,
+
+        /// Output format: json or prompt
         #[arg(long, default_value = "json")]
         format: String,
     },
+    /// Rank repository files by relevance to a directive
+    Rank {
+        /// Working directory (repository root)
+        #[arg(long)]
+        workdir: PathBuf,
+
+        /// File containing the directive text
+        #[arg(long)]
+        directive_file: PathBuf,
+
+        /// Maximum files to return (0 for all)
+        #[arg(long, default_value_t = apply_edits::rank::DEFAULT_LIMIT)]
+        limit: usize,
+
+        /// Output format: json or paths (one per line)
+        #[arg(long, default_value = "json")]
+        format: String,
+    },
+    /// Print a map of the repository with line counts, sizes and languages
+    Tree {
+        /// Working directory (repository root)
+        #[arg(long)]
+        workdir: PathBuf,
+
+        /// Collapse directories this deep (top-level entries are depth 1)
+        #[arg(long)]
+        max_depth: Option<usize>,
+
+        /// Collapse directories with more than this many files
+        #[arg(long, default_value_t = apply_edits::tree::DEFAULT_COLLAPSE_FILES)]
+        collapse: usize,
+
+        /// Output format: text, json or prompt
+        #[arg(long, default_value = "text")]
+        format: String,
+    },
 }
 
 fn main() {
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Apply { file, stdin, workdir, dry_run, partial } => {
-            run_apply(file, stdin, workdir, dry_run, partial);
+        Commands::Apply {
+            file,
+            stdin,
+            workdir,
+            dry_run,
+            partial,
+            verify,
+            verify_timeout,
+        } => {
+            let plan = VerifyPlan {
+                commands: verify,
+                timeout: Duration::from_secs(verify_timeout),
+            };
+            run_apply(file, stdin, workdir, dry_run, partial, &plan);
         }
         Commands::Read {
             file,
             files,
+            globs,
+            max_files,
+            range,
+            around,
             workdir,
             max_lines,
+            view,
+            truncate,
+            keywords,
+            budget_tokens,
             format,
+            no_line_numbers,
         } => {
-            run_read(file, files, workdir, max_lines, format);
+            let selection = ReadSelection {
+                file,
+                files,
+                globs,
+                max_files,
+                range,
+                around,
+                view,
+                truncate,
+                keywords,
+            };
+            run_read(selection, workdir, max_lines, budget_tokens, format, !no_line_numbers);
+        }
+        Commands::Detect { workdir } => {
+            run_detect(workdir);
+        }
+        Commands::Search {
+            workdir,
+            query,
+            regex,
+            ignore_case,
+            globs,
+            context,
+            max_results,
+            format,
+        } => {
+            let options = SearchOptions {
+                globs,
+                regex,
+                ignore_case,
+                context,
+                max_results,
+            };
+            run_search(workdir, query, options, format);
+        }
+        Commands::Rank {
+            workdir,
+            directive_file,
+            limit,
+            format,
+        } => {
+            run_rank(workdir, directive_file, limit, format);
+        }
+        Commands::Tree {
+            workdir,
+            max_depth,
+            collapse,
+            format,
+        } => {
+            run_tree(workdir, max_depth, collapse, format);
         }
     }
 }
 
 // NOTE(jimmylee)
 // Runs the apply subcommand.
-fn run_apply(file: Option<PathBuf>, stdin: bool, workdir: PathBuf, dry_run: bool, partial: bool) {
+fn run_apply(file: Option<PathBuf>, stdin: bool, workdir: PathBuf, dry_run: bool, partial: bool, verify: &VerifyPlan) {
     print_header();
     print_workdir(&workdir.display().to_string());
 
@@ -144,16 +348,16 @@ fn run_apply(file: Option<PathBuf>, stdin: bool, workdir: PathBuf, dry_run: bool
     };
 
     // Apply edits
-    let total = request.edits.len();
+    let total = request.edits.len() + request.groups.iter().map(|g| g.edits.len()).sum::<usize>();
     print_processing_start(total);
 
     // NOTE(angeldev)
-    // Use apply_edits_with_options to support dry-run and partial/atomic modes
-    let result = apply_edits::apply_edits_with_options(&workdir, &request.edits, dry_run, partial);
+    // Use apply_request to support dry-run, partial/atomic modes and edit groups
+    let result = apply_edits::apply_request(&workdir, &request, dry_run, partial, verify);
 
     // Print human-readable output for each edit
-    for (i, outcome) in result.edits.iter().enumerate() {
-        print_edit_outcome(outcome, i, total);
+    for outcome in &result.edits {
+        print_edit_outcome(outcome, outcome.index(), total);
     }
 
     // Print summary
@@ -166,6 +370,15 @@ fn run_apply(file: Option<PathBuf>, stdin: bool, workdir: PathBuf, dry_run: bool
         if !partial && result.failed > 0 {
             eprintln!("   All changes rolled back due to failure (atomic mode)");
         }
+        for group in result.groups.iter().filter(|g| partial && g.rolled_back) {
+            eprintln!("   Group '{}' rolled back", group.name);
+        }
+        if let Some(failed) = result.verify.iter().find(|v| !v.success) {
+            eprintln!("   Verification failed: {} - all changes rolled back", failed.command);
+            if !failed.stderr_tail.is_empty() {
+                eprintln!("{}", failed.stderr_tail);
+            }
+        }
     }
     flush_stderr();
 
@@ -181,26 +394,134 @@ fn run_apply(file: Option<PathBuf>, stdin: bool, workdir: PathBuf, dry_run: bool
 }
 
 // NOTE(jimmylee)
-// Runs the read subcommand.
-fn run_read(
+// Which files (and lines) the read subcommand should return.
+struct ReadSelection {
     file: Option<String>,
     files: Option<String>,
+    globs: Vec<String>,
+    max_files: usize,
+    range: Option<String>,
+    around: Option<String>,
+    view: String,
+    truncate: String,
+    keywords: Vec<String>,
+}
+
+// NOTE(jimmylee)
+// Runs the read subcommand.
+fn run_read(
+    selection: ReadSelection,
     workdir: PathBuf,
     max_lines: usize,
+    budget_tokens: Option<usize>,
     format: String,
+    line_numbers: bool,
 ) {
-    // Build list of files to read
-    let paths: Vec<String> = if let Some(f) = file {
-        vec![f]
+    let ReadSelection {
+        file,
+        files,
+        globs,
+        max_files,
+        range,
+        around,
+        view,
+        truncate,
+        keywords,
+    } = selection;
+
+    // Build list of files to read: explicit files first, then glob matches
+    let mut specs: Vec<ReadSpec> = if let Some(f) = file {
+        vec![ReadSpec::parse(&f)]
     } else if let Some(fs) = files {
-        fs.split(',').map(|s| s.trim().to_string()).collect()
+        fs.split(',').map(ReadSpec::parse).collect()
+    } else {
+        Vec::new()
+    };
+
+    let matches = if globs.is_empty() {
+        None
     } else {
-        print_error("Either --file or --files must be specified");
+        match glob_files(&workdir, &globs, max_files) {
+            Ok(matches) => Some(matches),
+            Err(message) => {
+                print_error(&message);
+                std::process::exit(1);
+            }
+        }
+    };
+    if let Some(matches) = &matches {
+        for path in &matches.paths {
+            if !specs.iter().any(|s| &s.path == path) {
+                specs.push(ReadSpec { path: path.clone(), range: None });
+            }
+        }
+        eprintln!(
+            "# {} file(s) matched, reading {}{}",
+            matches.matched,
+            matches.paths.len(),
+            if matches.skipped_binary.is_empty() {
+                String::new()
+            } else {
+                format!(", {} binary skipped", matches.skipped_binary.len())
+            }
+        );
+    }
+
+    if specs.is_empty() {
+        if matches.is_some() {
+            print_error(&format!("No files matched {}", globs.join(", ")));
+        } else {
+            print_error("Either --file, --files or --glob must be specified");
+        }
         std::process::exit(1);
+    }
+
+    // NOTE(jimmylee): --range/--around apply to files that didn't name a range
+    let default_range = match (range, around) {
+        (Some(r), _) => Some(LineRange::parse(&r)),
+        (_, Some(a)) => Some(LineRange::around(&a)),
+        _ => None,
+    };
+    match default_range {
+        Some(Ok(range)) => specs.iter_mut().filter(|s| s.range.is_none()).for_each(|s| s.range = Some(range)),
+        Some(Err(message)) => {
+            print_error(&message);
+            std::process::exit(1);
+        }
+        None => {}
+    }
+
+    let truncation = match Truncation::parse(&truncate, &keywords) {
+        Ok(truncation) => truncation,
+        Err(message) => {
+            print_error(&message);
+            std::process::exit(1);
+        }
     };
 
     // Read files
-    let results = read_files(&workdir, &paths, Some(max_lines));
+    // NOTE(jimmylee): With a budget, files are read whole and packed afterwards
+    let max_lines = if budget_tokens.is_some() { usize::MAX } else { max_lines };
+    let mut results = match view.as_str() {
+        "full" => read_specs(&workdir, &specs, Some(max_lines), &truncation),
+        "outline" => read_outlines(&workdir, &specs, Some(max_lines)),
+        _ => {
+            print_error(&format!("Unknown view: {}. Use 'full' or 'outline'", view));
+            std::process::exit(1);
+        }
+    };
+    results.glob = matches;
+    if let Some(budget) = budget_tokens {
+        pack(&workdir, &mut results, budget);
+        if let Some(report) = &results.budget {
+            eprintln!(
+                "# {} of {} token(s) used, {} file(s) omitted",
+                report.used_tokens,
+                report.budget_tokens,
+                report.omitted.len()
+            );
+        }
+    }
 
     // Output based on format
     match format.as_str() {
@@ -223,14 +544,164 @@ fn run_read(
                 println!("{}", json);
             }
         }
-        "prompt" => {
-            // Print formatted output suitable for LLM prompts
-            let output = format_files_for_prompt(&results);
-            print!("{}", output);
+        // NOTE(jimmylee): Text layouts for LLM prompts (see read::TextFormat)
+        other => match TextFormat::parse(other) {
+            Some(text_format) => print!("{}", format_files(&results, text_format, line_numbers)),
+            None => {
+                print_error(&format!(
+                    "Unknown format: {}. Use 'json', 'prompt', 'markdown', 'xml' or 'numbered-plain'",
+                    format
+                ));
+                std::process::exit(1);
+            }
+        },
+    }
+}
+
+// NOTE(jimmylee)
+// Runs the detect subcommand.
+// JSON goes to stdout; a one-line summary per command goes to stderr.
+fn run_detect(workdir: PathBuf) {
+    if !workdir.is_dir() {
+        print_error(&format!("Not a directory: {}", workdir.display()));
+        std::process::exit(1);
+    }
+
+    let detection = apply_edits::detect::detect(&workdir);
+
+    if let Some(manager) = &detection.package_manager {
+        eprintln!("# Package manager: {} ({})", manager.value, manager.evidence);
+    }
+    let commands = &detection.commands;
+    let kinds = [
+        ("test", &commands.test),
+        ("build", &commands.build),
+        ("lint", &commands.lint),
+        ("typecheck", &commands.typecheck),
+        ("format", &commands.format),
+    ];
+    for (kind, command) in kinds {
+        if let Some(command) = command {
+            eprintln!("# {}: {} ({})", kind, command.value, command.evidence);
+        }
+    }
+    if kinds.iter().all(|(_, command)| command.is_none()) {
+        eprintln!("# No validation commands detected");
+    }
+
+    if let Ok(json) = serde_json::to_string_pretty(&detection) {
+        println!("{}", json);
+    }
+}
+
+// NOTE(jimmylee)
+// Runs the search subcommand.
+fn run_search(workdir: PathBuf, query: String, options: SearchOptions, format: String) {
+    if !workdir.is_dir() {
+        print_error(&format!("Not a directory: {}", workdir.display()));
+        std::process::exit(1);
+    }
+
+    let result = match search(&workdir, &query, &options) {
+        Ok(result) => result,
+        Err(message) => {
+            print_error(&message);
+            std::process::exit(1);
+        }
+    };
+    eprintln!(
+        "# {} match(es) in {} of {} file(s){}",
+        result.matches,
+        result.files.len(),
+        result.files_searched,
+        if result.truncated { " (truncated, raise --max-results)" } else { "" }
+    );
+
+    match format.as_str() {
+        "json" => {
+            if let Ok(json) = serde_json::to_string_pretty(&result) {
+                println!("{}", json);
+            }
         }
+        "prompt" => print!("{}", format_search_for_prompt(&result)),
         _ => {
             print_error(&format!("Unknown format: {}. Use 'json' or 'prompt'", format));
             std::process::exit(1);
         }
     }
 }
+
+// NOTE(jimmylee)
+// Runs the rank subcommand.
+fn run_rank(workdir: PathBuf, directive_file: PathBuf, limit: usize, format: String) {
+    if !workdir.is_dir() {
+        print_error(&format!("Not a directory: {}", workdir.display()));
+        std::process::exit(1);
+    }
+    let directive = match std::fs::read_to_string(&directive_file) {
+        Ok(directive) => directive,
+        Err(e) => {
+            print_error(&format!("Failed to read directive file {}: {}", directive_file.display(), e));
+            std::process::exit(1);
+        }
+    };
+
+    let result = match rank(&workdir, &directive, limit) {
+        Ok(result) => result,
+        Err(message) => {
+            print_error(&message);
+            std::process::exit(1);
+        }
+    };
+    eprintln!(
+        "# Ranked {} file(s) on: {}",
+        result.files_scanned,
+        if result.terms.is_empty() { "(no terms)".to_string() } else { result.terms.join(", ") }
+    );
+
+    match format.as_str() {
+        "json" => {
+            if let Ok(json) = serde_json::to_string_pretty(&result) {
+                println!("{}", json);
+            }
+        }
+        "paths" => {
+            for file in &result.files {
+                println!("{}", file.path);
+            }
+        }
+        _ => {
+            print_error(&format!("Unknown format: {}. Use 'json' or 'paths'", format));
+            std::process::exit(1);
+        }
+    }
+}
+
+// NOTE(jimmylee)
+// Runs the tree subcommand.
+fn run_tree(workdir: PathBuf, max_depth: Option<usize>, collapse: usize, format: String) {
+    if !workdir.is_dir() {
+        print_error(&format!("Not a directory: {}", workdir.display()));
+        std::process::exit(1);
+    }
+
+    let options = TreeOptions {
+        max_depth,
+        collapse_files: collapse,
+    };
+    let tree = build_tree(&workdir, &options);
+
+    match format.as_str() {
+        "text" => println!("{}", render_text(&tree)),
+        "prompt" => print!("{}", render_prompt(&tree)),
+        "json" => {
+            if let Ok(json) = serde_json::to_string_pretty(&tree) {
+                println!("{}", json);
+            }
+        }
+        _ => {
+            print_error(&format!("Unknown format: {}. Use 'text', 'json' or 'prompt'", format));
+            std::process::exit(1);
+        }
+    }
+}
@@ -134,6 +134,48 @@ pub fn find_with_normalization(content: &str, search: &str) -> FindResult {
     FindResult::NotFound
 }
 
+// NOTE(angeldev)
+// Finds every occurrence of a marker as a line range (1-indexed, inclusive).
+// Tries exact matching first, then falls back to indentation-normalized matching
+// (same rules as find_with_normalization) when the marker is not found verbatim.
+pub fn find_marker_lines(content: &str, marker: &str) -> Vec<(usize, usize)> {
+    if marker.trim().is_empty() {
+        return Vec::new();
+    }
+
+    let marker_line_count = marker.trim_end_matches('\n').lines().count().max(1);
+
+    let exact: Vec<(usize, usize)> = content
+        .match_indices(marker)
+        .map(|(pos, _)| {
+            let start_line = content[..pos].matches('\n').count() + 1;
+            (start_line, start_line + marker_line_count - 1)
+        })
+        .collect();
+
+    if !exact.is_empty() {
+        return exact;
+    }
+
+    let norm_marker = normalize_indentation(marker.trim_end_matches('\n'));
+    let marker_lines: Vec<&str> = norm_marker.lines().collect();
+    let content_lines: Vec<&str> = content.lines().collect();
+
+    if marker_lines.len() > content_lines.len() {
+        return Vec::new();
+    }
+
+    (0..=content_lines.len() - marker_lines.len())
+        .filter(|&start_idx| {
+            content_lines[start_idx..start_idx + marker_lines.len()]
+                .iter()
+                .zip(marker_lines.iter())
+                .all(|(content_line, marker_line)| content_line.trim() == *marker_line)
+        })
+        .map(|start_idx| (start_idx + 1, start_idx + marker_lines.len()))
+        .collect()
+}
+
 // NOTE(angeldev)
 // Extracts the actual content from a file at the given line range, preserving original indentation.
 // Used after find_with_normalization finds a match with different indentation.
@@ -292,17 +334,19 @@ pub fn truncate_preview(s: &str, max_len: usize) -> String {
 
 // NOTE(jimmylee)
 // Gets the line number (1-indexed) for a byte position in content.
+// A position at column 0 belongs to the line it starts, not the one before it.
 pub fn byte_pos_to_line(content: &str, pos: usize) -> usize {
-    content[..pos].lines().count().max(1)
+    content[..pos].matches('\n').count() + 1
 }
 
 // NOTE(jimmylee)
 // Gets a range of lines affected by a replacement.
-// Returns (start_line, end_line) as 1-indexed values.
+// Returns (start_line, end_line) as 1-indexed values; a trailing newline in the
+// replaced text ends its last line rather than touching the next one.
 pub fn get_affected_lines(content: &str, pos: usize, old_len: usize) -> (usize, usize) {
     let start_line = byte_pos_to_line(content, pos);
-    let end_pos = pos + old_len;
-    let end_line = byte_pos_to_line(content, end_pos.min(content.len()));
+    let old = &content[pos..(pos + old_len).min(content.len())];
+    let end_line = start_line + old.strip_suffix('\n').unwrap_or(old).matches('\n').count();
     (start_line, end_line)
 }
 
@@ -319,9 +363,7 @@ pub fn insert_after_line(content: &str, anchor: &str, new_content: &str) -> Opti
             // Lines before and including the anchor line
             for (j, l) in lines.iter().enumerate() {
                 result.push_str(l);
-                if j <= i {
-                    result.push('\n');
-                } else if j < lines.len() - 1 {
+                if j <= i || j < lines.len() - 1 {
                     result.push('\n');
                 }
 
@@ -459,6 +501,31 @@ pub fn delete_line_range(content: &str, start: usize, end: usize) -> Option<Stri
     Some(result)
 }
 
+// NOTE(angeldev)
+// Replaces lines in the given range (1-indexed, inclusive) with new content.
+// Returns the new content.
+pub fn replace_line_range(content: &str, start: usize, end: usize, new_content: &str) -> Option<String> {
+    let lines: Vec<&str> = content.lines().collect();
+
+    if start == 0 || start > end || end > lines.len() {
+        return None;
+    }
+
+    let mut result: Vec<&str> = Vec::with_capacity(lines.len());
+    result.extend_from_slice(&lines[..start - 1]);
+    result.extend(new_content.lines());
+    result.extend_from_slice(&lines[end..]);
+
+    let mut output = result.join("\n");
+
+    // Handle trailing newline
+    if content.ends_with('\n') && !output.is_empty() {
+        output.push('\n');
+    }
+
+    Some(output)
+}
+
 // NOTE(jimmylee)
 // Deletes all lines containing the given search string.
 // Returns the new content and count of lines deleted.
@@ -547,6 +614,24 @@ mod tests {
         assert!(!new_content.contains("line3"));
     }
 
+    #[test]
+    fn test_replace_line_range() {
+        let content = "line1\nline2\nline3\n";
+        let result = replace_line_range(content, 2, 2, "a\nb");
+        assert_eq!(result, Some("line1\na\nb\nline3\n".to_string()));
+        assert_eq!(replace_line_range(content, 3, 4, "x"), None);
+    }
+
+    #[test]
+    fn test_find_marker_lines() {
+        let content = "a\n  // BEGIN\nb\n  // END\nc\n";
+        assert_eq!(find_marker_lines(content, "// BEGIN"), vec![(2, 2)]);
+        assert_eq!(find_marker_lines(content, "// END\nc"), vec![(4, 5)]);
+        // Indentation-normalized fallback
+        assert_eq!(find_marker_lines(content, "    // END\n  c"), vec![(4, 5)]);
+        assert!(find_marker_lines(content, "// MISSING").is_empty());
+    }
+
     #[test]
     fn test_normalize_whitespace() {
         let content = "line1  \nline2\t\nline3";
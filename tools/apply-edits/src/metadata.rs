@@ -0,0 +1,187 @@
+// NOTE(jimmylee)
+// Per-file metadata attached to read results.
+// The hash and mtime identify the exact version that was read, so later steps can
+// tell whether a file changed underneath them; language, indentation, line endings
+// and encoding let the Engineer prompt state the file's conventions explicitly.
+
+use crate::indent::{detect_indent_style, IndentStyle};
+use crate::language::Language;
+use crate::read::{decode_text, looks_binary};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+// NOTE(jimmylee)
+// Metadata of one file as read.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileMetadata {
+    // "sha256:<hex>" of the raw bytes
+    pub hash: String,
+    // Seconds since the Unix epoch
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mtime: Option<u64>,
+    pub language: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indent: Option<IndentStyle>,
+    pub line_ending: LineEnding,
+    pub encoding: Encoding,
+    pub is_binary: bool,
+}
+
+// NOTE(jimmylee)
+// Line terminators used in a file.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+    Mixed,
+    // No line breaks at all
+    None,
+}
+
+// NOTE(jimmylee)
+// Text encoding, from the byte order mark and UTF-8 validity.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum Encoding {
+    #[serde(rename = "utf-8")]
+    Utf8,
+    #[serde(rename = "utf-8-bom")]
+    Utf8Bom,
+    #[serde(rename = "utf-16le")]
+    Utf16Le,
+    #[serde(rename = "utf-16be")]
+    Utf16Be,
+    #[serde(rename = "unknown")]
+    Unknown,
+}
+
+impl FileMetadata {
+    // NOTE(jimmylee)
+    // Metadata of bytes read from full_path (path is workdir-relative).
+    pub fn from_bytes(full_path: &Path, path: &str, bytes: &[u8]) -> FileMetadata {
+        let is_binary = looks_binary(bytes);
+        let language = Language::from_path(path);
+        // detect_indent_style falls back to the language default, which would claim a
+        // convention for files with no indented lines at all
+        let indent = match decode_text(bytes.to_vec()) {
+            Ok(content) if !is_binary => {
+                let indented = content.lines().any(|l| l.starts_with([' ', '\t']) && !l.trim().is_empty());
+                let extension = Path::new(path).extension().and_then(|e| e.to_str()).unwrap_or("");
+                Some(if indented { detect_indent_style(&content, extension) } else { IndentStyle::Unknown })
+            }
+            _ => None,
+        };
+        let mtime = fs::metadata(full_path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs());
+
+        FileMetadata {
+            hash: content_hash(bytes),
+            mtime,
+            language: language.name().to_string(),
+            indent,
+            line_ending: line_ending(bytes),
+            encoding: encoding(bytes, is_binary),
+            is_binary,
+        }
+    }
+
+    // NOTE(jimmylee)
+    // Conventions worth stating in a prompt header ("indent: 2 spaces", "CRLF line endings").
+    // LF and UTF-8 are assumed, so only departures from them are listed.
+    pub fn conventions(&self) -> Vec<String> {
+        let mut notes = Vec::new();
+        if let Some(indent) = self.indent.filter(|i| *i != IndentStyle::Unknown) {
+            notes.push(format!("indent: {}", indent.describe()));
+        }
+        match self.line_ending {
+            LineEnding::Crlf => notes.push("CRLF line endings".to_string()),
+            LineEnding::Mixed => notes.push("mixed line endings".to_string()),
+            LineEnding::Lf | LineEnding::None => {}
+        }
+        if self.encoding == Encoding::Utf8Bom {
+            notes.push("UTF-8 with BOM".to_string());
+        }
+        notes
+    }
+}
+
+// NOTE(jimmylee)
+// "sha256:<hex>" of bytes.
+pub fn content_hash(bytes: &[u8]) -> String {
+    let digest = Sha256::digest(bytes);
+    let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("sha256:{}", hex)
+}
+
+fn line_ending(bytes: &[u8]) -> LineEnding {
+    let newlines = bytes.iter().filter(|b| **b == b'\n').count();
+    let crlf = bytes.windows(2).filter(|w| w == b"\r\n").count();
+    match (newlines, crlf) {
+        (0, _) => LineEnding::None,
+        (n, c) if c == n => LineEnding::Crlf,
+        (_, 0) => LineEnding::Lf,
+        _ => LineEnding::Mixed,
+    }
+}
+
+// A BOM wins; otherwise binary content has no text encoding
+fn encoding(bytes: &[u8], is_binary: bool) -> Encoding {
+    if bytes.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        Encoding::Utf8Bom
+    } else if bytes.starts_with(&[0xFF, 0xFE]) {
+        Encoding::Utf16Le
+    } else if bytes.starts_with(&[0xFE, 0xFF]) {
+        Encoding::Utf16Be
+    } else if !is_binary && std::str::from_utf8(bytes).is_ok() {
+        Encoding::Utf8
+    } else {
+        Encoding::Unknown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_metadata_of_text_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("app.ts");
+        let bytes = b"\xEF\xBB\xBFfunction a() {\r\n  return 1;\r\n}\r\n";
+        fs::write(&path, bytes).unwrap();
+
+        let metadata = FileMetadata::from_bytes(&path, "app.ts", bytes);
+        assert_eq!(metadata.language, "typescript");
+        assert_eq!(metadata.indent, Some(IndentStyle::Spaces(2)));
+        assert_eq!((metadata.line_ending, metadata.encoding), (LineEnding::Crlf, Encoding::Utf8Bom));
+        assert!(!metadata.is_binary && metadata.mtime.is_some());
+        assert_eq!(metadata.conventions(), vec!["indent: 2 spaces", "CRLF line endings", "UTF-8 with BOM"]);
+        assert_eq!(serde_json::to_value(metadata.encoding).unwrap(), "utf-8-bom");
+        assert_eq!(
+            content_hash(b"abc"),
+            "sha256:ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_metadata_of_binary_and_mixed() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("x");
+        let metadata = FileMetadata::from_bytes(&path, "logo.png", b"\x89PNG\r\n\0\0");
+        assert!(metadata.is_binary);
+        assert_eq!((metadata.indent, metadata.mtime, metadata.encoding), (None, None, Encoding::Unknown));
+
+        let metadata = FileMetadata::from_bytes(&path, "a.ts", b"a\nb\n");
+        assert_eq!((metadata.indent, metadata.conventions()), (Some(IndentStyle::Unknown), vec![]));
+
+        let metadata = FileMetadata::from_bytes(&path, "a.txt", b"a\r\nb\nc\xff");
+        assert_eq!((metadata.line_ending, metadata.encoding, metadata.indent), (LineEnding::Mixed, Encoding::Unknown, None));
+    }
+}
@@ -0,0 +1,735 @@
+// NOTE(jimmylee)
+// Outline (skeleton) view of a file.
+// Lists top-level declarations (functions, classes, structs, impls, exports, React
+// components, CSS rules, markdown headings) with the lines they span, plus the
+// members of container declarations like classes and impls. Detection is a
+// per-language line scanner on top of the lexical scanner, not a parser: it is
+// meant to tell the Engineer which range to read next, not to be exact.
+
+use crate::language::Language;
+use crate::scanner::code_mask;
+use serde::Serialize;
+
+// NOTE(jimmylee)
+// Longest signature shown per declaration.
+const MAX_SIGNATURE_CHARS: usize = 120;
+
+// NOTE(jimmylee)
+// Lines after a header in which a declaration's body may open
+// (Allman braces, where clauses, multi-line signatures).
+const MAX_HEADER_LOOKAHEAD: usize = 3;
+
+// NOTE(jimmylee)
+// One declaration in the outline (1-indexed, inclusive lines).
+// depth is 0 for top-level declarations and 1 for members of a container.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OutlineItem {
+    pub kind: String,
+    pub name: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub depth: usize,
+    pub signature: String,
+}
+
+// Declaration found on a header line: (kind, name)
+type Declaration = (&'static str, String);
+
+// NOTE(jimmylee)
+// Builds the outline for content, using the language of path.
+// Returns None for languages without an outline scanner.
+pub fn outline(content: &str, path: &str) -> Option<Vec<OutlineItem>> {
+    let language = Language::from_path(path);
+    let items = match language {
+        Language::JavaScript
+        | Language::TypeScript
+        | Language::Rust
+        | Language::Go
+        | Language::Java
+        | Language::Kotlin
+        | Language::C
+        | Language::Cpp
+        | Language::CSharp
+        | Language::Php
+        | Language::Css => bracket_outline(content, language),
+        Language::Shell => shell_outline(content),
+        Language::Python => python_outline(content),
+        Language::Ruby => ruby_outline(content),
+        Language::Markdown => markdown_outline(content),
+        Language::Vue | Language::Svelte | Language::Html => section_outline(content),
+        _ => return None,
+    };
+    Some(items)
+}
+
+// NOTE(jimmylee)
+// Renders an outline as numbered text: "START-END | signature", members indented.
+pub fn render(items: &[OutlineItem], total_lines: usize) -> String {
+    let width = total_lines.to_string().len();
+    let mut result = format!(
+        "Outline: {} declaration(s) in {} lines. Read a declaration with its line range.\n",
+        items.len(),
+        total_lines
+    );
+    for item in items {
+        let range = format!("{}-{}", item.start_line, item.end_line);
+        result.push_str(&format!(
+            "{:<width$} | {}{}\n",
+            range,
+            "  ".repeat(item.depth),
+            item.signature,
+            width = width * 2 + 1
+        ));
+    }
+    result.pop();
+    result
+}
+
+fn item(declaration: Declaration, start_line: usize, end_line: usize, depth: usize, line: &str) -> OutlineItem {
+    let (kind, name) = declaration;
+    let mut signature = line.trim().trim_end_matches('{').trim_end().to_string();
+    if signature.chars().count() > MAX_SIGNATURE_CHARS {
+        signature = signature.chars().take(MAX_SIGNATURE_CHARS).collect::<String>() + "...";
+    }
+    OutlineItem {
+        kind: kind.to_string(),
+        name,
+        start_line,
+        end_line,
+        depth,
+        signature,
+    }
+}
+
+// Bracket depth before and after each line, counting only code
+fn line_depths(content: &str, language: Language) -> Vec<(usize, usize)> {
+    let mask = code_mask(content, language);
+    let mut depths = Vec::new();
+    let mut depth: usize = 0;
+    let mut offset = 0;
+
+    for line in content.split_inclusive('\n') {
+        let before = depth;
+        for (i, b) in line.bytes().enumerate() {
+            if !mask.get(offset + i).copied().unwrap_or(false) {
+                continue;
+            }
+            match b {
+                b'{' | b'(' | b'[' => depth += 1,
+                b'}' | b')' | b']' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+        depths.push((before, depth));
+        offset += line.len();
+    }
+    depths
+}
+
+// NOTE(jimmylee)
+// Outline for brace languages: declarations at depth 0, and the members at
+// depth 1 of containers (classes, impls, traits, interfaces, namespaces).
+fn bracket_outline(content: &str, language: Language) -> Vec<OutlineItem> {
+    let lines: Vec<&str> = content.lines().collect();
+    let depths = line_depths(content, language);
+    let mut items = Vec::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        if depths[i].0 != 0 {
+            continue;
+        }
+        let Some(declaration) = top_level_declaration(language, line.trim()) else { continue };
+        let end = block_end(&lines, &depths, i);
+        let container = is_container(declaration.0);
+        items.push(item(declaration, i + 1, end + 1, 0, line));
+
+        if container {
+            for j in i + 1..end {
+                if depths[j].0 != 1 {
+                    continue;
+                }
+                if let Some(member) = member_declaration(language, lines[j].trim()) {
+                    let member_end = block_end(&lines, &depths, j);
+                    items.push(item(member, j + 1, member_end + 1, 1, lines[j]));
+                }
+            }
+        }
+    }
+    items
+}
+
+// Last line (0-indexed) of the declaration starting on line `start`
+fn block_end(lines: &[&str], depths: &[(usize, usize)], start: usize) -> usize {
+    let depth = depths[start].0;
+    let mut opened = start;
+
+    // A header that doesn't open anything may have its body on a following line
+    if depths[start].1 <= depth && !ends_statement(lines[start]) {
+        for k in start + 1..lines.len().min(start + 1 + MAX_HEADER_LOOKAHEAD) {
+            if depths[k].1 > depth {
+                opened = k;
+                break;
+            }
+            if depths[k].1 < depth || ends_statement(lines[k]) {
+                break;
+            }
+        }
+        if opened == start {
+            return start;
+        }
+    }
+
+    (opened..lines.len()).find(|&k| depths[k].1 <= depth).unwrap_or(lines.len() - 1)
+}
+
+fn ends_statement(line: &str) -> bool {
+    let line = line.trim_end();
+    line.ends_with(';') || line.ends_with('}')
+}
+
+fn is_container(kind: &str) -> bool {
+    matches!(kind, "class" | "impl" | "trait" | "interface" | "namespace" | "object")
+}
+
+// Leading identifier of s
+fn ident(s: &str) -> String {
+    s.trim_start()
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '$')
+        .collect()
+}
+
+// Strips any of the modifier words from the front of line
+fn strip_modifiers<'a>(mut line: &'a str, modifiers: &[&str]) -> &'a str {
+    loop {
+        let before = line;
+        for modifier in modifiers {
+            if let Some(rest) = line.strip_prefix(modifier) {
+                if rest.starts_with(' ') {
+                    line = rest.trim_start();
+                }
+            }
+        }
+        // pub(crate), pub(super) and friends
+        if let Some(rest) = line.strip_prefix("pub(") {
+            if let Some(close) = rest.find(')') {
+                line = rest[close + 1..].trim_start();
+            }
+        }
+        if line == before {
+            return line;
+        }
+    }
+}
+
+// First word and the rest of the line
+fn split_word(line: &str) -> (&str, &str) {
+    match line.split_once(char::is_whitespace) {
+        Some((word, rest)) => (word, rest.trim_start()),
+        None => (line, ""),
+    }
+}
+
+fn top_level_declaration(language: Language, line: &str) -> Option<Declaration> {
+    if line.is_empty() || line.starts_with("//") || line.starts_with("/*") || line.starts_with('*') {
+        return None;
+    }
+    match language {
+        Language::JavaScript | Language::TypeScript => js_declaration(line),
+        Language::Rust => rust_declaration(line),
+        Language::Go => go_declaration(line),
+        Language::Java | Language::Kotlin | Language::CSharp | Language::Php => class_language_declaration(line),
+        Language::C | Language::Cpp => c_declaration(line),
+        Language::Css => css_rule(line),
+        _ => None,
+    }
+}
+
+fn member_declaration(language: Language, line: &str) -> Option<Declaration> {
+    if line.is_empty() || line.starts_with("//") || line.starts_with('*') || line.starts_with('#') {
+        return None;
+    }
+    match language {
+        Language::Rust => {
+            let rest = strip_modifiers(line, &["pub", "async", "unsafe", "const", "extern", "default"]);
+            let name = ident(rest.strip_prefix("fn ")?);
+            (!name.is_empty()).then_some(("method", name))
+        }
+        Language::C | Language::Cpp | Language::CSharp => {
+            // Namespaces hold types as well as functions
+            c_declaration(line).or_else(|| method_declaration(line))
+        }
+        _ => method_declaration(line),
+    }
+}
+
+// NOTE(jimmylee)
+// JavaScript/TypeScript declarations. Capitalized functions and arrow functions
+// are reported as React components.
+fn js_declaration(line: &str) -> Option<Declaration> {
+    if let Some(rest) = line.strip_prefix("export ") {
+        let rest = rest.trim_start();
+        if rest.starts_with('{') || rest.starts_with('*') {
+            return Some(("export", rest.trim_end_matches(';').to_string()));
+        }
+    }
+    let exported_default = line.starts_with("export default");
+    let rest = strip_modifiers(line, &["export", "default", "declare", "async", "abstract"]);
+    let (word, after) = split_word(rest);
+
+    let declaration = match word.trim_end_matches('*') {
+        "function" => {
+            let name = ident(after.trim_start_matches('*'));
+            let kind = if starts_upper(&name) { "component" } else { "function" };
+            (kind, name)
+        }
+        "class" => ("class", ident(after)),
+        "interface" => ("interface", ident(after)),
+        "type" => ("type", ident(after)),
+        "enum" => ("enum", ident(after)),
+        "namespace" | "module" => ("namespace", ident(after)),
+        "const" | "let" | "var" => {
+            let name = ident(after);
+            let value = after.split_once('=').map(|(_, v)| v.trim()).unwrap_or("");
+            let callable = value.contains("=>") || value.starts_with("function") || value.starts_with("async");
+            let kind = match (callable || value.contains("forwardRef") || value.contains("memo("), starts_upper(&name)) {
+                (true, true) => "component",
+                (true, false) => "function",
+                _ => "variable",
+            };
+            (kind, name)
+        }
+        _ if exported_default => ("export", "default".to_string()),
+        _ => return None,
+    };
+    (!declaration.1.is_empty()).then_some(declaration)
+}
+
+fn starts_upper(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| c.is_uppercase())
+}
+
+fn rust_declaration(line: &str) -> Option<Declaration> {
+    if line.starts_with('#') {
+        return None;
+    }
+    if let Some(rest) = line.strip_prefix("macro_rules!") {
+        return Some(("macro", ident(rest)));
+    }
+    let rest = strip_modifiers(line, &["pub", "async", "unsafe", "extern", "default"]);
+    let rest = rest.strip_prefix("\"C\" ").unwrap_or(rest);
+    let (word, after) = split_word(rest);
+
+    let kind = match word {
+        "fn" => "function",
+        "struct" => "struct",
+        "enum" => "enum",
+        "union" => "union",
+        "trait" => "trait",
+        "mod" if line.trim_end().ends_with(';') => return None,
+        "mod" => "mod",
+        "type" => "type",
+        "static" => "static",
+        "const" if after.starts_with("fn ") => return Some(("function", ident(&after[3..]))),
+        "const" => "const",
+        _ if word == "impl" || word.starts_with("impl<") => {
+            // impl<T> Display for Wrapper<T>: keep everything up to the body
+            let header = &rest[4..];
+            let header = header.strip_prefix('<').map(skip_generics).unwrap_or(header);
+            let name = header.split(['{', ';']).next().unwrap_or("").trim();
+            return Some(("impl", name.to_string()));
+        }
+        _ => return None,
+    };
+    let name = ident(after);
+    (!name.is_empty()).then_some((kind, name))
+}
+
+// Skips to after the closing '>' of a generic parameter list (without the opening '<')
+fn skip_generics(s: &str) -> &str {
+    let mut depth = 1;
+    for (i, c) in s.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' => {
+                depth -= 1;
+                if depth == 0 {
+                    return &s[i + 1..];
+                }
+            }
+            _ => {}
+        }
+    }
+    s
+}
+
+fn go_declaration(line: &str) -> Option<Declaration> {
+    let (word, after) = split_word(line);
+    match word {
+        "func" => {
+            // Methods: func (r *Receiver) Name(...)
+            let after = match after.strip_prefix('(') {
+                Some(receiver) => receiver.split_once(')')?.1.trim_start(),
+                None => after,
+            };
+            let kind = if line.starts_with("func (") { "method" } else { "function" };
+            Some((kind, ident(after)))
+        }
+        "type" => {
+            let name = ident(after);
+            let kind = if after.contains(" struct") {
+                "struct"
+            } else if after.contains(" interface") {
+                "interface"
+            } else {
+                "type"
+            };
+            Some((kind, name))
+        }
+        "const" | "var" => Some((if word == "const" { "const" } else { "variable" }, ident(after))),
+        _ => None,
+    }
+}
+
+// NOTE(jimmylee)
+// Java, Kotlin, C# and PHP type declarations (and Kotlin/PHP top-level functions).
+fn class_language_declaration(line: &str) -> Option<Declaration> {
+    if line.starts_with('@') && !line.starts_with("@interface") {
+        return None;
+    }
+    let rest = strip_modifiers(
+        line,
+        &[
+            "public", "private", "protected", "internal", "static", "final", "abstract", "sealed", "open", "data",
+            "partial", "inline", "value", "enum", "annotation", "readonly",
+        ],
+    );
+    let (word, after) = split_word(rest);
+    let kind = match word {
+        "class" | "record" => "class",
+        "interface" | "@interface" => "interface",
+        "trait" => "trait",
+        "object" => "object",
+        "struct" => "class",
+        "namespace" => "namespace",
+        "fun" | "function" => {
+            // Kotlin extension functions: fun String.shout()
+            let name = after.split('(').next().unwrap_or("").rsplit('.').next().unwrap_or("");
+            return Some(("function", ident(name)));
+        }
+        _ if rest.starts_with("enum ") || line.contains(" enum ") => "enum",
+        _ => return None,
+    };
+    let name = ident(after);
+    (!name.is_empty()).then_some((kind, name))
+}
+
+// NOTE(jimmylee)
+// Methods inside a container: a name followed by a parameter list, that isn't a
+// control statement, a call or an initialized field.
+fn method_declaration(line: &str) -> Option<Declaration> {
+    let (head, _) = line.split_once('(')?;
+    if head.contains('=') || head.contains('.') && !head.contains("fun ") || head.ends_with(' ') && head.trim().is_empty() {
+        return None;
+    }
+    if line.trim_end().ends_with(';') && !line.contains("abstract") {
+        return None;
+    }
+    let name = head.trim_end().rsplit([' ', '\t', '*', '&']).next().unwrap_or("");
+    let name = name.rsplit('.').next().unwrap_or(name);
+    let name = ident(name);
+    let control = ["if", "for", "while", "switch", "catch", "return", "new", "throw", "else", "do", "super", "this"];
+    if name.is_empty() || control.contains(&name.as_str()) || name.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some(("method", name))
+}
+
+fn c_declaration(line: &str) -> Option<Declaration> {
+    if let Some(rest) = line.strip_prefix("#define ") {
+        return Some(("macro", ident(rest)));
+    }
+    if line.starts_with('#') {
+        return None;
+    }
+    let rest = strip_modifiers(line, &["typedef", "static", "inline", "extern", "template<>", "export"]);
+    let (word, after) = split_word(rest);
+    match word {
+        "struct" | "union" | "enum" if !rest.trim_end().ends_with(';') || rest.contains('{') => {
+            let kind = if word == "enum" { "enum" } else { "struct" };
+            Some((kind, ident(after.trim_start_matches("class "))))
+        }
+        "class" => Some(("class", ident(after))),
+        "namespace" => Some(("namespace", ident(after))),
+        _ => {
+            // Function definitions: a return type, a name and parameters; prototypes end in ';'
+            let (head, _) = line.split_once('(')?;
+            if line.trim_end().ends_with(';') || head.contains('=') || head.split_whitespace().count() < 2 {
+                return None;
+            }
+            let name = head.rsplit([' ', '*', '&']).next().unwrap_or("");
+            let name = name.rsplit("::").next().unwrap_or(name);
+            let name = ident(name);
+            let control = ["if", "for", "while", "switch", "return"];
+            (!name.is_empty() && !control.contains(&name.as_str())).then_some(("function", name))
+        }
+    }
+}
+
+fn css_rule(line: &str) -> Option<Declaration> {
+    let (selector, _) = line.split_once('{')?;
+    let selector = selector.trim();
+    if selector.is_empty() {
+        return None;
+    }
+    let kind = if selector.starts_with('@') { "at_rule" } else { "rule" };
+    Some((kind, selector.to_string()))
+}
+
+// NOTE(jimmylee)
+// Shell: functions declared at column 0, ending at the next "}" at column 0.
+// Brackets aren't counted because case patterns and heredocs unbalance them.
+fn shell_outline(content: &str) -> Vec<OutlineItem> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut items = Vec::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        let rest = line.strip_prefix("function ").unwrap_or(line);
+        let name = ident(rest);
+        let after = rest[name.len()..].trim_start();
+        if name.is_empty() || !(after.starts_with("()") || line.starts_with("function ")) {
+            continue;
+        }
+        let end = (i..lines.len()).find(|&k| lines[k].starts_with('}')).unwrap_or(i);
+        items.push(item(("function", name), i + 1, end + 1, 0, line));
+    }
+    items
+}
+
+// NOTE(jimmylee)
+// Python: top-level def/class, plus the methods directly inside each class.
+// A block ends at the last code line before the next line indented as far out.
+fn python_outline(content: &str) -> Vec<OutlineItem> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mask = code_mask(content, Language::Python);
+    let mut offset = 0;
+    let mut code_line = Vec::with_capacity(lines.len());
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim();
+        let start = offset + (line.len() - line.trim_start().len());
+        code_line.push(!trimmed.is_empty() && !trimmed.starts_with('#') && mask.get(start).copied().unwrap_or(false));
+        offset += line.len();
+    }
+
+    let indent = |line: &str| line.len() - line.trim_start().len();
+    let block_end = |start: usize| {
+        let outer = indent(lines[start]);
+        let mut end = start;
+        for k in start + 1..lines.len() {
+            if code_line[k] && indent(lines[k]) <= outer && !lines[k].trim_start().starts_with(')') {
+                break;
+            }
+            if code_line[k] {
+                end = k;
+            }
+        }
+        end
+    };
+    let declaration = |line: &str| -> Option<Declaration> {
+        let rest = line.trim().strip_prefix("async ").unwrap_or(line.trim());
+        if let Some(name) = rest.strip_prefix("def ") {
+            Some(("function", ident(name)))
+        } else {
+            rest.strip_prefix("class ").map(|name| ("class", ident(name)))
+        }
+    };
+
+    let mut items = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        if !code_line[i] || indent(line) != 0 {
+            continue;
+        }
+        let Some(found) = declaration(line) else { continue };
+        let end = block_end(i);
+        let is_class = found.0 == "class";
+        items.push(item(found, i + 1, end + 1, 0, line.trim_end_matches(':')));
+
+        if is_class {
+            let member_indent = (i + 1..=end).find(|&k| code_line[k]).map(|k| indent(lines[k]));
+            for k in i + 1..=end {
+                if !code_line[k] || Some(indent(lines[k])) != member_indent {
+                    continue;
+                }
+                if let Some((_, name)) = declaration(lines[k]) {
+                    items.push(item(("method", name), k + 1, block_end(k) + 1, 1, lines[k].trim_end_matches(':')));
+                }
+            }
+        }
+    }
+    items
+}
+
+// NOTE(jimmylee)
+// Ruby: def/class/module blocks, ended by the `end` at the same indentation.
+fn ruby_outline(content: &str) -> Vec<OutlineItem> {
+    let lines: Vec<&str> = content.lines().collect();
+    let indent = |line: &str| line.len() - line.trim_start().len();
+    let mut items = Vec::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        let (word, after) = split_word(line.trim());
+        let kind = match word {
+            "class" => "class",
+            "module" => "namespace",
+            "def" => "function",
+            _ => continue,
+        };
+        let depth = indent(line) / 2;
+        if depth > 1 {
+            continue;
+        }
+        let end = (i + 1..lines.len())
+            .find(|&k| indent(lines[k]) == indent(line) && lines[k].trim() == "end")
+            .unwrap_or(i);
+        let kind = if kind == "function" && depth == 1 { "method" } else { kind };
+        let name = after.split(['(', ' ', '<', ';']).next().unwrap_or("").to_string();
+        items.push(item((kind, name), i + 1, end + 1, depth, line));
+    }
+    items
+}
+
+// NOTE(jimmylee)
+// Markdown: headings up to level 3, each spanning until the next heading of the
+// same or a higher level. Fenced code blocks are skipped.
+fn markdown_outline(content: &str) -> Vec<OutlineItem> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut headings: Vec<(usize, usize)> = Vec::new(); // (line index, level)
+    let mut fenced = false;
+
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fenced = !fenced;
+            continue;
+        }
+        let level = line.chars().take_while(|c| *c == '#').count();
+        if !fenced && (1..=3).contains(&level) && line[level..].starts_with(' ') {
+            headings.push((i, level));
+        }
+    }
+
+    headings
+        .iter()
+        .enumerate()
+        .map(|(n, &(i, level))| {
+            let end = headings[n + 1..]
+                .iter()
+                .find(|(_, l)| *l <= level)
+                .map(|(k, _)| k - 1)
+                .unwrap_or(lines.len() - 1);
+            let title = lines[i][level..].trim().to_string();
+            item(("heading", title), i + 1, end + 1, level - 1, lines[i])
+        })
+        .collect()
+}
+
+// Single-file components: the <template>, <script> and <style> sections
+fn section_outline(content: &str) -> Vec<OutlineItem> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut items = Vec::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        for tag in ["template", "script", "style"] {
+            if !line.starts_with(&format!("<{}", tag)) {
+                continue;
+            }
+            let close = format!("</{}>", tag);
+            let end = (i..lines.len()).find(|&k| lines[k].contains(&close)).unwrap_or(i);
+            items.push(item(("section", tag.to_string()), i + 1, end + 1, 0, line));
+        }
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(content: &str, path: &str) -> Vec<(String, String, usize, usize, usize)> {
+        outline(content, path)
+            .unwrap()
+            .into_iter()
+            .map(|i| (i.kind, i.name, i.start_line, i.end_line, i.depth))
+            .collect()
+    }
+
+    fn entry(kind: &str, name: &str, start: usize, end: usize, depth: usize) -> (String, String, usize, usize, usize) {
+        (kind.to_string(), name.to_string(), start, end, depth)
+    }
+
+    #[test]
+    fn test_typescript_and_rust_outlines() {
+        let ts = "import x from 'y';\n\nexport const Button = ({ label }: Props) => {\n  const s = \"}\";\n  return <b>{label}</b>;\n};\n\nexport class Store {\n  private items = [];\n\n  add(item: Item) {\n    if (item) {\n      this.items.push(item);\n    }\n  }\n}\n\nexport { Store as default };\n";
+        assert_eq!(
+            summary(ts, "src/button.tsx"),
+            vec![
+                entry("component", "Button", 3, 6, 0),
+                entry("class", "Store", 8, 16, 0),
+                entry("method", "add", 11, 15, 1),
+                entry("export", "{ Store as default }", 18, 18, 0),
+            ]
+        );
+
+        let rust = "use std::fmt;\n\n#[derive(Debug)]\npub struct Point {\n    x: i32,\n}\n\nimpl<T> fmt::Display for Wrapper<T>\nwhere\n    T: fmt::Display,\n{\n    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {\n        write!(f, \"{}\", self.0)\n    }\n}\n\npub(crate) fn main() {}\n";
+        assert_eq!(
+            summary(rust, "lib.rs"),
+            vec![
+                entry("struct", "Point", 4, 6, 0),
+                entry("impl", "fmt::Display for Wrapper<T>", 8, 15, 0),
+                entry("method", "fmt", 12, 14, 1),
+                entry("function", "main", 17, 17, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_python_markdown_and_css_outlines() {
+        let py = "import os\n\nclass Repo:\n    \"\"\"Docs.\n\ndef not_a_method(): ...\n\"\"\"\n\n    def path(self):\n        return os.getcwd()\n\n    async def load(self):\n        pass\n\ndef main():\n    Repo()\n";
+        assert_eq!(
+            summary(py, "repo.py"),
+            vec![
+                entry("class", "Repo", 3, 13, 0),
+                entry("method", "path", 9, 10, 1),
+                entry("method", "load", 12, 13, 1),
+                entry("function", "main", 15, 16, 0),
+            ]
+        );
+
+        let md = "# Title\nintro\n## Setup\n```sh\n# not a heading\n```\n## Usage\ntext\n";
+        let names: Vec<(String, usize, usize)> =
+            outline(md, "README.md").unwrap().into_iter().map(|i| (i.name, i.start_line, i.end_line)).collect();
+        assert_eq!(
+            names,
+            vec![("Title".to_string(), 1, 8), ("Setup".to_string(), 3, 6), ("Usage".to_string(), 7, 8)]
+        );
+
+        let css = ".a,\n.b {\n  color: red;\n}\n@media (max-width: 600px) {\n  .a { color: blue; }\n}\n";
+        assert_eq!(
+            summary(css, "site.css"),
+            vec![entry("rule", ".b", 2, 4, 0), entry("at_rule", "@media (max-width: 600px)", 5, 7, 0)]
+        );
+        assert!(outline("x", "data.bin").is_none());
+
+        let sh = "#!/bin/bash\nsetup() {\n    case $1 in\n        a) echo ')' ;;\n    esac\n}\n\nfunction main {\n    setup\n}\n";
+        assert_eq!(summary(sh, "run.sh"), vec![entry("function", "setup", 2, 6, 0), entry("function", "main", 8, 10, 0)]);
+    }
+
+    #[test]
+    fn test_render() {
+        let items = outline("fn a() {\n}\n\nfn b() {}\n", "x.rs").unwrap();
+        assert_eq!(
+            render(&items, 120),
+            "Outline: 2 declaration(s) in 120 lines. Read a declaration with its line range.\n1-2     | fn a()\n4-4     | fn b() {}"
+        );
+    }
+}
@@ -64,6 +64,7 @@ pub fn print_edit_warning(
 
 // NOTE(jimmylee)
 // Prints a failed edit result to stderr with detailed error info.
+#[allow(clippy::too_many_arguments)]
 pub fn print_edit_error(
     index: usize,
     total: usize,
@@ -170,6 +171,7 @@ pub fn print_edit_outcome(outcome: &EditOutcome, index: usize, total: usize) {
             path,
             edit_type,
             message,
+            files,
             ..
         } => {
             let msg = message
@@ -177,6 +179,10 @@ pub fn print_edit_outcome(outcome: &EditOutcome, index: usize, total: usize) {
                 .map(|s| s.as_str())
                 .unwrap_or("Success");
             print_edit_success(index, total, edit_type, path, msg);
+            // NOTE(angeldev): Multi-file edits list each file they changed
+            for file in files.iter().flatten() {
+                eprintln!("        {} {}", file.path.white(), format!("({})", file.count).dimmed());
+            }
         }
         EditOutcome::Warning {
             path,
@@ -223,7 +229,7 @@ pub fn print_read_header(path: &str, lines: usize, bytes: usize) {
 
 // NOTE(jimmylee)
 // Formats byte count as human-readable string.
-fn format_bytes(bytes: usize) -> String {
+pub fn format_bytes(bytes: usize) -> String {
     if bytes < 1024 {
         format!("{} B", bytes)
     } else if bytes < 1024 * 1024 {
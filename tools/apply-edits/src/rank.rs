@@ -0,0 +1,370 @@
+// NOTE(jimmylee)
+// Directive-driven file ranking for the `rank` subcommand.
+// Replaces the keyword loop in prioritize_files_for_context (lib/planning.sh).
+// The directive is tokenized (camelCase and snake_case split, stop words dropped,
+// light suffix stemming) and every ignore-aware, non-binary, non-generated file is
+// scored as the sum of:
+//   content     BM25 over the file contents
+//   path        terms in the file name (strong) or directory names (weak)
+//   entry_point index/main/app/router files
+//   config      package.json, tsconfig.json and friends
+//   churn       how often the file changed in the last CHURN_COMMITS commits
+// Each file carries its breakdown so callers can see why it ranked where it did.
+
+use crate::read::{is_binary, matching_files, read_text};
+use crate::tree::is_generated_name;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+// NOTE(jimmylee)
+// Files returned unless --limit says otherwise.
+pub const DEFAULT_LIMIT: usize = 50;
+
+// NOTE(jimmylee)
+// Files larger than this are ranked by path and history only.
+const MAX_INDEXED_BYTES: u64 = 512 * 1024;
+
+// NOTE(jimmylee)
+// Most directive terms used for scoring.
+const MAX_TERMS: usize = 32;
+
+// NOTE(jimmylee)
+// Commits of history read for the churn score.
+const CHURN_COMMITS: usize = 100;
+
+// NOTE(jimmylee)
+// BM25 parameters (the usual defaults).
+const BM25_K1: f64 = 1.2;
+const BM25_B: f64 = 0.75;
+
+// NOTE(jimmylee)
+// Signal weights, relative to one BM25 point. The ratios follow the old shell
+// scoring: file name 10, path 5, entry point 3, config 2.
+const FILENAME_WEIGHT: f64 = 2.0;
+const DIRECTORY_WEIGHT: f64 = 1.0;
+const ENTRY_POINT_WEIGHT: f64 = 0.6;
+const CONFIG_WEIGHT: f64 = 0.4;
+const CORE_DIR_WEIGHT: f64 = 0.2;
+const CHURN_WEIGHT: f64 = 1.0;
+
+// NOTE(jimmylee)
+// Words that say nothing about where the change belongs.
+const STOP_WORDS: &[&str] = &[
+    "the", "and", "for", "that", "this", "with", "from", "into", "onto", "are", "was", "were", "been", "being", "but",
+    "not", "you", "your", "our", "their", "its", "can", "could", "should", "would", "will", "must", "may", "might",
+    "have", "has", "had", "does", "doesn", "don", "all", "any", "each", "some", "more", "most", "other", "such",
+    "than", "then", "there", "these", "those", "when", "where", "which", "while", "who", "what", "why", "how", "also",
+    "just", "only", "very", "make", "made", "add", "adds", "added", "use", "uses", "using", "new", "way", "like",
+    "want", "need", "needs", "please", "sure", "codebase", "code", "repo", "repository", "project", "file", "files",
+    "follow", "simple", "exactly", "specific", "style", "convention", "conventions", "problem", "solves", "missed",
+    "complication", "adheres",
+];
+
+const ENTRY_POINTS: &[&str] = &[
+    "index.ts", "index.tsx", "index.js", "index.jsx", "main.ts", "main.tsx", "main.js", "main.py", "main.go", "main.rs",
+    "lib.rs", "app.ts", "app.tsx", "app.js", "app.jsx", "app.py", "app.vue", "app.svelte", "routes.ts", "router.ts",
+    "routes.js", "router.js", "__init__.py", "manage.py",
+];
+
+const CONFIG_FILES: &[&str] = &[
+    "package.json", "tsconfig.json", "cargo.toml", "pyproject.toml", "go.mod", "gemfile", "pom.xml", "build.gradle",
+];
+const CONFIG_PREFIXES: &[&str] = &["next.config.", "vite.config.", "tailwind.config.", "webpack.config."];
+
+const CORE_DIRS: &[&str] = &["lib", "utils", "core", "api"];
+
+// NOTE(jimmylee)
+// Per-signal contributions to a file's score.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct ScoreBreakdown {
+    pub content: f64,
+    pub path: f64,
+    pub entry_point: f64,
+    pub config: f64,
+    pub churn: f64,
+}
+
+// NOTE(jimmylee)
+// A ranked file.
+#[derive(Debug, Serialize)]
+pub struct RankedFile {
+    pub path: String,
+    pub score: f64,
+    pub matched_terms: Vec<String>,
+    pub breakdown: ScoreBreakdown,
+}
+
+// NOTE(jimmylee)
+// Result of a ranking run.
+#[derive(Debug, Serialize)]
+pub struct RankResult {
+    pub terms: Vec<String>,
+    pub files_scanned: usize,
+    pub files: Vec<RankedFile>,
+}
+
+// A file's term statistics
+struct Document {
+    path: String,
+    length: usize,
+    frequencies: HashMap<String, usize>,
+}
+
+// NOTE(jimmylee)
+// Ranks the files under workdir against directive, best first, keeping limit files
+// (all of them when limit is 0).
+pub fn rank(workdir: &Path, directive: &str, limit: usize) -> Result<RankResult, String> {
+    let terms: Vec<String> = query_terms(directive);
+    let paths: Vec<String> = matching_files(workdir, &["**".to_string()])?
+        .into_iter()
+        .filter(|path| !is_generated_name(path.rsplit('/').next().unwrap_or(path)))
+        .filter(|path| !is_binary(&workdir.join(path)))
+        .collect();
+
+    let documents: Vec<Document> = paths.iter().map(|path| index(workdir, path, &terms)).collect();
+    let indexed: Vec<&Document> = documents.iter().filter(|d| d.length > 0).collect();
+    let average_length = indexed.iter().map(|d| d.length).sum::<usize>() as f64 / indexed.len().max(1) as f64;
+    let idf: HashMap<&str, f64> = terms
+        .iter()
+        .map(|term| {
+            let df = indexed.iter().filter(|d| d.frequencies.contains_key(term)).count() as f64;
+            let n = indexed.len() as f64;
+            (term.as_str(), ((n - df + 0.5) / (df + 0.5) + 1.0).ln())
+        })
+        .collect();
+
+    let churn = churn_counts(workdir);
+    let max_churn = churn.values().copied().max().unwrap_or(0);
+
+    let mut files: Vec<RankedFile> = documents
+        .iter()
+        .map(|document| {
+            let mut matched = BTreeSet::new();
+            let mut breakdown = ScoreBreakdown::default();
+
+            for term in &terms {
+                let Some(&tf) = document.frequencies.get(term) else { continue };
+                let tf = tf as f64;
+                let norm = 1.0 - BM25_B + BM25_B * document.length as f64 / average_length.max(1.0);
+                breakdown.content += idf[term.as_str()] * tf * (BM25_K1 + 1.0) / (tf + BM25_K1 * norm);
+                matched.insert(term.clone());
+            }
+
+            let (path_score, path_terms) = path_score(&document.path, &terms);
+            breakdown.path = path_score;
+            matched.extend(path_terms);
+            (breakdown.entry_point, breakdown.config) = heuristics(&document.path);
+            if let Some(&count) = churn.get(&document.path) {
+                breakdown.churn = CHURN_WEIGHT * (1.0 + count as f64).ln() / (1.0 + max_churn as f64).ln();
+            }
+
+            let score = breakdown.content + breakdown.path + breakdown.entry_point + breakdown.config + breakdown.churn;
+            RankedFile {
+                path: document.path.clone(),
+                score: round(score),
+                matched_terms: matched.into_iter().collect(),
+                breakdown: ScoreBreakdown {
+                    content: round(breakdown.content),
+                    path: round(breakdown.path),
+                    entry_point: round(breakdown.entry_point),
+                    config: round(breakdown.config),
+                    churn: round(breakdown.churn),
+                },
+            }
+        })
+        .collect();
+
+    files.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.path.cmp(&b.path)));
+    if limit > 0 {
+        files.truncate(limit);
+    }
+    Ok(RankResult {
+        terms,
+        files_scanned: documents.len(),
+        files,
+    })
+}
+
+fn round(value: f64) -> f64 {
+    (value * 1000.0).round() / 1000.0
+}
+
+// NOTE(jimmylee)
+// Lowercased, stemmed words of text. Splits on non-alphanumerics and camelCase
+// boundaries ("fetchUserName" is fetch, user, name); drops short words and numbers.
+pub fn tokenize(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut previous: Option<char> = None;
+
+    for c in text.chars() {
+        let boundary = !c.is_alphanumeric()
+            || (c.is_uppercase() && previous.is_some_and(|p| p.is_lowercase() || p.is_ascii_digit()));
+        if boundary && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        if c.is_alphanumeric() {
+            current.extend(c.to_lowercase());
+        }
+        previous = Some(c);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+
+    words
+        .into_iter()
+        .filter(|w| w.len() >= 3 && !w.chars().all(|c| c.is_ascii_digit()))
+        .map(|w| stem(&w))
+        .collect()
+}
+
+// Strips common inflections so "routes", "routing" and "route" meet
+fn stem(word: &str) -> String {
+    for suffix in ["ing", "ies", "es", "ed", "s"] {
+        if let Some(base) = word.strip_suffix(suffix) {
+            if base.len() >= 3 && !base.ends_with('s') {
+                return if suffix == "ies" { format!("{}y", base) } else { base.to_string() };
+            }
+        }
+    }
+    word.to_string()
+}
+
+fn query_terms(directive: &str) -> Vec<String> {
+    let stop_words: BTreeSet<String> = STOP_WORDS.iter().map(|w| stem(w)).collect();
+    let mut seen = BTreeSet::new();
+    tokenize(directive)
+        .into_iter()
+        .filter(|term| !stop_words.contains(term) && seen.insert(term.clone()))
+        .take(MAX_TERMS)
+        .collect()
+}
+
+fn index(workdir: &Path, path: &str, terms: &[String]) -> Document {
+    let mut document = Document {
+        path: path.to_string(),
+        length: 0,
+        frequencies: HashMap::new(),
+    };
+    let full_path = workdir.join(path);
+    if fs::metadata(&full_path).map(|m| m.len() > MAX_INDEXED_BYTES).unwrap_or(true) {
+        return document;
+    }
+    let Ok(content) = read_text(&full_path) else { return document };
+
+    for token in tokenize(&content) {
+        document.length += 1;
+        if terms.contains(&token) {
+            *document.frequencies.entry(token).or_default() += 1;
+        }
+    }
+    document
+}
+
+// Path signal and the terms it matched
+fn path_score(path: &str, terms: &[String]) -> (f64, Vec<String>) {
+    let (directories, name) = path.rsplit_once('/').unwrap_or(("", path));
+    let name_tokens = tokenize(name);
+    let directory_tokens = tokenize(directories);
+
+    let mut score = 0.0;
+    let mut matched = Vec::new();
+    for term in terms {
+        if name_tokens.contains(term) {
+            score += FILENAME_WEIGHT;
+        } else if directory_tokens.contains(term) {
+            score += DIRECTORY_WEIGHT;
+        } else {
+            continue;
+        }
+        matched.push(term.clone());
+    }
+    (score, matched)
+}
+
+// Entry point and config boosts (core directories count as a small entry point boost)
+fn heuristics(path: &str) -> (f64, f64) {
+    let (directories, name) = path.rsplit_once('/').unwrap_or(("", path));
+    let name = name.to_lowercase();
+
+    let mut entry_point = 0.0;
+    if ENTRY_POINTS.contains(&name.as_str()) {
+        entry_point += ENTRY_POINT_WEIGHT;
+    }
+    if directories.split('/').any(|d| CORE_DIRS.contains(&d)) {
+        entry_point += CORE_DIR_WEIGHT;
+    }
+
+    let is_config = CONFIG_FILES.contains(&name.as_str()) || CONFIG_PREFIXES.iter().any(|p| name.starts_with(p));
+    (entry_point, if is_config { CONFIG_WEIGHT } else { 0.0 })
+}
+
+// How many recent commits touched each workdir-relative path; empty outside git
+fn churn_counts(workdir: &Path) -> HashMap<String, usize> {
+    let output = Command::new("git")
+        .current_dir(workdir)
+        .args(["log", "--name-only", "--relative", "--pretty=format:", "-n"])
+        .arg(CHURN_COMMITS.to_string())
+        .stderr(Stdio::null())
+        .output();
+
+    let mut counts = HashMap::new();
+    if let Some(output) = output.ok().filter(|o| o.status.success()) {
+        for line in String::from_utf8_lossy(&output.stdout).lines().filter(|l| !l.is_empty()) {
+            *counts.entry(line.to_string()).or_default() += 1;
+        }
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let full = root.join(path);
+        fs::create_dir_all(full.parent().unwrap()).unwrap();
+        fs::write(full, content).unwrap();
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(tokenize("fetchUserName in user_routes.ts, HTTP2 404"), vec!["fetch", "user", "name", "user", "rout", "http2"]);
+        assert_eq!(
+            query_terms("Add a footer to the codebase that shows the copyright year. Footers should be simple."),
+            vec!["footer", "show", "copyright", "year"]
+        );
+    }
+
+    #[test]
+    fn test_rank_orders_by_signals() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        write(root, "src/components/Footer.tsx", "export function Footer() { return <footer>copyright</footer> }\n");
+        write(root, "src/layout.tsx", "import { Footer } from './components/Footer'\nconst x = 1\n");
+        write(root, "src/index.ts", "console.log('hi')\n");
+        write(root, "src/math.ts", "export const add = (a, b) => a + b\n");
+        write(root, "package.json", "{}\n");
+        write(root, "package-lock.json", "{\"footer\": \"footer\"}\n");
+
+        let result = rank(root, "Add a footer with the copyright notice", 10).unwrap();
+        assert_eq!(result.terms, vec!["footer", "copyright", "notice"]);
+        assert_eq!(result.files_scanned, 5);
+
+        let order: Vec<&str> = result.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(order, vec!["src/components/Footer.tsx", "src/layout.tsx", "src/index.ts", "package.json", "src/math.ts"]);
+
+        let top = &result.files[0];
+        assert_eq!(top.matched_terms, vec!["copyright", "footer"]);
+        assert_eq!(top.breakdown.path, FILENAME_WEIGHT);
+        assert!(top.breakdown.content > result.files[1].breakdown.content);
+        assert_eq!(result.files[2].breakdown.entry_point, ENTRY_POINT_WEIGHT);
+
+        assert_eq!(rank(root, "footer", 2).unwrap().files.len(), 2);
+    }
+}
@@ -2,10 +2,80 @@
 // File reading operations with line number formatting.
 // Provides formatted file content for the Engineer's context.
 
+use crate::budget::BudgetReport;
+use crate::error::EditError;
+use crate::indent::IndentStyle;
+use crate::metadata::{FileMetadata, LineEnding};
+use crate::outline::{outline, render, OutlineItem};
+use crate::walk::{names_hidden, relative_path, PathFilter};
+use ignore::WalkBuilder;
 use serde::Serialize;
 use std::fs;
+use std::io::Read;
 use std::path::Path;
 
+// NOTE(jimmylee)
+// Directories skipped by --glob reads even when no ignore file lists them.
+// A pattern that starts inside one ("dist/**") still reaches it.
+pub const DEFAULT_EXCLUDES: &[&str] = &[
+    "node_modules",
+    "dist",
+    "build",
+    "out",
+    ".next",
+    ".nuxt",
+    ".svelte-kit",
+    "target",
+    "vendor",
+    "coverage",
+    "__pycache__",
+    ".venv",
+    "venv",
+    ".git",
+];
+
+// NOTE(jimmylee)
+// Default cap on files read from --glob matches.
+pub const DEFAULT_MAX_FILES: usize = 20;
+
+// NOTE(jimmylee)
+// Lines kept either side of a keyword match by the relevant strategy.
+const RELEVANT_CONTEXT: usize = 3;
+
+// NOTE(jimmylee)
+// How an unranged read longer than max_lines is cut down.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Truncation {
+    // Outline when the language has one, otherwise head
+    #[default]
+    Auto,
+    // The first max_lines lines
+    Head,
+    // The first two thirds of max_lines and the last third
+    HeadTail,
+    // A short head plus windows around lines containing any keyword
+    // (case-insensitive); head_tail when nothing matches
+    Relevant(Vec<String>),
+}
+
+impl Truncation {
+    // NOTE(jimmylee)
+    // Parses a --truncate strategy; relevant needs at least one keyword.
+    pub fn parse(strategy: &str, keywords: &[String]) -> Result<Truncation, String> {
+        match strategy {
+            "auto" => Ok(Truncation::Auto),
+            "head" => Ok(Truncation::Head),
+            "head_tail" => Ok(Truncation::HeadTail),
+            "relevant" if keywords.is_empty() => Err("--truncate relevant needs --keywords".to_string()),
+            "relevant" => Ok(Truncation::Relevant(keywords.to_vec())),
+            _ => Err(format!(
+                "Unknown truncation: {}. Use 'auto', 'head', 'head_tail' or 'relevant'",
+                strategy
+            )),
+        }
+    }
+}
+
 // NOTE(jimmylee)
 // Result of reading a single file.
 #[derive(Debug, Serialize)]
@@ -18,12 +88,39 @@ pub struct FileReadResult {
     pub bytes: Option<usize>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub truncated: Option<bool>,
+    // NOTE(jimmylee): First and last line shown when a range was requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<usize>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub content: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub content_with_line_numbers: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    // NOTE(jimmylee): Set by --budget-tokens packing (see budget.rs)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub view: Option<View>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tokens: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outline: Option<Vec<OutlineItem>>,
+    // NOTE(jimmylee): Hash, mtime and conventions of the version read (see metadata.rs)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<FileMetadata>,
+}
+
+// NOTE(jimmylee)
+// How much of a file a read result shows.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum View {
+    Full,
+    HeadTail,
+    Relevant,
+    Outline,
+    Omitted,
 }
 
 // NOTE(jimmylee)
@@ -31,6 +128,101 @@ pub struct FileReadResult {
 #[derive(Debug, Serialize)]
 pub struct MultiFileReadResult {
     pub files: Vec<FileReadResult>,
+    // NOTE(jimmylee): Present when files were selected by --glob
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub glob: Option<GlobMatches>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget: Option<BudgetReport>,
+}
+
+// NOTE(jimmylee)
+// Files selected by --glob patterns, in path order.
+#[derive(Debug, Default, Serialize)]
+pub struct GlobMatches {
+    // Text files matched, before --max-files
+    pub matched: usize,
+    // Paths to read (at most max_files)
+    #[serde(skip)]
+    pub paths: Vec<String>,
+    pub truncated: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skipped_binary: Vec<String>,
+}
+
+// NOTE(jimmylee)
+// An inclusive, 1-indexed window of lines to read.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LineRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl LineRange {
+    // NOTE(jimmylee)
+    // Parses "2380-2450". The end may be omitted ("2380-") to read to the end of the file.
+    pub fn parse(spec: &str) -> Result<LineRange, String> {
+        let invalid = || format!("Invalid line range '{}': expected START-END, e.g. 100-180", spec);
+        let (start, end) = spec.split_once('-').ok_or_else(invalid)?;
+        let start: usize = start.trim().parse().map_err(|_| invalid())?;
+        let end: usize = match end.trim() {
+            "" => usize::MAX,
+            end => end.parse().map_err(|_| invalid())?,
+        };
+        if start == 0 || end < start {
+            return Err(invalid());
+        }
+        Ok(LineRange { start, end })
+    }
+
+    // NOTE(jimmylee)
+    // Parses "2400:30", the 30 lines either side of line 2400.
+    pub fn around(spec: &str) -> Result<LineRange, String> {
+        let invalid = || format!("Invalid --around '{}': expected LINE:RADIUS, e.g. 2400:30", spec);
+        let (line, radius) = spec.split_once(':').ok_or_else(invalid)?;
+        let line: usize = line.trim().parse().map_err(|_| invalid())?;
+        let radius: usize = radius.trim().parse().map_err(|_| invalid())?;
+        if line == 0 {
+            return Err(invalid());
+        }
+        Ok(LineRange {
+            start: line.saturating_sub(radius).max(1),
+            end: line.saturating_add(radius),
+        })
+    }
+}
+
+// NOTE(jimmylee)
+// A file to read, optionally limited to a range ("src/app.ts:100-180").
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReadSpec {
+    pub path: String,
+    pub range: Option<LineRange>,
+}
+
+impl ReadSpec {
+    // NOTE(jimmylee)
+    // Splits a trailing ":START-END" off a path. Anything else after the last colon
+    // is left as part of the path.
+    pub fn parse(spec: &str) -> ReadSpec {
+        let spec = spec.trim();
+        let range = spec.rsplit_once(':').and_then(|(path, suffix)| {
+            let numeric = suffix.contains('-') && suffix.chars().all(|c| c.is_ascii_digit() || c == '-');
+            match LineRange::parse(suffix) {
+                Ok(range) if numeric && !path.is_empty() => Some((path, range)),
+                _ => None,
+            }
+        });
+        match range {
+            Some((path, range)) => ReadSpec {
+                path: path.to_string(),
+                range: Some(range),
+            },
+            None => ReadSpec {
+                path: spec.to_string(),
+                range: None,
+            },
+        }
+    }
 }
 
 // NOTE(jimmylee)
@@ -40,55 +232,153 @@ pub fn read_file_with_line_numbers(
     workdir: &Path,
     path: &str,
     max_lines: Option<usize>,
+) -> FileReadResult {
+    read_file_range(workdir, path, None, max_lines)
+}
+
+// NOTE(jimmylee)
+// Reads a file, optionally only a range of it. Ranged output keeps the original
+// line numbers and marks the lines left out before and after the window.
+// max_lines still caps how many lines are shown.
+pub fn read_file_range(
+    workdir: &Path,
+    path: &str,
+    range: Option<LineRange>,
+    max_lines: Option<usize>,
+) -> FileReadResult {
+    read_file_truncated(workdir, path, range, max_lines, &Truncation::Auto)
+}
+
+// NOTE(jimmylee)
+// read_file_range with a choice of how files over max_lines are cut down.
+// Ranged reads are never truncated by strategy, only capped.
+pub fn read_file_truncated(
+    workdir: &Path,
+    path: &str,
+    range: Option<LineRange>,
+    max_lines: Option<usize>,
+    truncation: &Truncation,
 ) -> FileReadResult {
     let file_path = workdir.join(path);
+    let empty = FileReadResult {
+        path: path.to_string(),
+        exists: file_path.exists(),
+        lines: None,
+        bytes: None,
+        truncated: None,
+        start_line: None,
+        end_line: None,
+        content: None,
+        content_with_line_numbers: None,
+        error: None,
+        view: None,
+        tokens: None,
+        outline: None,
+        metadata: None,
+    };
 
-    if !file_path.exists() {
+    if !empty.exists {
+        return empty;
+    }
+    let bytes = match fs::read(&file_path) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            return FileReadResult {
+                error: Some(e.to_string()),
+                ..empty
+            }
+        }
+    };
+    let empty = FileReadResult {
+        metadata: Some(FileMetadata::from_bytes(&file_path, path, &bytes)),
+        ..empty
+    };
+    if empty.metadata.as_ref().is_some_and(|m| m.is_binary) {
         return FileReadResult {
-            path: path.to_string(),
-            exists: false,
-            lines: None,
-            bytes: None,
-            truncated: None,
-            content: None,
-            content_with_line_numbers: None,
-            error: None,
+            error: Some("Binary file, not shown".to_string()),
+            ..empty
         };
     }
 
-    match fs::read_to_string(&file_path) {
+    match decode_text(bytes) {
         Ok(content) => {
             let total_lines = content.lines().count();
             let total_bytes = content.len();
             let max = max_lines.unwrap_or(500);
-            let truncated = total_lines > max;
 
-            // Add line numbers
-            let content_with_numbers = add_line_numbers(&content, max);
+            let Some(range) = range else {
+                let truncated = total_lines > max;
 
-            // Also provide raw content (truncated if necessary)
-            let raw_content: String = content.lines().take(max).collect::<Vec<_>>().join("\n");
+                // NOTE(jimmylee): Files over the limit get an outline rather than a
+                // blind head when the language has one
+                if truncated && *truncation != Truncation::Auto && *truncation != Truncation::Head {
+                    let (view, windows) = truncation_windows(&content, total_lines, max, truncation);
+                    return FileReadResult {
+                        lines: Some(total_lines),
+                        bytes: Some(total_bytes),
+                        truncated: Some(true),
+                        view: Some(view),
+                        content_with_line_numbers: Some(add_line_numbers_in_windows(&content, &windows)),
+                        ..empty
+                    };
+                }
+                if truncated && *truncation == Truncation::Auto {
+                    if let Some(view) = outline_view(path, &content, total_lines) {
+                        return FileReadResult {
+                            lines: Some(total_lines),
+                            bytes: Some(total_bytes),
+                            metadata: empty.metadata,
+                            ..view
+                        };
+                    }
+                }
+
+                // Add line numbers
+                let content_with_numbers = add_line_numbers(&content, max);
+
+                // Also provide raw content (truncated if necessary)
+                let raw_content: String = content.lines().take(max).collect::<Vec<_>>().join("\n");
+
+                return FileReadResult {
+                    lines: Some(total_lines),
+                    bytes: Some(total_bytes),
+                    truncated: Some(truncated),
+                    content: Some(raw_content),
+                    content_with_line_numbers: Some(content_with_numbers),
+                    ..empty
+                };
+            };
+
+            if range.start > total_lines.max(1) {
+                return FileReadResult {
+                    lines: Some(total_lines),
+                    bytes: Some(total_bytes),
+                    error: Some(format!(
+                        "Line {} is past the end of the file ({} lines)",
+                        range.start, total_lines
+                    )),
+                    ..empty
+                };
+            }
+
+            let start = range.start;
+            let end = range.end.min(total_lines).min(start.saturating_add(max.max(1)) - 1);
+            let window: Vec<&str> = content.lines().skip(start - 1).take(end + 1 - start).collect();
 
             FileReadResult {
-                path: path.to_string(),
-                exists: true,
                 lines: Some(total_lines),
                 bytes: Some(total_bytes),
-                truncated: Some(truncated),
-                content: Some(raw_content),
-                content_with_line_numbers: Some(content_with_numbers),
-                error: None,
+                truncated: Some(start > 1 || end < total_lines),
+                start_line: Some(start),
+                end_line: Some(end),
+                content: Some(window.join("\n")),
+                content_with_line_numbers: Some(add_line_numbers_in_range(&content, start, end)),
+                ..empty
             }
         }
         Err(e) => FileReadResult {
-            path: path.to_string(),
-            exists: true,
-            lines: None,
-            bytes: None,
-            truncated: None,
-            content: None,
-            content_with_line_numbers: None,
-            error: Some(e.to_string()),
+            error: Some(e),
+            ..empty
         },
     }
 }
@@ -105,7 +395,246 @@ pub fn read_files_with_line_numbers(
         .map(|path| read_file_with_line_numbers(workdir, path, max_lines))
         .collect();
 
-    MultiFileReadResult { files }
+    MultiFileReadResult {
+        files,
+        glob: None,
+        budget: None,
+    }
+}
+
+// NOTE(jimmylee)
+// Reads files given as specs, each with its own optional range.
+pub fn read_specs(
+    workdir: &Path,
+    specs: &[ReadSpec],
+    max_lines: Option<usize>,
+    truncation: &Truncation,
+) -> MultiFileReadResult {
+    let files = specs
+        .iter()
+        .map(|spec| read_file_truncated(workdir, &spec.path, spec.range, max_lines, truncation))
+        .collect();
+
+    MultiFileReadResult {
+        files,
+        glob: None,
+        budget: None,
+    }
+}
+
+// NOTE(jimmylee)
+// Reads a file as an outline of its declarations (see outline.rs).
+// Files in languages without an outline are read normally.
+pub fn read_file_outline(workdir: &Path, path: &str, max_lines: Option<usize>) -> FileReadResult {
+    let file_path = workdir.join(path);
+    let outlined = fs::read(&file_path).ok().and_then(|bytes| {
+        let metadata = FileMetadata::from_bytes(&file_path, path, &bytes);
+        let content = decode_text(bytes).ok().filter(|_| !metadata.is_binary)?;
+        let view = outline_view(path, &content, content.lines().count())?;
+        Some(FileReadResult {
+            metadata: Some(metadata),
+            ..view
+        })
+    });
+    outlined.unwrap_or_else(|| read_file_range(workdir, path, None, max_lines))
+}
+
+// NOTE(jimmylee)
+// Reads files as outlines.
+pub fn read_outlines(workdir: &Path, specs: &[ReadSpec], max_lines: Option<usize>) -> MultiFileReadResult {
+    let files = specs
+        .iter()
+        .map(|spec| match spec.range {
+            Some(range) => read_file_range(workdir, &spec.path, Some(range), max_lines),
+            None => read_file_outline(workdir, &spec.path, max_lines),
+        })
+        .collect();
+
+    MultiFileReadResult {
+        files,
+        glob: None,
+        budget: None,
+    }
+}
+
+// NOTE(jimmylee)
+// The outline view of content, or None if its language has no outline or no
+// declarations were found.
+pub(crate) fn outline_view(path: &str, content: &str, total_lines: usize) -> Option<FileReadResult> {
+    let items = outline(content, path).filter(|items| !items.is_empty())?;
+    Some(FileReadResult {
+        path: path.to_string(),
+        exists: true,
+        lines: Some(total_lines),
+        bytes: Some(content.len()),
+        truncated: Some(true),
+        start_line: None,
+        end_line: None,
+        content: None,
+        content_with_line_numbers: Some(render(&items, total_lines)),
+        error: None,
+        view: Some(View::Outline),
+        tokens: None,
+        outline: Some(items),
+        metadata: None,
+    })
+}
+
+// NOTE(jimmylee)
+// Finds files matching the globs, honoring .gitignore/.ignore and DEFAULT_EXCLUDES.
+// Paths are sorted, binary files skipped, and at most max_files returned.
+pub fn glob_files(workdir: &Path, patterns: &[String], max_files: usize) -> Result<GlobMatches, String> {
+    let mut matches = GlobMatches::default();
+    for path in matching_files(workdir, patterns)? {
+        if is_binary(&workdir.join(&path)) {
+            matches.skipped_binary.push(path);
+        } else {
+            matches.matched += 1;
+            if matches.paths.len() < max_files {
+                matches.paths.push(path);
+            }
+        }
+    }
+    matches.truncated = matches.matched > matches.paths.len();
+    Ok(matches)
+}
+
+// NOTE(jimmylee)
+// Workdir-relative paths of the files matching patterns, sorted. Honors ignore files
+// and DEFAULT_EXCLUDES; binary files are included, hidden ones only for patterns
+// that name a dot segment (".github/**").
+pub(crate) fn matching_files(workdir: &Path, patterns: &[String]) -> Result<Vec<String>, String> {
+    let filter = PathFilter::new(patterns).map_err(|e| match e {
+        EditError::InvalidEdit { reason } => reason,
+        other => other.to_string(),
+    })?;
+    // Excluded directories a pattern explicitly starts in stay walkable
+    let allowed: Vec<&str> = DEFAULT_EXCLUDES
+        .iter()
+        .copied()
+        .filter(|dir| patterns.iter().any(|p| p.trim_start_matches("./").starts_with(&format!("{}/", dir))))
+        .collect();
+
+    let mut paths: Vec<String> = WalkBuilder::new(workdir)
+        .require_git(false)
+        .hidden(!names_hidden(patterns))
+        .filter_entry(move |entry| {
+            let name = entry.file_name().to_string_lossy();
+            let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+            !(is_dir && DEFAULT_EXCLUDES.contains(&name.as_ref()) && !allowed.contains(&name.as_ref()))
+        })
+        .build()
+        .flatten()
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .map(|entry| relative_path(workdir, entry.path()))
+        .filter(|rel_path| filter.matches(rel_path))
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+// NOTE(jimmylee)
+// Bytes at the start of a file inspected when deciding whether it is binary.
+pub const BINARY_SNIFF_BYTES: usize = 8192;
+
+// NOTE(jimmylee)
+// Returns true if the file looks binary (see looks_binary).
+pub fn is_binary(path: &Path) -> bool {
+    let mut head = [0u8; BINARY_SNIFF_BYTES];
+    let read = fs::File::open(path).and_then(|mut f| f.read(&mut head));
+    read.is_ok_and(|n| looks_binary(&head[..n]))
+}
+
+// NOTE(jimmylee)
+// Returns true if bytes look binary: a NUL in the first BINARY_SNIFF_BYTES.
+// UTF-16 text is full of NULs, so a UTF-16 byte order mark rules that out first.
+pub fn looks_binary(bytes: &[u8]) -> bool {
+    if bytes.starts_with(&[0xFF, 0xFE]) || bytes.starts_with(&[0xFE, 0xFF]) {
+        return false;
+    }
+    bytes[..bytes.len().min(BINARY_SNIFF_BYTES)].contains(&0)
+}
+
+// NOTE(jimmylee)
+// Decodes file bytes as text: UTF-16 when they start with its byte order mark,
+// UTF-8 otherwise. A byte order mark is dropped either way, so line 1 reads as it
+// shows in an editor.
+pub fn decode_text(bytes: Vec<u8>) -> Result<String, String> {
+    let units = |unit: fn([u8; 2]) -> u16| -> Vec<u16> { bytes[2..].chunks_exact(2).map(|c| unit([c[0], c[1]])).collect() };
+    let text = if bytes.starts_with(&[0xFF, 0xFE]) {
+        String::from_utf16(&units(u16::from_le_bytes)).map_err(|e| e.to_string())?
+    } else if bytes.starts_with(&[0xFE, 0xFF]) {
+        String::from_utf16(&units(u16::from_be_bytes)).map_err(|e| e.to_string())?
+    } else {
+        String::from_utf8(bytes).map_err(|e| e.to_string())?
+    };
+    Ok(match text.strip_prefix('\u{feff}') {
+        Some(rest) => rest.to_string(),
+        None => text,
+    })
+}
+
+// NOTE(jimmylee)
+// Reads a file as text (see decode_text).
+pub fn read_text(path: &Path) -> Result<String, String> {
+    decode_text(fs::read(path).map_err(|e| e.to_string())?)
+}
+
+// NOTE(jimmylee)
+// The view and line windows (1-indexed, inclusive) that keep at most max of
+// total_lines under a head_tail or relevant truncation.
+fn truncation_windows(
+    content: &str,
+    total_lines: usize,
+    max: usize,
+    truncation: &Truncation,
+) -> (View, Vec<(usize, usize)>) {
+    let head_tail = || {
+        let tail = max / 3;
+        let head = max - tail;
+        let mut windows = vec![(1, head)];
+        if tail > 0 {
+            windows.push((total_lines + 1 - tail, total_lines));
+        }
+        (View::HeadTail, windows)
+    };
+    let Truncation::Relevant(keywords) = truncation else { return head_tail() };
+
+    let keywords: Vec<String> = keywords.iter().map(|k| k.to_lowercase()).collect();
+    let matches: Vec<usize> = content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| {
+            let line = line.to_lowercase();
+            keywords.iter().any(|k| line.contains(k))
+        })
+        .map(|(index, _)| index + 1)
+        .collect();
+    if matches.is_empty() {
+        return head_tail();
+    }
+
+    // A quarter of the budget for the head, the rest for match windows in file order
+    let head = (max / 4).max(1);
+    let mut windows = vec![(1, head)];
+    let mut remaining = max - head;
+    for line in matches {
+        let Some(last) = windows.last_mut().filter(|_| remaining > 0) else { break };
+        let start = line.saturating_sub(RELEVANT_CONTEXT).max(1);
+        let end = (line + RELEVANT_CONTEXT).min(total_lines);
+        let start = start.max(last.1 + 1);
+        if start > end {
+            continue;
+        }
+        let end = end.min(start + remaining - 1);
+        remaining -= end + 1 - start;
+        if start == last.1 + 1 {
+            last.1 = end;
+        } else {
+            windows.push((start, end));
+        }
+    }
+    (View::Relevant, windows)
 }
 
 // NOTE(jimmylee)
@@ -148,51 +677,269 @@ fn add_line_numbers(content: &str, max_lines: usize) -> String {
     result
 }
 
+// NOTE(jimmylee)
+// Adds original line numbers to lines start..=end of content.
+// Lines outside the window are replaced by explicit "omitted" markers.
+fn add_line_numbers_in_range(content: &str, start: usize, end: usize) -> String {
+    add_line_numbers_in_windows(content, &[(start, end)])
+}
+
+// NOTE(jimmylee)
+// Adds original line numbers to several windows of content (1-indexed, inclusive,
+// in order, not overlapping). Every gap before, between and after the windows
+// becomes one "... (lines A-B omitted)" marker.
+pub(crate) fn add_line_numbers_in_windows(content: &str, windows: &[(usize, usize)]) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let last = windows.last().map(|w| w.1).unwrap_or(0).min(lines.len());
+    let width = last.to_string().len().max(3);
+    let mut result = String::new();
+    let omit = |result: &mut String, from: usize, to: usize| {
+        if from <= to {
+            result.push_str(&format!("{:>width$} | ... (lines {}-{} omitted)\n", "...", from, to, width = width));
+        }
+    };
+
+    let mut next = 1;
+    for &(start, end) in windows {
+        let end = end.min(lines.len());
+        if start < next || start > end {
+            continue;
+        }
+        omit(&mut result, next, start - 1);
+        for (i, line) in lines.iter().enumerate().take(end).skip(start - 1) {
+            result.push_str(&format!("{:>width$} | {}\n", i + 1, line, width = width));
+        }
+        next = end + 1;
+    }
+    omit(&mut result, next, lines.len());
+
+    result.pop();
+    result
+}
+
+// NOTE(jimmylee)
+// Text layouts for read results, chosen per prompt with --format.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextFormat {
+    // "### path" headings and fenced blocks tagged with the file extension
+    Prompt,
+    // Like Prompt, with fences tagged by language name ("typescript")
+    Markdown,
+    // <file path="..." lines="..."> tags around the content
+    Xml,
+    // "==> path <==" headers and bare content, no markup
+    NumberedPlain,
+}
+
+impl TextFormat {
+    // NOTE(jimmylee)
+    // Parses a --format value; json is handled by the caller.
+    pub fn parse(format: &str) -> Option<TextFormat> {
+        match format {
+            "prompt" => Some(TextFormat::Prompt),
+            "markdown" => Some(TextFormat::Markdown),
+            "xml" => Some(TextFormat::Xml),
+            "numbered-plain" => Some(TextFormat::NumberedPlain),
+            _ => None,
+        }
+    }
+}
+
 // NOTE(jimmylee)
 // Formats file read results as a human-readable string for prompts.
 // Format suitable for embedding in LLM context.
 pub fn format_for_prompt(results: &MultiFileReadResult) -> String {
+    format_files(results, TextFormat::Prompt, true)
+}
+
+// NOTE(jimmylee)
+// Formats file read results in the given layout. Without line numbers the "N | "
+// prefixes are stripped but omitted-line markers stay.
+pub fn format_files(results: &MultiFileReadResult, format: TextFormat, line_numbers: bool) -> String {
     let mut output = String::new();
+    if format == TextFormat::Xml {
+        output.push_str("<files>\n");
+    }
 
     for file in &results.files {
-        if file.exists {
-            let lines_info = file
-                .lines
-                .map(|l| format!("{} lines", l))
-                .unwrap_or_default();
-            let truncated_info = if file.truncated.unwrap_or(false) {
-                " (truncated)"
-            } else {
-                ""
-            };
-
-            output.push_str(&format!(
-                "### {} ({}{})\n\n",
-                file.path, lines_info, truncated_info
-            ));
-
-            if let Some(ref content) = file.content_with_line_numbers {
-                // Detect file extension for syntax highlighting hint
-                let ext = Path::new(&file.path)
-                    .extension()
-                    .and_then(|e| e.to_str())
-                    .unwrap_or("");
-
-                output.push_str(&format!("```{}\n{}\n```\n\n", ext, content));
-            } else if let Some(ref error) = file.error {
-                output.push_str(&format!("*Error reading file: {}*\n\n", error));
+        let content = file.content_with_line_numbers.as_ref().map(|text| {
+            let text = text.trim_end_matches('\n');
+            match line_numbers {
+                true => text.to_string(),
+                false => strip_line_numbers(text),
             }
-        } else {
-            output.push_str(&format!(
-                "### {}\n\n*File does not exist - will be created*\n\n",
-                file.path
-            ));
+        });
+        match format {
+            TextFormat::Prompt | TextFormat::Markdown => markdown_file(&mut output, file, content, format),
+            TextFormat::Xml => xml_file(&mut output, file, content),
+            TextFormat::NumberedPlain => plain_file(&mut output, file, content),
         }
     }
 
+    if format == TextFormat::Xml {
+        output.push_str("</files>\n");
+    }
     output
 }
 
+// "120 lines, showing 10-40, indent: 2 spaces"
+fn summary(file: &FileReadResult) -> String {
+    let lines_info = file
+        .lines
+        .map(|l| format!("{} lines", l))
+        .unwrap_or_default();
+    let truncated_info = match (file.view, file.start_line, file.end_line) {
+        (Some(View::HeadTail), _, _) => " (head and tail only)".to_string(),
+        (Some(View::Relevant), _, _) => " (head and matching sections only)".to_string(),
+        (Some(View::Outline), _, _) => " (outline)".to_string(),
+        (Some(View::Omitted), _, _) => " (omitted)".to_string(),
+        (_, Some(start), Some(end)) => format!(", showing {}-{}", start, end),
+        _ if file.truncated.unwrap_or(false) => " (truncated)".to_string(),
+        _ => String::new(),
+    };
+
+    // NOTE(jimmylee): State the file's conventions so edits can match them
+    let conventions: String = file
+        .metadata
+        .iter()
+        .flat_map(|m| m.conventions())
+        .map(|note| format!(", {}", note))
+        .collect();
+
+    format!("{}{}{}", lines_info, truncated_info, conventions)
+}
+
+fn markdown_file(output: &mut String, file: &FileReadResult, content: Option<String>, format: TextFormat) {
+    if !file.exists {
+        output.push_str(&format!(
+            "### {}\n\n*File does not exist - will be created*\n\n",
+            file.path
+        ));
+        return;
+    }
+
+    output.push_str(&format!("### {} ({})\n\n", file.path, summary(file)));
+    if let Some(content) = content {
+        // Syntax highlighting hint: the extension, or the language name for markdown
+        let ext = Path::new(&file.path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("");
+        let language = file.metadata.as_ref().map(|m| m.language.as_str()).unwrap_or("unknown");
+        let tag = match format {
+            TextFormat::Markdown if language != "unknown" => language,
+            _ => ext,
+        };
+        output.push_str(&format!("```{}\n{}\n```\n\n", tag, content));
+    } else if let Some(ref error) = file.error {
+        output.push_str(&format!("*Error reading file: {}*\n\n", error));
+    } else if file.view == Some(View::Omitted) {
+        output.push_str("*Not shown - over the token budget*\n\n");
+    }
+}
+
+// Content goes inside the tag verbatim, as models read it; content with markup
+// characters is wrapped in CDATA so it can't end the tag or break the document
+fn xml_file(output: &mut String, file: &FileReadResult, content: Option<String>) {
+    let mut attributes = vec![("path", file.path.clone())];
+    if !file.exists {
+        attributes.push(("exists", "false".to_string()));
+    }
+    if let Some(lines) = file.lines {
+        attributes.push(("lines", lines.to_string()));
+    }
+    if let (Some(start), Some(end)) = (file.start_line, file.end_line) {
+        attributes.push(("range", format!("{}-{}", start, end)));
+    }
+    if let Some(view) = file.view.filter(|v| *v != View::Full) {
+        let name = match view {
+            View::HeadTail => "head_tail",
+            View::Relevant => "relevant",
+            View::Outline => "outline",
+            View::Omitted => "omitted",
+            View::Full => "full",
+        };
+        attributes.push(("view", name.to_string()));
+    }
+    if let Some(metadata) = &file.metadata {
+        if metadata.language != "unknown" {
+            attributes.push(("language", metadata.language.clone()));
+        }
+        if let Some(indent) = metadata.indent.filter(|i| *i != IndentStyle::Unknown) {
+            attributes.push(("indent", indent.describe()));
+        }
+        match metadata.line_ending {
+            LineEnding::Crlf => attributes.push(("line_endings", "crlf".to_string())),
+            LineEnding::Mixed => attributes.push(("line_endings", "mixed".to_string())),
+            LineEnding::Lf | LineEnding::None => {}
+        }
+    }
+    if let Some(error) = &file.error {
+        attributes.push(("error", error.clone()));
+    }
+
+    let attributes: String = attributes
+        .iter()
+        .map(|(name, value)| format!(" {}=\"{}\"", name, xml_escape(value)))
+        .collect();
+    match content {
+        Some(content) => output.push_str(&format!("<file{}>\n{}\n</file>\n", attributes, xml_text(&content))),
+        None => output.push_str(&format!("<file{} />\n", attributes)),
+    }
+}
+
+// Text as XML element content: verbatim, or as CDATA when it has markup characters
+// (a "]]>" inside is split across two sections)
+fn xml_text(text: &str) -> String {
+    if text.contains(['<', '&']) || text.contains("]]>") {
+        format!("<![CDATA[{}]]>", text.replace("]]>", "]]]]><![CDATA[>"))
+    } else {
+        text.to_string()
+    }
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn plain_file(output: &mut String, file: &FileReadResult, content: Option<String>) {
+    if !file.exists {
+        output.push_str(&format!("==> {} <==\n(does not exist - will be created)\n\n", file.path));
+        return;
+    }
+
+    output.push_str(&format!("==> {} ({}) <==\n", file.path, summary(file)));
+    if let Some(content) = content {
+        output.push_str(&format!("{}\n\n", content));
+    } else if let Some(ref error) = file.error {
+        output.push_str(&format!("(error reading file: {})\n\n", error));
+    } else if file.view == Some(View::Omitted) {
+        output.push_str("(not shown - over the token budget)\n\n");
+    }
+}
+
+// NOTE(jimmylee)
+// Removes the "  12 | " prefixes add_line_numbers and outlines write. Marker lines
+// keep their text ("... (lines 4-56 omitted)").
+fn strip_line_numbers(text: &str) -> String {
+    text.lines()
+        .map(|line| match line.split_once(" | ") {
+            Some((prefix, rest))
+                if !prefix.trim().is_empty()
+                    && prefix.trim().chars().all(|c| c.is_ascii_digit() || c == '-' || c == '.') =>
+            {
+                rest
+            }
+            _ => line,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -252,6 +999,223 @@ mod tests {
         assert!(result.content.is_none());
     }
 
+    #[test]
+    fn test_read_range_keeps_line_numbers() {
+        let dir = tempdir().unwrap();
+        let content = (1..=3000).map(|i| format!("line{}", i)).collect::<Vec<_>>().join("\n");
+        fs::write(dir.path().join("big.ts"), content).unwrap();
+
+        let range = LineRange::parse("2380-2382").unwrap();
+        let result = read_file_range(dir.path(), "big.ts", Some(range), Some(500));
+        assert_eq!((result.start_line, result.end_line, result.truncated), (Some(2380), Some(2382), Some(true)));
+        assert_eq!(result.content.as_deref(), Some("line2380\nline2381\nline2382"));
+        assert_eq!(
+            result.content_with_line_numbers.unwrap(),
+            " ... | ... (lines 1-2379 omitted)\n2380 | line2380\n2381 | line2381\n2382 | line2382\n \
+             ... | ... (lines 2383-3000 omitted)"
+        );
+
+        let around = LineRange::around("2:5").unwrap();
+        let result = read_file_range(dir.path(), "big.ts", Some(around), Some(3));
+        assert_eq!((result.start_line, result.end_line), (Some(1), Some(3)));
+
+        let past = read_file_range(dir.path(), "big.ts", LineRange::parse("4000-").ok(), None);
+        assert!(past.error.unwrap().contains("past the end"));
+    }
+
+    #[test]
+    fn test_parse_specs() {
+        let spec = ReadSpec::parse("src/app.ts:100-180");
+        assert_eq!((spec.path.as_str(), spec.range), ("src/app.ts", Some(LineRange { start: 100, end: 180 })));
+        assert_eq!(ReadSpec::parse("notes:todo.md").range, None);
+        assert_eq!(ReadSpec::parse("src/app.ts").path, "src/app.ts");
+        assert!(LineRange::parse("180-100").is_err());
+        assert_eq!(LineRange::around("10:30").unwrap(), LineRange { start: 1, end: 40 });
+    }
+
+    #[test]
+    fn test_outline_replaces_head_truncation() {
+        let dir = tempdir().unwrap();
+        let mut content = String::from("export function first() {\n");
+        content.push_str(&"  work();\n".repeat(600));
+        content.push_str("}\n\nexport const Last = () => <div />;\n");
+        fs::write(dir.path().join("big.tsx"), &content).unwrap();
+        fs::write(dir.path().join("big.txt"), &content).unwrap();
+
+        let result = read_file_with_line_numbers(dir.path(), "big.tsx", Some(500));
+        assert_eq!((result.view, result.truncated), (Some(View::Outline), Some(true)));
+        let text = result.content_with_line_numbers.unwrap();
+        assert!(text.contains("\n1-602   | export function first()\n604-604 | export const Last"));
+
+        let plain = read_file_with_line_numbers(dir.path(), "big.txt", Some(500));
+        assert_eq!(plain.view, None);
+        assert!(plain.content_with_line_numbers.unwrap().contains("(104 more lines)"));
+
+        let small = read_file_outline(dir.path(), "big.tsx", Some(5000));
+        assert_eq!(small.outline.map(|o| o.len()), Some(2));
+    }
+
+    #[test]
+    fn test_truncation_strategies() {
+        let dir = tempdir().unwrap();
+        let content: String = (1..=100)
+            .map(|i| match i {
+                60 => "export function Widget() {\n".to_string(),
+                62 => "  return widget;\n".to_string(),
+                _ => format!("line {}\n", i),
+            })
+            .collect();
+        fs::write(dir.path().join("big.txt"), &content).unwrap();
+        let read = |truncation: &Truncation| read_file_truncated(dir.path(), "big.txt", None, Some(12), truncation);
+
+        let relevant = read(&Truncation::parse("relevant", &["WIDGET".to_string()]).unwrap());
+        assert_eq!((relevant.view, relevant.truncated, relevant.content), (Some(View::Relevant), Some(true), None));
+        let text = relevant.content_with_line_numbers.unwrap();
+        assert!(text.starts_with("  1 | line 1\n  2 | line 2\n  3 | line 3\n... | ... (lines 4-56 omitted)\n 57 | line 57\n"));
+        assert!(text.ends_with(" 65 | line 65\n... | ... (lines 66-100 omitted)"));
+
+        let head_tail = read(&Truncation::HeadTail).content_with_line_numbers.unwrap();
+        assert!(head_tail.contains("  8 | line 8\n... | ... (lines 9-96 omitted)\n 97 | line 97\n"));
+
+        // Nothing matches: head and tail instead
+        let unmatched = read(&Truncation::Relevant(vec!["missing".to_string()]));
+        assert_eq!(unmatched.view, Some(View::HeadTail));
+        assert_eq!(read(&Truncation::Head).view, None);
+
+        assert!(Truncation::parse("relevant", &[]).is_err());
+        assert!(Truncation::parse("middle", &[]).is_err());
+    }
+
+    #[test]
+    fn test_glob_files() {
+        let dir = tempdir().unwrap();
+        for path in ["src/b.ts", "src/a.ts", "node_modules/x/i.ts", "dist/out.ts", "gen/skip.ts"] {
+            fs::create_dir_all(dir.path().join(path).parent().unwrap()).unwrap();
+            fs::write(dir.path().join(path), "export {}\n").unwrap();
+        }
+        fs::write(dir.path().join("src/logo.ts"), b"\x89PNG\0\0").unwrap();
+        fs::write(dir.path().join(".ignore"), "gen/\n").unwrap();
+
+        let found = glob_files(dir.path(), &["*.ts".to_string()], 10).unwrap();
+        assert_eq!(found.paths, vec!["src/a.ts", "src/b.ts"]);
+        assert_eq!(found.skipped_binary, vec!["src/logo.ts"]);
+        assert!(!found.truncated);
+
+        let found = glob_files(dir.path(), &["*.ts".to_string()], 1).unwrap();
+        assert_eq!((found.paths.len(), found.matched, found.truncated), (1, 2, true));
+
+        let found = glob_files(dir.path(), &["dist/**".to_string()], 10).unwrap();
+        assert_eq!(found.paths, vec!["dist/out.ts"]);
+    }
+
+    #[test]
+    fn test_read_reports_metadata() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("main.go"), "package main\n\nfunc main() {\n\tprintln(1)\n}\n").unwrap();
+        fs::write(dir.path().join("logo.png"), b"\x89PNG\0").unwrap();
+
+        let results = read_files_with_line_numbers(dir.path(), &["main.go".to_string(), "logo.png".to_string()], None);
+        let metadata = results.files[0].metadata.as_ref().unwrap();
+        assert_eq!((metadata.language.as_str(), metadata.indent), ("go", Some(IndentStyle::Tabs)));
+        assert_eq!(metadata.hash, crate::metadata::content_hash(&fs::read(dir.path().join("main.go")).unwrap()));
+        assert!(format_for_prompt(&results).contains("### main.go (5 lines, indent: tabs)"));
+
+        let binary = &results.files[1];
+        assert!(binary.metadata.as_ref().unwrap().is_binary);
+        assert_eq!(binary.error.as_deref(), Some("Binary file, not shown"));
+    }
+
+    #[test]
+    fn test_read_utf16_as_text() {
+        let dir = tempdir().unwrap();
+        let text = "[core]\r\n\tname = \"a\"\r\n";
+        let le: Vec<u8> = [0xFF, 0xFE].into_iter().chain(text.encode_utf16().flat_map(u16::to_le_bytes)).collect();
+        let be: Vec<u8> = [0xFE, 0xFF].into_iter().chain(text.encode_utf16().flat_map(u16::to_be_bytes)).collect();
+        fs::write(dir.path().join("le.ini"), &le).unwrap();
+        fs::write(dir.path().join("be.ini"), &be).unwrap();
+        assert!(!is_binary(&dir.path().join("le.ini")));
+
+        let results = read_files_with_line_numbers(dir.path(), &["le.ini".to_string(), "be.ini".to_string()], None);
+        for file in &results.files {
+            assert_eq!((file.error.as_deref(), file.lines), (None, Some(2)));
+            assert_eq!(file.content.as_deref(), Some("[core]\n\tname = \"a\""));
+            assert!(!file.metadata.as_ref().unwrap().is_binary);
+        }
+        let metadata = results.files[0].metadata.as_ref().unwrap();
+        assert_eq!((metadata.encoding, metadata.indent), (crate::metadata::Encoding::Utf16Le, Some(IndentStyle::Tabs)));
+    }
+
+    #[test]
+    fn test_read_drops_utf8_bom() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("app.ts"), "\u{feff}import a from 'a';\nrun(a);\n").unwrap();
+
+        let file = read_file_range(dir.path(), "app.ts", None, None);
+        assert_eq!(file.content.as_deref(), Some("import a from 'a';\nrun(a);"));
+        assert!(file.content_with_line_numbers.unwrap().starts_with("1 | import a"));
+        assert_eq!(file.metadata.unwrap().encoding, crate::metadata::Encoding::Utf8Bom);
+        assert_eq!(read_text(&dir.path().join("app.ts")).unwrap(), "import a from 'a';\nrun(a);\n");
+    }
+
+    #[test]
+    fn test_xml_content_round_trips() {
+        let dir = tempdir().unwrap();
+        let content = "<file path=\"x\">\n</file>\nif a < b && c ]]> d {}";
+        fs::write(dir.path().join("tricky.md"), content).unwrap();
+        fs::write(dir.path().join("plain.txt"), "no markup").unwrap();
+        let specs = [ReadSpec::parse("tricky.md"), ReadSpec::parse("plain.txt")];
+        let results = read_specs(dir.path(), &specs, None, &Truncation::Auto);
+        let xml = format_files(&results, TextFormat::Xml, false);
+
+        // What an XML parser reads as each file element's text
+        let mut texts = Vec::new();
+        let mut rest = xml.as_str();
+        while let Some(start) = rest.find("<file ") {
+            let (_, mut body) = rest[start..].split_once(">\n").unwrap();
+            let mut text = String::new();
+            while let Some(cdata) = body.strip_prefix("<![CDATA[") {
+                let (section, after) = cdata.split_once("]]>").unwrap();
+                text.push_str(section);
+                body = after;
+            }
+            let (plain, after) = body.split_once("\n</file>\n").unwrap();
+            text.push_str(plain);
+            texts.push(text);
+            rest = after;
+        }
+        assert_eq!(texts, vec![content.to_string(), "no markup".to_string()]);
+        assert!(xml.contains("<file path=\"plain.txt\" lines=\"1\">\nno markup\n</file>"));
+    }
+
+    #[test]
+    fn test_text_formats() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("app.ts"), "const a = 1;\nif (a) {\n  run(\"<x>\");\n}\n").unwrap();
+        let specs = [ReadSpec::parse("app.ts"), ReadSpec::parse("app.ts:2-3"), ReadSpec::parse("new.ts")];
+        let results = read_specs(dir.path(), &specs, None, &Truncation::Auto);
+
+        let xml = format_files(&results, TextFormat::Xml, true);
+        assert_eq!(
+            xml,
+            "<files>\n\
+             <file path=\"app.ts\" lines=\"4\" language=\"typescript\" indent=\"2 spaces\">\n\
+             <![CDATA[1 | const a = 1;\n2 | if (a) {\n3 |   run(\"<x>\");\n4 | }]]>\n</file>\n\
+             <file path=\"app.ts\" lines=\"4\" range=\"2-3\" language=\"typescript\" indent=\"2 spaces\">\n\
+             <![CDATA[... | ... (lines 1-1 omitted)\n  2 | if (a) {\n  3 |   run(\"<x>\");\n... | ... (lines 4-4 omitted)]]>\n</file>\n\
+             <file path=\"new.ts\" exists=\"false\" />\n\
+             </files>\n"
+        );
+
+        let markdown = format_files(&results, TextFormat::Markdown, false);
+        assert!(markdown.starts_with("### app.ts (4 lines, indent: 2 spaces)\n\n```typescript\nconst a = 1;\nif (a) {\n  run"));
+        assert!(markdown.contains("```typescript\n... (lines 1-1 omitted)\nif (a) {\n"));
+
+        let plain = format_files(&results, TextFormat::NumberedPlain, true);
+        assert!(plain.starts_with("==> app.ts (4 lines, indent: 2 spaces) <==\n1 | const a = 1;\n"));
+        assert!(plain.ends_with("==> new.ts <==\n(does not exist - will be created)\n\n"));
+        assert_eq!(TextFormat::parse("html"), None);
+    }
+
     #[test]
     fn test_format_for_prompt() {
         let results = MultiFileReadResult {
@@ -262,9 +1226,15 @@ mod tests {
                     lines: Some(10),
                     bytes: Some(200),
                     truncated: Some(false),
+                    start_line: None,
+                    end_line: None,
                     content: Some("fn main() {}".to_string()),
                     content_with_line_numbers: Some("1 | fn main() {}".to_string()),
                     error: None,
+                    view: None,
+                    tokens: None,
+                    outline: None,
+                    metadata: None,
                 },
                 FileReadResult {
                     path: "new_file.rs".to_string(),
@@ -272,11 +1242,19 @@ mod tests {
                     lines: None,
                     bytes: None,
                     truncated: None,
+                    start_line: None,
+                    end_line: None,
                     content: None,
                     content_with_line_numbers: None,
                     error: None,
+                    view: None,
+                    tokens: None,
+                    outline: None,
+                    metadata: None,
                 },
             ],
+            glob: None,
+            budget: None,
         };
 
         let output = format_for_prompt(&results);
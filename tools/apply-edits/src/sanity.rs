@@ -0,0 +1,305 @@
+// NOTE(angeldev)
+// Post-edit syntax sanity checks.
+// A cheap per-language pass run on every file a request touched: bracket balance
+// (ignoring strings and comments) for brace languages, a real parse for
+// JSON/TOML/YAML, and indentation consistency for Python. JSON files that allow
+// comments (tsconfig.json, .vscode/settings.json) only get the bracket check.
+// Only regressions count: a file that was already unbalanced before the request
+// is not reported.
+
+use crate::error::EditError;
+use crate::language::Language;
+use crate::scanner::{brackets_match, code_mask, line_of_offset, line_starts};
+use serde::Deserialize;
+
+// NOTE(angeldev)
+// The first problem found in a file (1-indexed position).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxIssue {
+    pub line: usize,
+    pub column: usize,
+    pub reason: String,
+}
+
+// NOTE(angeldev)
+// Checks content for the language of path. Returns None if it looks sound or the
+// language has no check.
+pub fn check(content: &str, path: &str) -> Option<SyntaxIssue> {
+    let language = Language::from_path(path);
+    match language {
+        // JavaScript's comment and string rules cover JSONC
+        Language::Json if is_jsonc(path) => check_brackets(content, Language::JavaScript),
+        Language::Json => check_json(content),
+        Language::Toml => check_toml(content),
+        Language::Yaml => check_yaml(content),
+        Language::Python => check_brackets(content, language).or_else(|| check_python_indent(content)),
+        Language::JavaScript
+        | Language::TypeScript
+        | Language::Rust
+        | Language::Go
+        | Language::Java
+        | Language::Kotlin
+        | Language::C
+        | Language::Cpp
+        | Language::CSharp
+        | Language::Php
+        | Language::Css => check_brackets(content, language),
+        _ => None,
+    }
+}
+
+// NOTE(angeldev)
+// Returns a syntax_regression error if `after` has a problem that `before` didn't.
+// A file without a `before` (created by the request) is held to the same standard.
+pub fn find_regression(path: &str, before: Option<&str>, after: &str) -> Option<EditError> {
+    if before.is_some_and(|b| check(b, path).is_some()) {
+        return None;
+    }
+    let issue = check(after, path)?;
+    Some(EditError::SyntaxRegression {
+        path: path.to_string(),
+        line: issue.line,
+        column: issue.column,
+        reason: issue.reason,
+    })
+}
+
+// NOTE(angeldev)
+// JSON files whose tools accept comments and trailing commas.
+fn is_jsonc(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    ["tsconfig", "jsconfig"].iter().any(|prefix| name.starts_with(prefix))
+        || matches!(name, "devcontainer.json" | ".devcontainer.json")
+        || path.starts_with(".vscode/")
+        || path.contains("/.vscode/")
+}
+
+// Position of a byte offset as (line, column), both 1-indexed
+fn position(content: &str, starts: &[usize], offset: usize) -> (usize, usize) {
+    let line = line_of_offset(starts, offset).max(1);
+    let line_start = starts.get(line - 1).copied().unwrap_or(0);
+    let column = content[line_start..offset.min(content.len())].chars().count() + 1;
+    (line, column)
+}
+
+fn issue_at(content: &str, starts: &[usize], offset: usize, reason: String) -> SyntaxIssue {
+    let (line, column) = position(content, starts, offset);
+    SyntaxIssue { line, column, reason }
+}
+
+// Finds the first unmatched closer, or else the innermost unclosed opener
+fn check_brackets(content: &str, language: Language) -> Option<SyntaxIssue> {
+    let mask = code_mask(content, language);
+    let starts = line_starts(content);
+    let mut stack: Vec<(u8, usize)> = Vec::new();
+
+    for (i, &b) in content.as_bytes().iter().enumerate() {
+        if !mask[i] {
+            continue;
+        }
+        match b {
+            b'{' | b'(' | b'[' => stack.push((b, i)),
+            b'}' | b')' | b']' => match stack.pop() {
+                Some((open, _)) if brackets_match(open, b) => {}
+                Some((open, at)) => {
+                    let (line, _) = position(content, &starts, at);
+                    let reason = format!("'{}' does not close '{}' opened at line {}", b as char, open as char, line);
+                    return Some(issue_at(content, &starts, i, reason));
+                }
+                None => return Some(issue_at(content, &starts, i, format!("unmatched '{}'", b as char))),
+            },
+            _ => {}
+        }
+    }
+
+    let (open, at) = stack.pop()?;
+    Some(issue_at(content, &starts, at, format!("'{}' is never closed", open as char)))
+}
+
+fn check_json(content: &str) -> Option<SyntaxIssue> {
+    if content.trim().is_empty() {
+        return None;
+    }
+    let err = serde_json::from_str::<serde_json::Value>(content).err()?;
+    Some(SyntaxIssue {
+        line: err.line().max(1),
+        column: err.column().max(1),
+        reason: err.to_string(),
+    })
+}
+
+fn check_toml(content: &str) -> Option<SyntaxIssue> {
+    let err = content.parse::<toml_edit::DocumentMut>().err()?;
+    let starts = line_starts(content);
+    let offset = err.span().map(|s| s.start).unwrap_or(0);
+    let reason = err.message().trim().to_string();
+    Some(issue_at(content, &starts, offset, reason))
+}
+
+fn check_yaml(content: &str) -> Option<SyntaxIssue> {
+    // Multi-document files parse one document at a time
+    for document in serde_yaml::Deserializer::from_str(content) {
+        if let Err(err) = serde_yaml::Value::deserialize(document) {
+            let (line, column) = err.location().map(|l| (l.line(), l.column())).unwrap_or((1, 1));
+            return Some(SyntaxIssue { line, column, reason: err.to_string() });
+        }
+    }
+    None
+}
+
+// NOTE(angeldev)
+// Python indentation: no line may indent with both tabs and spaces, the file must
+// not switch between them, a line ending in ':' must be followed by a deeper one,
+// no other line may indent deeper than the one before it, and a dedent must return
+// to an enclosing level. Lines continuing an open bracket or a trailing backslash
+// are free to indent as they like.
+fn check_python_indent(content: &str) -> Option<SyntaxIssue> {
+    let mask = code_mask(content, Language::Python);
+    let starts = line_starts(content);
+    let mut style: Option<char> = None;
+    let mut opener: Option<(usize, usize)> = None; // (line, indent) of a line ending in ':'
+    let mut levels: Vec<usize> = vec![0];
+    let mut depth = 0usize;
+    let mut continued = false;
+    let mut statement_width = 0; // indent of the line the current statement started on
+
+    for (idx, line) in content.lines().enumerate() {
+        let start = starts.get(idx).copied().unwrap_or(0);
+        let is_code = |i: usize| mask.get(start + i).copied().unwrap_or(true);
+        let trimmed = line.trim_start();
+        // Skip blank lines, comments and lines inside multi-line strings
+        if trimmed.is_empty() || trimmed.starts_with('#') || !is_code(0) {
+            continue;
+        }
+
+        let indent = &line[..line.len() - trimmed.len()];
+        let width = indent.chars().map(|c| if c == '\t' { 8 } else { 1 }).sum::<usize>();
+        if indent.contains(' ') && indent.contains('\t') {
+            return Some(issue_at(content, &starts, start, "indentation mixes tabs and spaces".to_string()));
+        }
+        if let Some(c) = indent.chars().next() {
+            match style {
+                Some(s) if s != c => {
+                    let reason = format!("indented with {} but the file uses {}", name(c), name(s));
+                    return Some(issue_at(content, &starts, start, reason));
+                }
+                _ => style = Some(c),
+            }
+        }
+
+        if depth == 0 && !continued {
+            statement_width = width;
+            let outer = levels.last().copied().unwrap_or(0);
+            if let Some((line_number, opened)) = opener.take() {
+                if width <= opened {
+                    let reason = format!("expected an indented block after line {}", line_number);
+                    return Some(issue_at(content, &starts, start, reason));
+                }
+                levels.push(width);
+            } else if width > outer {
+                return Some(issue_at(content, &starts, start, "unexpected indent".to_string()));
+            } else if width < outer {
+                while levels.last().is_some_and(|level| *level > width) {
+                    levels.pop();
+                }
+                if levels.last() != Some(&width) {
+                    let reason = "dedent does not match any outer indentation level".to_string();
+                    return Some(issue_at(content, &starts, start, reason));
+                }
+            }
+        }
+
+        for c in line.char_indices().filter(|(i, _)| is_code(*i)).map(|(_, c)| c) {
+            match c {
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+        let code_end = line.char_indices().rev().find(|(i, c)| !c.is_whitespace() && is_code(*i));
+        continued = matches!(code_end, Some((_, '\\')));
+        if depth == 0 && matches!(code_end, Some((_, ':'))) {
+            opener = Some((idx + 1, statement_width));
+        }
+    }
+    None
+}
+
+fn name(c: char) -> &'static str {
+    if c == '\t' {
+        "tabs"
+    } else {
+        "spaces"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_brackets_ignore_strings_and_comments() {
+        let ok = "function f() {\n  const s = \"}\"; // )\n  return [1, (2)];\n}\n";
+        assert_eq!(check(ok, "a.js"), None);
+
+        let unclosed = "function f() {\n  if (x) {\n    go();\n}\n";
+        let issue = check(unclosed, "a.js").unwrap();
+        assert_eq!((issue.line, issue.column), (1, 14));
+        assert_eq!(issue.reason, "'{' is never closed");
+
+        let wrong = "fn main() {\n    let v = vec![1, 2);\n}\n";
+        let issue = check(wrong, "main.rs").unwrap();
+        assert_eq!((issue.line, issue.column), (2, 22));
+    }
+
+    #[test]
+    fn test_structured_and_python_checks() {
+        assert!(check("{\"a\": 1}\n", "x.json").is_none());
+        assert_eq!(check("{\"a\": 1,\n}\n", "x.json").unwrap().line, 2);
+        assert!(check("[a]\nb = 1\n", "x.toml").is_none());
+        assert!(check("[a]\nb = \n", "x.toml").is_some());
+        assert!(check("a:\n  - 1\n", "x.yaml").is_none());
+        assert!(check("a: [1, 2\n", "x.yaml").is_some());
+
+        assert!(check("def f():\n    \"\"\"x:\n\"\"\"\n    return 1\n", "a.py").is_none());
+        let issue = check("def f():\nreturn 1\n", "a.py").unwrap();
+        assert_eq!(issue.reason, "expected an indented block after line 1");
+        let issue = check("if x:\n    a()\n\tb()\n", "a.py").unwrap();
+        assert_eq!(issue.line, 3);
+    }
+
+    #[test]
+    fn test_python_unexpected_indent() {
+        let issue = check("import os\n    x = 1\n", "a.py").unwrap();
+        assert_eq!((issue.line, issue.reason.as_str()), (2, "unexpected indent"));
+        let issue = check("def f():\n    a = 1\n        b = 2\n", "a.py").unwrap();
+        assert_eq!((issue.line, issue.reason.as_str()), (3, "unexpected indent"));
+        let issue = check("if x:\n        a()\n    b()\n", "a.py").unwrap();
+        assert_eq!((issue.line, issue.reason.as_str()), (3, "dedent does not match any outer indentation level"));
+
+        // Bracket and backslash continuations, and closing back out of nested blocks
+        let ok = "def f(a,\n        b):\n    x = {\n        'k': 1,\n    }\n    y = a + \\\n            b\n    if y:\n        for i in x:\n            pass\n    return y\n\nf(1, 2)\n";
+        assert!(check(ok, "a.py").is_none(), "{:?}", check(ok, "a.py"));
+    }
+
+    #[test]
+    fn test_jsonc_files_allow_comments() {
+        let tsconfig = "{\n  // Strict mode\n  \"compilerOptions\": { \"strict\": true, },\n}\n";
+        assert!(find_regression("tsconfig.json", None, tsconfig).is_none());
+        assert!(find_regression("web/tsconfig.build.json", None, tsconfig).is_none());
+        assert!(find_regression(".vscode/settings.json", None, "{ /* editor */ \"a\": 1 }").is_none());
+        assert!(find_regression("package.json", None, tsconfig).is_some());
+
+        let issue = check("{\n  // }\n  \"a\": [1\n}\n", ".devcontainer/devcontainer.json").unwrap();
+        assert_eq!(issue.line, 4);
+    }
+
+    #[test]
+    fn test_only_regressions_are_reported() {
+        let broken = "{\n";
+        assert!(find_regression("a.ts", Some(broken), "{\n{\n").is_none());
+        let err = find_regression("a.ts", Some("{}\n"), broken).unwrap();
+        assert!(matches!(err, EditError::SyntaxRegression { line: 1, column: 1, .. }));
+        assert!(find_regression("README.md", None, "(").is_none());
+    }
+}
@@ -0,0 +1,240 @@
+// NOTE(angeldev)
+// Lightweight lexical scanner.
+// Marks which bytes of a file are code and which are inside strings or comments,
+// so bracket matching and header lookups are not fooled by text like "{" in a string.
+// This is deliberately not a parser: it only knows each language's comment and
+// string delimiters (see language.rs).
+
+use crate::language::Language;
+
+// NOTE(angeldev)
+// Returns one flag per byte of content: true for code, false for bytes inside
+// strings and comments (delimiters included).
+pub fn code_mask(content: &str, language: Language) -> Vec<bool> {
+    let syntax = language.syntax();
+    let bytes = content.as_bytes();
+    let len = bytes.len();
+    let mut mask = vec![true; len];
+    let mut i = 0;
+
+    while i < len {
+        let rest = &bytes[i..];
+
+        // Line comments run to the end of the line
+        if let Some(prefix) = syntax
+            .line_comments
+            .iter()
+            .find(|p| rest.starts_with(p.as_bytes()))
+        {
+            // "#" only starts a comment at line start or after whitespace ($#, url#frag)
+            let hash_ok = *prefix != "#" || i == 0 || bytes[i - 1].is_ascii_whitespace();
+            if hash_ok {
+                let end = find_byte(bytes, i, b'\n').unwrap_or(len);
+                mark(&mut mask, i, end);
+                i = end;
+                continue;
+            }
+        }
+
+        // Block comments run to the closing delimiter
+        if let Some((open, close)) = syntax.block_comment {
+            if rest.starts_with(open.as_bytes()) {
+                let end = find_seq(bytes, i + open.len(), close.as_bytes())
+                    .map(|p| p + close.len())
+                    .unwrap_or(len);
+                mark(&mut mask, i, end);
+                i = end;
+                continue;
+            }
+        }
+
+        // Python triple-quoted strings
+        if syntax.triple_quotes && (rest.starts_with(b"\"\"\"") || rest.starts_with(b"'''")) {
+            let end = find_seq(bytes, i + 3, &rest[..3])
+                .map(|p| p + 3)
+                .unwrap_or(len);
+            mark(&mut mask, i, end);
+            i = end;
+            continue;
+        }
+
+        // Rust raw strings: r"..." and r#"..."#
+        if syntax.raw_strings && bytes[i] == b'r' && (i == 0 || !is_ident_byte(bytes[i - 1])) {
+            let hashes = rest[1..].iter().take_while(|b| **b == b'#').count();
+            if rest.get(1 + hashes) == Some(&b'"') {
+                let mut terminator = vec![b'"'];
+                terminator.extend(std::iter::repeat_n(b'#', hashes));
+                let end = find_seq(bytes, i + 2 + hashes, &terminator)
+                    .map(|p| p + terminator.len())
+                    .unwrap_or(len);
+                mark(&mut mask, i, end);
+                i = end;
+                continue;
+            }
+        }
+
+        // Rust char literals ('a', '\n') as opposed to lifetimes ('a)
+        if syntax.char_literals && bytes[i] == b'\'' {
+            if let Some(end) = char_literal_end(content, i) {
+                mark(&mut mask, i, end);
+                i = end;
+                continue;
+            }
+            i += 1;
+            continue;
+        }
+
+        // Ordinary quoted strings with backslash escapes
+        if syntax.quotes.iter().any(|q| *q as u32 == bytes[i] as u32) {
+            let quote = bytes[i];
+            let multiline = syntax.multiline_strings || quote == b'`';
+            let end = string_end(bytes, i + 1, quote, multiline);
+            mark(&mut mask, i, end);
+            i = end;
+            continue;
+        }
+
+        i += 1;
+    }
+
+    mask
+}
+
+// NOTE(angeldev)
+// Returns the byte offset at which each line starts.
+pub fn line_starts(content: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(content.match_indices('\n').map(|(pos, _)| pos + 1))
+        .filter(|pos| *pos < content.len() || *pos == 0)
+        .collect()
+}
+
+// NOTE(angeldev)
+// Returns the 1-indexed line containing the byte offset.
+pub fn line_of_offset(starts: &[usize], offset: usize) -> usize {
+    match starts.binary_search(&offset) {
+        Ok(idx) => idx + 1,
+        Err(idx) => idx,
+    }
+}
+
+// NOTE(angeldev)
+// Returns true for the bracket pairs the scanner balances.
+pub fn brackets_match(open: u8, close: u8) -> bool {
+    matches!((open, close), (b'{', b'}') | (b'(', b')') | (b'[', b']'))
+}
+
+fn mark(mask: &mut [bool], start: usize, end: usize) {
+    for flag in &mut mask[start..end] {
+        *flag = false;
+    }
+}
+
+fn find_byte(bytes: &[u8], from: usize, needle: u8) -> Option<usize> {
+    bytes[from.min(bytes.len())..]
+        .iter()
+        .position(|b| *b == needle)
+        .map(|p| p + from)
+}
+
+fn find_seq(bytes: &[u8], from: usize, needle: &[u8]) -> Option<usize> {
+    if from > bytes.len() {
+        return None;
+    }
+    bytes[from..]
+        .windows(needle.len())
+        .position(|w| w == needle)
+        .map(|p| p + from)
+}
+
+fn is_ident_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_'
+}
+
+// NOTE(angeldev)
+// Finds the end (exclusive) of a quoted string starting after the opening quote.
+// Single-line strings stop at an unescaped newline so a stray apostrophe in prose
+// cannot swallow the rest of the file.
+fn string_end(bytes: &[u8], from: usize, quote: u8, multiline: bool) -> usize {
+    let mut i = from;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'\n' if !multiline => return i,
+            b if b == quote => return i + 1,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+// NOTE(angeldev)
+// Returns the end (exclusive) of a Rust char literal at start, or None for a lifetime.
+fn char_literal_end(content: &str, start: usize) -> Option<usize> {
+    let rest = &content[start + 1..];
+    let mut chars = rest.char_indices();
+
+    match chars.next()? {
+        (_, '\\') => {
+            // Escapes: '\n', '\'', '\u{1F600}'
+            let close = rest.get(2..)?.find('\'')?;
+            if close <= 8 {
+                Some(start + 1 + 2 + close + 1)
+            } else {
+                None
+            }
+        }
+        (_, c) => match chars.next() {
+            Some((pos, '\'')) if c != '\'' => Some(start + 1 + pos + 1),
+            _ => None,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code_only(content: &str, language: Language) -> String {
+        let mask = code_mask(content, language);
+        content
+            .bytes()
+            .zip(mask)
+            .filter(|(_, is_code)| *is_code)
+            .map(|(b, _)| b as char)
+            .collect()
+    }
+
+    #[test]
+    fn test_skips_strings_and_comments() {
+        let js = "let a = \"{\"; // }\nlet b = `x\n}`; /* { */ f(1);";
+        assert_eq!(code_only(js, Language::JavaScript), "let a = ; \nlet b = ;  f(1);");
+    }
+
+    #[test]
+    fn test_rust_lifetimes_and_chars() {
+        let rs = "fn f<'a>(x: &'a str) -> char { '{' }";
+        assert_eq!(code_only(rs, Language::Rust), "fn f<'a>(x: &'a str) -> char {  }");
+
+        let escaped = "let q = '\\''; let n = '\\n'; {}";
+        assert_eq!(code_only(escaped, Language::Rust), "let q = ; let n = ; {}");
+
+        let raw = "let s = r#\"}\"#; {}";
+        assert_eq!(code_only(raw, Language::Rust), "let s = ; {}");
+    }
+
+    #[test]
+    fn test_python_triple_quotes() {
+        let py = "def f():\n    \"\"\"doc (\n\"\"\"\n    return 1  # )";
+        assert_eq!(code_only(py, Language::Python), "def f():\n    \n    return 1  ");
+    }
+
+    #[test]
+    fn test_line_of_offset() {
+        let starts = line_starts("a\nbc\nd");
+        assert_eq!(starts, vec![0, 2, 5]);
+        assert_eq!(line_of_offset(&starts, 0), 1);
+        assert_eq!(line_of_offset(&starts, 3), 2);
+        assert_eq!(line_of_offset(&starts, 5), 3);
+    }
+}
@@ -0,0 +1,276 @@
+// NOTE(jimmylee)
+// Repository search for the `search` subcommand.
+// Finds lines matching a literal (or, with --regex, a pattern) across the files
+// selected by --glob, skipping ignored, excluded and binary files like `read --glob`.
+// Hits are grouped per file and shown with --context lines around them, numbered
+// the same way `read` numbers lines, so the Engineer can cite real call sites.
+
+use crate::read::{add_line_numbers_in_windows, is_binary, matching_files, read_text};
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+use std::path::Path;
+
+// NOTE(jimmylee)
+// Matching lines returned before the search stops.
+pub const DEFAULT_MAX_RESULTS: usize = 100;
+
+// NOTE(jimmylee)
+// Longest hit text kept in JSON; longer lines (minified code) are cut.
+const MAX_HIT_CHARS: usize = 300;
+
+// NOTE(jimmylee)
+// What to search for and where.
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    pub globs: Vec<String>,
+    pub regex: bool,
+    pub ignore_case: bool,
+    pub context: usize,
+    pub max_results: usize,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        SearchOptions {
+            globs: Vec::new(),
+            regex: false,
+            ignore_case: false,
+            context: 0,
+            max_results: DEFAULT_MAX_RESULTS,
+        }
+    }
+}
+
+// NOTE(jimmylee)
+// One matching line (1-indexed line, 1-indexed character column of the first match).
+#[derive(Debug, Serialize)]
+pub struct SearchHit {
+    pub line: usize,
+    pub column: usize,
+    pub text: String,
+}
+
+// NOTE(jimmylee)
+// The hits in one file with their numbered context.
+#[derive(Debug, Serialize)]
+pub struct FileHits {
+    pub path: String,
+    pub lines: usize,
+    pub hits: Vec<SearchHit>,
+    pub content_with_line_numbers: String,
+}
+
+// NOTE(jimmylee)
+// Result of a search.
+#[derive(Debug, Serialize)]
+pub struct SearchResult {
+    pub query: String,
+    pub files_searched: usize,
+    pub matches: usize,
+    pub truncated: bool,
+    pub files: Vec<FileHits>,
+}
+
+// NOTE(jimmylee)
+// Searches the files under workdir for query.
+// Fails on an invalid regex or glob.
+pub fn search(workdir: &Path, query: &str, options: &SearchOptions) -> Result<SearchResult, String> {
+    if query.is_empty() {
+        return Err("Query must not be empty".to_string());
+    }
+    let pattern = if options.regex { query.to_string() } else { regex::escape(query) };
+    let matcher = RegexBuilder::new(&pattern)
+        .case_insensitive(options.ignore_case)
+        .build()
+        .map_err(|e| format!("Invalid regex '{}': {}", query, e))?;
+
+    // Only exclusions (or nothing) means search everything else
+    let mut globs = options.globs.clone();
+    if globs.iter().all(|g| g.starts_with('!')) {
+        globs.insert(0, "**".to_string());
+    }
+
+    let mut result = SearchResult {
+        query: query.to_string(),
+        files_searched: 0,
+        matches: 0,
+        truncated: false,
+        files: Vec::new(),
+    };
+    for path in matching_files(workdir, &globs)? {
+        if result.truncated {
+            break;
+        }
+        let full_path = workdir.join(&path);
+        if is_binary(&full_path) {
+            continue;
+        }
+        let Ok(content) = read_text(&full_path) else { continue };
+        result.files_searched += 1;
+
+        // Past the limit, files are only scanned until one more match shows truncation
+        let (hits, more) = find_hits(&matcher, &content, options.max_results - result.matches);
+        result.truncated = more;
+        if !hits.is_empty() {
+            result.matches += hits.len();
+            result.files.push(file_hits(path, &content, hits, options.context));
+        }
+    }
+    Ok(result)
+}
+
+// Up to limit hits in content, and whether more matching lines exist
+fn find_hits(matcher: &Regex, content: &str, limit: usize) -> (Vec<SearchHit>, bool) {
+    let mut hits = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let Some(found) = matcher.find(line) else { continue };
+        if hits.len() == limit {
+            return (hits, true);
+        }
+        hits.push(SearchHit {
+            line: index + 1,
+            column: line[..found.start()].chars().count() + 1,
+            text: line.chars().take(MAX_HIT_CHARS).collect(),
+        });
+    }
+    (hits, false)
+}
+
+fn file_hits(path: String, content: &str, hits: Vec<SearchHit>, context: usize) -> FileHits {
+    let total = content.lines().count();
+
+    // Context windows around each hit, merged when at most one line separates them
+    // (a gap marker would be longer than the line it hides); likewise a single
+    // line before the first window or after the last is shown rather than omitted
+    let mut windows: Vec<(usize, usize)> = Vec::new();
+    for hit in &hits {
+        let start = match hit.line.saturating_sub(context).max(1) {
+            2 => 1,
+            start => start,
+        };
+        let end = (hit.line + context).min(total);
+        let end = if end + 1 == total { total } else { end };
+        match windows.last_mut() {
+            Some(last) if start <= last.1 + 2 => last.1 = last.1.max(end),
+            _ => windows.push((start, end)),
+        }
+    }
+
+    FileHits {
+        path,
+        lines: total,
+        content_with_line_numbers: add_line_numbers_in_windows(content, &windows),
+        hits,
+    }
+}
+
+// NOTE(jimmylee)
+// Formats search results for embedding in a prompt, one block per file.
+pub fn format_for_prompt(result: &SearchResult) -> String {
+    let mut output = format!(
+        "## Search results for `{}` ({} match(es) in {} file(s){})\n\n",
+        result.query,
+        result.matches,
+        result.files.len(),
+        if result.truncated { ", truncated" } else { "" }
+    );
+
+    for file in &result.files {
+        let ext = Path::new(&file.path).extension().and_then(|e| e.to_str()).unwrap_or("");
+        output.push_str(&format!(
+            "### {} ({} match(es), {} lines)\n\n```{}\n{}\n```\n\n",
+            file.path,
+            file.hits.len(),
+            file.lines,
+            ext,
+            file.content_with_line_numbers
+        ));
+    }
+    if result.files.is_empty() {
+        output.push_str("*No matches*\n\n");
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn setup() -> tempfile::TempDir {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("node_modules/x")).unwrap();
+        let body: String = (1..=20)
+            .map(|i| if i == 5 || i == 7 { format!("call(fetchUser) {}\n", i) } else { format!("line {}\n", i) })
+            .collect();
+        fs::write(root.join("src/app.ts"), body).unwrap();
+        fs::write(root.join("src/api.py"), "def fetch_user():\n    pass\n").unwrap();
+        fs::write(root.join("src/logo.bin"), b"fetchUser\0").unwrap();
+        fs::write(root.join("node_modules/x/index.js"), "fetchUser()\n").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_literal_search_with_context() {
+        let dir = setup();
+        let options = SearchOptions {
+            context: 1,
+            ..SearchOptions::default()
+        };
+        let result = search(dir.path(), "call(fetchUser)", &options).unwrap();
+
+        assert_eq!((result.files_searched, result.matches, result.truncated), (2, 2, false));
+        let file = &result.files[0];
+        assert_eq!(file.path, "src/app.ts");
+        assert_eq!((file.hits[1].line, file.hits[1].column), (7, 1));
+        assert_eq!(
+            file.content_with_line_numbers,
+            "... | ... (lines 1-3 omitted)\n  4 | line 4\n  5 | call(fetchUser) 5\n  6 | line 6\n  7 | call(fetchUser) 7\n  8 | line 8\n... | ... (lines 9-20 omitted)"
+        );
+    }
+
+    #[test]
+    fn test_windows_one_line_apart_merge() {
+        let content: String = (1..=12).map(|i| format!("line {}\n", i)).collect();
+        let hits = |lines: &[usize]| -> Vec<SearchHit> {
+            lines.iter().map(|&line| SearchHit { line, column: 1, text: format!("line {}", line) }).collect()
+        };
+
+        // Windows 2-4 and 6-8 leave only line 5 (and line 1) between them
+        let file = file_hits("a.txt".to_string(), &content, hits(&[3, 7]), 1);
+        assert!(!file.content_with_line_numbers.contains("(lines 1-1 omitted)"));
+        assert!(!file.content_with_line_numbers.contains("(lines 5-5 omitted)"));
+        assert!(file.content_with_line_numbers.starts_with("  1 | line 1\n"));
+        assert!(file.content_with_line_numbers.ends_with("  8 | line 8\n... | ... (lines 9-12 omitted)"));
+
+        // Two lines apart still get a marker
+        let file = file_hits("a.txt".to_string(), &content, hits(&[3, 8]), 1);
+        assert!(file.content_with_line_numbers.contains("(lines 5-6 omitted)"));
+    }
+
+    #[test]
+    fn test_regex_globs_and_limit() {
+        let dir = setup();
+        let options = SearchOptions {
+            regex: true,
+            ignore_case: true,
+            globs: vec!["*.py".to_string()],
+            ..SearchOptions::default()
+        };
+        let result = search(dir.path(), r"fetch_?user", &options).unwrap();
+        assert_eq!(result.files.len(), 1);
+        assert_eq!(result.files[0].hits[0].column, 5);
+
+        let options = SearchOptions {
+            max_results: 1,
+            ..SearchOptions::default()
+        };
+        let result = search(dir.path(), "fetchUser", &options).unwrap();
+        assert_eq!((result.matches, result.truncated), (1, true));
+
+        assert!(search(dir.path(), "(", &SearchOptions { regex: true, ..SearchOptions::default() }).is_err());
+    }
+}
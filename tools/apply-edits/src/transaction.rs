@@ -2,18 +2,75 @@
 // Transaction support for atomic edit operations.
 // Provides rollback capability when any edit fails in atomic mode.
 
-use crate::edits::Edit;
-use crate::error::{ApplyResult, EditOutcome};
+use crate::edits::{read_file, Edit};
+use crate::error::{ApplyResult, EditError, EditOutcome, EditResult, GroupOutcome};
+use crate::linemap::{apply_tracked, LineTracker};
+use crate::sanity::find_regression;
+use crate::verify::{run_plan, VerifyPlan};
+use crate::walk::expand_globs;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
+// NOTE(angeldev)
+// A file as it was before a request touched it: raw bytes (so binary files
+// survive a rename rollback) and permission bits (so chmod-ing creates roll back).
+#[derive(Debug, Clone)]
+pub struct FileBackup {
+    content: Vec<u8>,
+    permissions: std::fs::Permissions,
+}
+
+impl FileBackup {
+    // NOTE(angeldev)
+    // Captures a file's state. Returns None if it doesn't exist.
+    pub fn capture(full_path: &Path) -> Option<FileBackup> {
+        let metadata = std::fs::metadata(full_path).ok().filter(|m| m.is_file())?;
+        let content = std::fs::read(full_path).ok()?;
+
+        Some(FileBackup {
+            content,
+            permissions: metadata.permissions(),
+        })
+    }
+
+    // NOTE(angeldev)
+    // The captured file contents.
+    pub fn content(&self) -> &[u8] {
+        &self.content
+    }
+
+    // NOTE(angeldev)
+    // Writes the captured contents and permissions back.
+    pub fn restore(&self, full_path: &Path) -> std::io::Result<()> {
+        std::fs::write(full_path, &self.content)?;
+        std::fs::set_permissions(full_path, self.permissions.clone())
+    }
+}
+
+// NOTE(angeldev)
+// State at the start of an edit group, for rolling back just that group.
+struct GroupSavepoint {
+    backups: HashMap<PathBuf, Option<FileBackup>>,
+    created_dirs: Vec<PathBuf>,
+    // Files the transaction first touched inside the group
+    new_files: Vec<PathBuf>,
+    lines: LineTracker,
+}
+
 // NOTE(angeldev)
 // Manages atomic batch processing with automatic rollback on failure.
 // Backs up files before modification and restores them if any edit fails.
 pub struct EditTransaction {
     workdir: PathBuf,
-    backups: HashMap<PathBuf, Option<String>>, // None means file didn't exist (was created)
+    backups: HashMap<PathBuf, Option<FileBackup>>, // None means file didn't exist (was created)
+    // Directories missing when a new file's backup was taken, which the edit may create
+    created_dirs: Vec<PathBuf>,
     applied_files: Vec<PathBuf>,
+    lines: LineTracker,
+    // Dry-run view of paths moved by rename/copy: Some(source) is read from the
+    // source on disk, None means the file no longer exists
+    simulated: HashMap<String, Option<String>>,
+    group: Option<GroupSavepoint>,
 }
 
 impl EditTransaction {
@@ -23,23 +80,50 @@ impl EditTransaction {
         EditTransaction {
             workdir: workdir.to_path_buf(),
             backups: HashMap::new(),
+            created_dirs: Vec::new(),
             applied_files: Vec::new(),
+            lines: LineTracker::default(),
+            simulated: HashMap::new(),
+            group: None,
         }
     }
 
+    // NOTE(angeldev)
+    // Enables line-number mapping for the line-based edits in this request.
+    pub fn track_lines(&mut self, edits: &[Edit]) {
+        self.lines = LineTracker::for_edits(edits);
+    }
+
     // NOTE(angeldev)
     // Backs up a file before modification.
     // Only backs up once per file (first modification wins).
     pub fn backup_file(&mut self, rel_path: &str) {
         let full_path = self.workdir.join(rel_path);
 
+        // NOTE(angeldev): A group keeps its own copy of each file as the group first saw it
+        if let Some(group) = &mut self.group {
+            if !group.backups.contains_key(&full_path) {
+                let backup = FileBackup::capture(&full_path);
+                if backup.is_none() {
+                    group.created_dirs.extend(missing_dirs(&self.workdir, &full_path));
+                }
+                group.backups.insert(full_path.clone(), backup);
+            }
+        }
+
         // Only backup if we haven't already
         if self.backups.contains_key(&full_path) {
             return;
         }
 
         // Read current content (None if file doesn't exist)
-        let content = std::fs::read_to_string(&full_path).ok();
+        let content = FileBackup::capture(&full_path);
+        if content.is_none() {
+            self.created_dirs.extend(missing_dirs(&self.workdir, &full_path));
+        }
+        if let Some(group) = &mut self.group {
+            group.new_files.push(full_path.clone());
+        }
         self.backups.insert(full_path.clone(), content);
         self.applied_files.push(full_path);
     }
@@ -52,24 +136,108 @@ impl EditTransaction {
 
         // Backup before any modification
         if !dry_run {
-            self.backup_file(path);
+            match edit.glob_paths() {
+                // NOTE(angeldev): Back up every file the globs reach; an invalid glob fails the edit itself
+                Some(patterns) => {
+                    for file in expand_globs(&self.workdir, patterns).unwrap_or_default() {
+                        self.backup_file(&file);
+                    }
+                }
+                None => self.backup_file(path),
+            }
+            if let Some(destination) = edit.destination() {
+                self.backup_file(destination);
+            }
         }
 
         if dry_run {
             // In dry-run mode, simulate the edit without writing
-            self.simulate_edit(edit, index)
+            let outcome = self.simulate_edit(edit, index);
+            if outcome.is_success() {
+                self.simulate_move(edit);
+            }
+            outcome
         } else {
-            // Actually apply the edit
-            edit.apply(&self.workdir, index)
+            // Actually apply the edit, mapping original line numbers first
+            apply_tracked(&mut self.lines, &self.workdir, edit, index)
+        }
+    }
+
+    // NOTE(angeldev)
+    // Records a simulated rename/copy so later dry-run edits can target the new path.
+    fn simulate_move(&mut self, edit: &Edit) {
+        let (from, to, is_rename) = match edit {
+            Edit::Rename { from, to, .. } => (from, to, true),
+            Edit::Copy { from, to } => (from, to, false),
+            _ => return,
+        };
+
+        let source = match self.simulated.get(from) {
+            Some(Some(source)) => source.clone(),
+            _ => from.clone(),
+        };
+        self.simulated.insert(to.clone(), Some(source));
+        if is_rename {
+            self.simulated.insert(from.clone(), None);
+        }
+    }
+
+    // NOTE(angeldev)
+    // Reads a file as it would be after earlier simulated renames and copies.
+    fn simulated_read(&self, path: &str) -> EditResult<String> {
+        match self.simulated.get(path) {
+            Some(Some(source)) => read_file(&self.workdir, source),
+            Some(None) => Err(EditError::FileNotFound {
+                path: path.to_string(),
+            }),
+            None => read_file(&self.workdir, path),
+        }
+    }
+
+    // NOTE(angeldev)
+    // Checks existence as it would be after earlier simulated renames and copies.
+    fn simulated_exists(&self, path: &str) -> bool {
+        match self.simulated.get(path) {
+            Some(Some(source)) => self.workdir.join(source).exists(),
+            Some(None) => false,
+            None => self.workdir.join(path).exists(),
         }
     }
 
     // NOTE(angeldev)
     // Simulates an edit without writing to disk.
-    // Returns Ok if the edit WOULD succeed, Error if it would fail.
+    // Returns Ok if the edit WOULD succeed, Error if it would fail, and an
+    // already_applied warning if it would fail because its effect is in place.
     fn simulate_edit(&self, edit: &Edit, index: usize) -> EditOutcome {
-        use crate::edits::{read_file, Edit::*};
-        use crate::error::EditError;
+        use crate::edits::idempotent::{already_applied, ALREADY_APPLIED};
+
+        let outcome = self.simulate_apply(edit, index);
+        if outcome.is_success() {
+            return outcome;
+        }
+
+        let read = |p: &str| self.simulated_read(p);
+        let exists = |p: &str| self.simulated_exists(p);
+        let applied = match edit.glob_paths() {
+            Some(patterns) => edit.files_already_applied(&self.workdir, patterns),
+            None => already_applied(edit, &read, &exists),
+        };
+        match applied {
+            Some(message) => {
+                EditOutcome::warning(index, &edit.label(), edit.type_name(), ALREADY_APPLIED, &format!("{} (dry-run)", message))
+            }
+            None => outcome,
+        }
+    }
+
+    // Dry-run counterpart of Edit::apply
+    fn simulate_apply(&self, edit: &Edit, index: usize) -> EditOutcome {
+        use crate::edits::between::locate_between;
+        use crate::edits::block::locate_block;
+        use crate::edits::ensure::{ensure_import, ensure_line, Ensured};
+        use crate::edits::structured::{edit_config, KeyOp};
+        use crate::edits::symbol::{plan_rename_symbol, summarize, SymbolOptions};
+        use crate::edits::{check_expected, CreateMode, Edit::*};
         use crate::matcher::{count_occurrences, find_closest_matches, find_line_with_anchor, find_with_normalization, FindResult};
 
         let path = edit.path();
@@ -77,7 +245,7 @@ impl EditTransaction {
 
         match edit {
             Replace { search, .. } | ReplaceAll { search, .. } => {
-                match read_file(&self.workdir, path) {
+                match self.simulated_read(path) {
                     Ok(content) => {
                         let count = count_occurrences(&content, search);
                         if count > 0 {
@@ -121,7 +289,7 @@ impl EditTransaction {
             }
 
             InsertAfter { anchor, .. } | InsertBefore { anchor, .. } => {
-                match read_file(&self.workdir, path) {
+                match self.simulated_read(path) {
                     Ok(content) => {
                         if find_line_with_anchor(&content, anchor).is_some() {
                             EditOutcome::ok_with_details(
@@ -149,11 +317,14 @@ impl EditTransaction {
                 }
             }
 
-            InsertAtLine { line, .. } => {
-                match read_file(&self.workdir, path) {
+            InsertAtLine { line, expected, .. } => {
+                match self.simulated_read(path) {
                     Ok(content) => {
                         let total_lines = content.lines().count();
                         if *line > 0 && *line <= total_lines + 1 {
+                            if let Err(e) = check_expected(&content, path, *line, *line, expected.as_deref()) {
+                                return EditOutcome::from_error(index, path, edit_type, &e);
+                            }
                             EditOutcome::ok_with_details(
                                 index,
                                 path,
@@ -178,30 +349,42 @@ impl EditTransaction {
                 }
             }
 
-            Create { .. } => {
-                let full_path = self.workdir.join(path);
-                if full_path.exists() {
-                    EditOutcome::ok_with_details(
+            Create { mode, .. } => {
+                match (self.simulated_exists(path), mode) {
+                    (true, CreateMode::CreateNew) => EditOutcome::from_error(
+                        index,
+                        path,
+                        edit_type,
+                        &EditError::FileExists {
+                            path: path.to_string(),
+                        },
+                    ),
+                    (true, CreateMode::IfMissing) => EditOutcome::ok_with_details(
+                        index,
+                        path,
+                        edit_type,
+                        None,
+                        Some("Would leave existing file unchanged (dry-run)".to_string()),
+                    ),
+                    (true, CreateMode::Overwrite) => EditOutcome::ok_with_details(
                         index,
                         path,
                         edit_type,
                         None,
                         Some("Would overwrite existing file (dry-run)".to_string()),
-                    )
-                } else {
-                    EditOutcome::ok_with_details(
+                    ),
+                    (false, _) => EditOutcome::ok_with_details(
                         index,
                         path,
                         edit_type,
                         None,
                         Some("Would create new file (dry-run)".to_string()),
-                    )
+                    ),
                 }
             }
 
             DeleteFile { .. } => {
-                let full_path = self.workdir.join(path);
-                if full_path.exists() {
+                if self.simulated_exists(path) {
                     EditOutcome::ok_with_details(
                         index,
                         path,
@@ -221,17 +404,46 @@ impl EditTransaction {
                 }
             }
 
-            DeleteLines { start_line, end_line, .. } => {
-                match read_file(&self.workdir, path) {
+            Rename { to, .. } | Copy { to, .. } => {
+                let overwrite = matches!(edit, Rename { overwrite: true, .. });
+                if !self.simulated_exists(path) {
+                    EditOutcome::from_error(
+                        index,
+                        path,
+                        edit_type,
+                        &EditError::FileNotFound {
+                            path: path.to_string(),
+                        },
+                    )
+                } else if self.simulated_exists(to) && !overwrite {
+                    EditOutcome::from_error(index, path, edit_type, &EditError::FileExists { path: to.clone() })
+                } else {
+                    EditOutcome::ok_with_details(
+                        index,
+                        path,
+                        edit_type,
+                        None,
+                        Some(format!("Would {} to {} (dry-run)", edit_type, to)),
+                    )
+                }
+            }
+
+            DeleteLines { start_line, end_line, expected, .. }
+            | ReplaceLines { start_line, end_line, expected, .. } => {
+                match self.simulated_read(path) {
                     Ok(content) => {
                         let total_lines = content.lines().count();
                         if *start_line > 0 && *end_line >= *start_line && *end_line <= total_lines {
+                            if let Err(e) = check_expected(&content, path, *start_line, *end_line, expected.as_deref()) {
+                                return EditOutcome::from_error(index, path, edit_type, &e);
+                            }
+                            let verb = if matches!(edit, ReplaceLines { .. }) { "replace" } else { "delete" };
                             EditOutcome::ok_with_details(
                                 index,
                                 path,
                                 edit_type,
                                 None,
-                                Some(format!("Would delete lines {}-{} (dry-run)", start_line, end_line)),
+                                Some(format!("Would {} lines {}-{} (dry-run)", verb, start_line, end_line)),
                             )
                         } else {
                             EditOutcome::from_error(
@@ -251,8 +463,136 @@ impl EditTransaction {
                 }
             }
 
+            ReplaceBetween { start, end, inclusive, .. }
+            | DeleteBetween { start, end, inclusive, .. } => {
+                match self.simulated_read(path) {
+                    Ok(content) => match locate_between(&content, path, start, end, *inclusive) {
+                        Ok(region) => EditOutcome::ok_with_details(
+                            index,
+                            path,
+                            edit_type,
+                            None,
+                            Some(format!(
+                                "Would {} lines {}-{} between markers (dry-run)",
+                                if matches!(edit, ReplaceBetween { .. }) { "replace" } else { "delete" },
+                                region.first_line,
+                                region.last_line
+                            )),
+                        ),
+                        Err(e) => EditOutcome::from_error(index, path, edit_type, &e),
+                    },
+                    Err(e) => EditOutcome::from_error(index, path, edit_type, &e),
+                }
+            }
+
+            ReplaceBlock { header, .. } => {
+                match self.simulated_read(path) {
+                    Ok(content) => match locate_block(&content, path, header) {
+                        Ok(region) => EditOutcome::ok_with_details(
+                            index,
+                            path,
+                            edit_type,
+                            None,
+                            Some(format!(
+                                "Would replace block at lines {}-{} (dry-run)",
+                                region.first_line, region.last_line
+                            )),
+                        ),
+                        Err(e) => EditOutcome::from_error(index, path, edit_type, &e),
+                    },
+                    Err(e) => EditOutcome::from_error(index, path, edit_type, &e),
+                }
+            }
+
+            SetKey { key_path, .. } | DeleteKey { key_path, .. } | AppendToArray { key_path, .. } => {
+                let (op, verb) = match edit {
+                    SetKey { value, .. } => (KeyOp::Set(value), "set"),
+                    AppendToArray { value, .. } => (KeyOp::Append(value), "append to"),
+                    _ => (KeyOp::Delete, "delete"),
+                };
+                match self
+                    .simulated_read(path)
+                    .and_then(|content| edit_config(&content, path, key_path, op))
+                {
+                    Ok(_) => EditOutcome::ok_with_details(
+                        index,
+                        path,
+                        edit_type,
+                        None,
+                        Some(format!("Would {} {} (dry-run)", verb, key_path)),
+                    ),
+                    Err(e) => EditOutcome::from_error(index, path, edit_type, &e),
+                }
+            }
+
+            EnsureLine { .. } | EnsureImport { .. } => {
+                let ensured = self.simulated_read(path).and_then(|content| match edit {
+                    EnsureLine { line, after, section, .. } => {
+                        ensure_line(&content, path, line, after.as_deref(), section.as_deref())
+                    }
+                    EnsureImport { import, .. } => ensure_import(&content, path, import),
+                    _ => unreachable!("only ensure edits reach this arm"),
+                });
+                match ensured {
+                    Ok(Ensured::Present { line }) => EditOutcome::ok_with_details(
+                        index,
+                        path,
+                        edit_type,
+                        None,
+                        Some(format!("Already present at line {} (dry-run)", line)),
+                    ),
+                    Ok(Ensured::Inserted { line, .. }) | Ok(Ensured::Merged { line, .. }) => {
+                        EditOutcome::ok_with_details(
+                            index,
+                            path,
+                            edit_type,
+                            None,
+                            Some(format!("Would add at line {} (dry-run)", line)),
+                        )
+                    }
+                    Err(e) => EditOutcome::from_error(index, path, edit_type, &e),
+                }
+            }
+
+            RenameSymbol {
+                from,
+                to,
+                paths,
+                word_boundary,
+                skip_strings_and_comments,
+            } => {
+                let label = edit.label();
+                let options = SymbolOptions {
+                    word_boundary: *word_boundary,
+                    skip_strings_and_comments: *skip_strings_and_comments,
+                };
+                match plan_rename_symbol(&self.workdir, paths, from, to, options) {
+                    Ok(planned) if !planned.is_empty() => {
+                        let (_, files) = summarize(from, to, &planned);
+                        let total: usize = files.iter().map(|f| f.count).sum();
+                        let message = format!(
+                            "Would rename {} occurrence(s) in {} file(s) (dry-run)",
+                            total,
+                            files.len()
+                        );
+                        EditOutcome::ok_with_files(index, &label, edit_type, message, files)
+                    }
+                    Ok(_) => EditOutcome::from_error(
+                        index,
+                        &label,
+                        edit_type,
+                        &EditError::SearchNotFound {
+                            path: label.clone(),
+                            search_preview: from.clone(),
+                            closest_matches: Vec::new(),
+                        },
+                    ),
+                    Err(e) => EditOutcome::from_error(index, &label, edit_type, &e),
+                }
+            }
+
             DeleteMatch { search, .. } => {
-                match read_file(&self.workdir, path) {
+                match self.simulated_read(path) {
                     Ok(content) => {
                         let count = content.lines().filter(|l| l.contains(search)).count();
                         EditOutcome::ok_with_details(
@@ -268,7 +608,7 @@ impl EditTransaction {
             }
 
             Append { .. } | Prepend { .. } => {
-                match read_file(&self.workdir, path) {
+                match self.simulated_read(path) {
                     Ok(_) => EditOutcome::ok_with_details(
                         index,
                         path,
@@ -286,32 +626,46 @@ impl EditTransaction {
     // Rolls back all changes made during this transaction.
     // Restores files to their original state, deletes newly created files.
     pub fn rollback(self) {
-        eprintln!("🔄 Rolling back {} file(s)...", self.backups.len());
-
-        for (path, original_content) in self.backups {
-            match original_content {
-                Some(content) => {
-                    // Restore original content
-                    if let Err(e) = std::fs::write(&path, content) {
-                        eprintln!("⚠️  Failed to restore {}: {}", path.display(), e);
-                    } else {
-                        eprintln!("   Restored: {}", path.display());
-                    }
-                }
-                None => {
-                    // File was newly created - delete it
-                    if path.exists() {
-                        if let Err(e) = std::fs::remove_file(&path) {
-                            eprintln!("⚠️  Failed to remove {}: {}", path.display(), e);
-                        } else {
-                            eprintln!("   Removed: {}", path.display());
-                        }
-                    }
-                }
+        restore_backups(self.backups);
+        remove_created_dirs(self.created_dirs);
+    }
+
+    // NOTE(angeldev)
+    // Runs the syntax sanity pass on every file this transaction changed.
+    pub fn syntax_regressions(&self) -> Vec<(String, EditError)> {
+        syntax_regressions(&self.workdir, self.backups.iter().map(|(path, backup)| (path.clone(), backup.as_ref())))
+    }
+
+    // NOTE(angeldev)
+    // Starts an edit group; rollback_group undoes only what happens after this.
+    pub fn begin_group(&mut self) {
+        self.group = Some(GroupSavepoint {
+            backups: HashMap::new(),
+            created_dirs: Vec::new(),
+            new_files: Vec::new(),
+            lines: self.lines.clone(),
+        });
+    }
+
+    // NOTE(angeldev)
+    // Ends the current group, keeping its changes.
+    pub fn end_group(&mut self) {
+        self.group = None;
+    }
+
+    // NOTE(angeldev)
+    // Restores the files the current group touched to their state when it began.
+    // Files only the group touched are no longer part of the transaction.
+    pub fn rollback_group(&mut self) {
+        if let Some(group) = self.group.take() {
+            self.lines = group.lines;
+            restore_backups(group.backups);
+            remove_created_dirs(group.created_dirs);
+            for path in &group.new_files {
+                self.backups.remove(path);
             }
+            self.applied_files.retain(|path| !group.new_files.contains(path));
         }
-
-        eprintln!("✓ Rollback complete");
     }
 
     // NOTE(angeldev)
@@ -321,6 +675,118 @@ impl EditTransaction {
     }
 }
 
+// NOTE(angeldev)
+// Checks changed files against their backups (None for files the request created)
+// and returns the workdir-relative path and error of each syntax regression.
+pub fn syntax_regressions<'a>(
+    workdir: &Path,
+    files: impl IntoIterator<Item = (PathBuf, Option<&'a FileBackup>)>,
+) -> Vec<(String, EditError)> {
+    let mut found = Vec::new();
+    for (full_path, backup) in files {
+        let Ok(after) = std::fs::read_to_string(&full_path) else { continue };
+        let before = match backup.map(|b| std::str::from_utf8(b.content())) {
+            Some(Ok(before)) => Some(before),
+            Some(Err(_)) => continue,
+            None => None,
+        };
+        if before == Some(after.as_str()) {
+            continue;
+        }
+
+        let rel_path = full_path
+            .strip_prefix(workdir)
+            .unwrap_or(&full_path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        if let Some(error) = find_regression(&rel_path, before, &after) {
+            found.push((rel_path, error));
+        }
+    }
+    found.sort_by(|a, b| a.0.cmp(&b.0));
+    found
+}
+
+// NOTE(angeldev)
+// Adds an outcome for each syntax regression, against the last edit to the file.
+// Atomic mode reports errors (the caller rolls back); partial mode keeps the
+// changes and reports warnings.
+pub fn report_syntax_regressions(
+    result: &mut ApplyResult,
+    edits: &[Edit],
+    regressions: Vec<(String, EditError)>,
+    partial: bool,
+) {
+    for (path, error) in regressions {
+        let index = edits
+            .iter()
+            .rposition(|e| e.path() == path || e.destination() == Some(path.as_str()))
+            .unwrap_or(edits.len().saturating_sub(1));
+        let edit_type = edits.get(index).map(|e| e.type_name()).unwrap_or("");
+        let outcome = if partial {
+            EditOutcome::warning(index, &path, edit_type, "syntax_regression", &error.to_string())
+        } else {
+            EditOutcome::from_error(index, &path, edit_type, &error)
+        };
+        result.add_outcome(outcome);
+    }
+}
+
+// NOTE(angeldev)
+// Writes backed-up files back and removes files that didn't exist before.
+fn restore_backups(backups: HashMap<PathBuf, Option<FileBackup>>) {
+    eprintln!("🔄 Rolling back {} file(s)...", backups.len());
+
+    for (path, original_content) in backups {
+        match original_content {
+            Some(backup) => {
+                // Restore original content and permissions
+                if let Err(e) = backup.restore(&path) {
+                    eprintln!("⚠️  Failed to restore {}: {}", path.display(), e);
+                } else {
+                    eprintln!("   Restored: {}", path.display());
+                }
+            }
+            None => {
+                // File was newly created - delete it
+                if path.exists() {
+                    if let Err(e) = std::fs::remove_file(&path) {
+                        eprintln!("⚠️  Failed to remove {}: {}", path.display(), e);
+                    } else {
+                        eprintln!("   Removed: {}", path.display());
+                    }
+                }
+            }
+        }
+    }
+
+    eprintln!("✓ Rollback complete");
+}
+
+// NOTE(angeldev)
+// Ancestors of a file (below workdir) that don't exist yet, deepest first.
+pub fn missing_dirs(workdir: &Path, full_path: &Path) -> Vec<PathBuf> {
+    full_path
+        .ancestors()
+        .skip(1)
+        .take_while(|dir| *dir != workdir && dir.starts_with(workdir) && !dir.exists())
+        .map(Path::to_path_buf)
+        .collect()
+}
+
+// NOTE(angeldev)
+// Removes directories a rolled-back request created, deepest first.
+// Directories that still hold files are left alone.
+pub fn remove_created_dirs(mut dirs: Vec<PathBuf>) {
+    dirs.sort_by(|a, b| b.components().count().cmp(&a.components().count()).then_with(|| a.cmp(b)));
+    dirs.dedup();
+    for dir in dirs {
+        if std::fs::remove_dir(&dir).is_ok() {
+            eprintln!("   Removed: {}/", dir.display());
+        }
+    }
+}
+
 // NOTE(angeldev)
 // Applies edits with support for dry-run and partial/atomic modes.
 pub fn apply_with_transaction(
@@ -328,20 +794,116 @@ pub fn apply_with_transaction(
     edits: &[Edit],
     dry_run: bool,
     partial: bool,
+) -> ApplyResult {
+    apply_groups_with_transaction(workdir, edits, &[], &[], dry_run, partial, &VerifyPlan::default())
+}
+
+// NOTE(angeldev)
+// Applies edits where groups[i] is the group of edit i (None if ungrouped) and
+// names holds the group names. A group's edits must be contiguous.
+// In partial mode a failing edit rolls back only its group and skips the rest of
+// the group; in atomic mode any failure still rolls back everything.
+// Verification commands run last, once everything is written; a failing command
+// rolls back the whole transaction in either mode.
+pub fn apply_groups_with_transaction(
+    workdir: &Path,
+    edits: &[Edit],
+    groups: &[Option<usize>],
+    names: &[String],
+    dry_run: bool,
+    partial: bool,
+    verify: &VerifyPlan,
 ) -> ApplyResult {
     let mut result = ApplyResult::new();
     let mut transaction = EditTransaction::begin(workdir);
+    transaction.track_lines(edits);
+    let mut current: Option<(usize, GroupOutcome)> = None;
 
     for (index, edit) in edits.iter().enumerate() {
+        let group = groups.get(index).copied().flatten();
+        if current.as_ref().map(|(id, _)| *id) != group {
+            if let Some((_, done)) = current.take() {
+                transaction.end_group();
+                result.groups.push(done);
+            }
+            if let Some(id) = group {
+                transaction.begin_group();
+                current = Some((id, GroupOutcome::new(&names[id])));
+            }
+        }
+
+        if let Some((_, outcome)) = &mut current {
+            outcome.edits.push(index);
+            if outcome.rolled_back {
+                continue;
+            }
+        }
+
         let outcome = transaction.apply_edit(edit, index, dry_run);
         let is_success = outcome.is_success();
 
         result.add_outcome(outcome);
 
+        if let Some((_, outcome)) = &mut current {
+            outcome.success &= is_success;
+        }
+
         // In atomic mode (not partial), fail fast and rollback
         if !is_success && !partial && !dry_run {
             eprintln!();
             eprintln!("❌ Edit {} failed - triggering rollback", index + 1);
+            result.groups.extend(current.take().map(|(_, outcome)| outcome));
+            for outcome in &mut result.groups {
+                outcome.rolled_back = true;
+            }
+            transaction.rollback();
+            return result;
+        }
+
+        // In partial mode, a failed edit undoes the rest of its group
+        if !is_success && !dry_run {
+            if let Some((_, outcome)) = &mut current {
+                eprintln!();
+                eprintln!("❌ Edit {} failed - rolling back group '{}'", index + 1, outcome.name);
+                transaction.rollback_group();
+                outcome.rolled_back = true;
+                let reason = format!("Rolled back with group '{}' after edit {} failed", outcome.name, index + 1);
+                result.mark_rolled_back(&outcome.edits, &reason);
+            }
+        }
+    }
+
+    if let Some((_, done)) = current.take() {
+        result.groups.push(done);
+    }
+
+    // NOTE(angeldev): Edits that each succeeded can still leave a file unparseable
+    if !dry_run {
+        let regressions = transaction.syntax_regressions();
+        if !regressions.is_empty() {
+            report_syntax_regressions(&mut result, edits, regressions, partial);
+            if !partial {
+                eprintln!();
+                eprintln!("❌ Syntax check failed - triggering rollback");
+                for outcome in &mut result.groups {
+                    outcome.rolled_back = true;
+                }
+                transaction.rollback();
+                return result;
+            }
+        }
+    }
+
+    // NOTE(angeldev): Verification sees the edited tree; nothing is committed until it passes
+    if !dry_run && !verify.commands.is_empty() {
+        result.verify = run_plan(workdir, verify);
+        if let Some(failed) = result.verify.iter().find(|v| !v.success) {
+            eprintln!();
+            eprintln!("❌ Verification failed ({}) - triggering rollback", failed.command);
+            result.success = false;
+            for outcome in &mut result.groups {
+                outcome.rolled_back = true;
+            }
             transaction.rollback();
             return result;
         }
@@ -354,3 +916,223 @@ pub fn apply_with_transaction(
 
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn parse(json: &str) -> Vec<Edit> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_rename_rolls_back() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("old.txt"), "hello\n").unwrap();
+
+        let edits = parse(
+            r#"[
+                {"type": "rename", "from": "old.txt", "to": "lib/util/new.txt"},
+                {"type": "replace", "path": "lib/util/new.txt", "search": "missing", "replace": "x"}
+            ]"#,
+        );
+        let result = apply_with_transaction(dir.path(), &edits, false, false);
+
+        assert!(!result.success);
+        assert_eq!(fs::read_to_string(dir.path().join("old.txt")).unwrap(), "hello\n");
+        assert!(!dir.path().join("lib").exists());
+    }
+
+    #[test]
+    fn test_rollback_removes_created_dirs() {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/main.rs"), "fn main() {}\n").unwrap();
+        let missing = r#"{"type": "replace", "path": "src/main.rs", "search": "missing", "replace": "x"}"#;
+
+        // Transaction path
+        let edits = parse(&format!(
+            r#"[{{"type": "create", "path": "newdir/deep/a.txt", "content": "a\n"}},
+                {{"type": "create", "path": "src/gen/b.rs", "content": "b\n"}}, {}]"#,
+            missing
+        ));
+        assert!(!apply_with_transaction(dir.path(), &edits, false, false).success);
+
+        // Batched path (several edits to one file)
+        let edits = parse(
+            r#"[
+                {"type": "create", "path": "newdir/deep/a.txt", "content": "a\n"},
+                {"type": "replace", "path": "newdir/deep/a.txt", "search": "missing", "replace": "x"}
+            ]"#,
+        );
+        assert!(!crate::apply_edits(dir.path(), &edits).success);
+
+        // A group rolled back on its own in partial mode
+        let request: crate::EditRequest = serde_json::from_str(&format!(
+            r#"{{"groups": [{{"name": "gen", "edits": [
+                {{"type": "create", "path": "newdir/deep/a.txt", "content": "a\n"}}, {}
+            ]}}]}}"#,
+            missing
+        ))
+        .unwrap();
+        let result = crate::apply_request(dir.path(), &request, false, true, &VerifyPlan::default());
+        assert!(result.groups[0].rolled_back);
+
+        assert!(!dir.path().join("newdir").exists());
+        assert!(!dir.path().join("src/gen").exists());
+        assert_eq!(fs::read_to_string(dir.path().join("src/main.rs")).unwrap(), "fn main() {}\n");
+    }
+
+    #[test]
+    fn test_failed_group_rolls_back_alone() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("app.js"), "import a from 'a';\n\nrender(a);\n").unwrap();
+        fs::write(dir.path().join("util.js"), "export const x = 1;\n").unwrap();
+
+        let request: crate::EditRequest = serde_json::from_str(
+            r#"{"groups": [
+                {"name": "button", "edits": [
+                    {"type": "create", "path": "Button.js", "content": "export default 1;\n"},
+                    {"type": "insert_after", "path": "app.js", "anchor": "import a", "content": "import Button from './Button';"},
+                    {"type": "replace", "path": "app.js", "search": "missing", "replace": "x"},
+                    {"type": "append", "path": "app.js", "content": "never();\n"}
+                ]},
+                {"name": "util", "edits": [
+                    {"type": "replace", "path": "util.js", "search": "x = 1", "replace": "x = 2"}
+                ]}
+            ]}"#,
+        )
+        .unwrap();
+        let result = crate::apply_request(dir.path(), &request, false, true, &VerifyPlan::default());
+
+        assert!(!result.success);
+        assert_eq!(result.edits.len(), 4);
+        assert_eq!((result.applied, result.failed), (1, 3));
+        assert!(matches!(&result.edits[0], crate::EditOutcome::Error { error, .. } if error == "rolled_back"));
+        assert!(!dir.path().join("Button.js").exists());
+        assert_eq!(fs::read_to_string(dir.path().join("app.js")).unwrap(), "import a from 'a';\n\nrender(a);\n");
+        assert_eq!(fs::read_to_string(dir.path().join("util.js")).unwrap(), "export const x = 2;\n");
+
+        let groups: Vec<(&str, bool, bool)> =
+            result.groups.iter().map(|g| (g.name.as_str(), g.success, g.rolled_back)).collect();
+        assert_eq!(groups, vec![("button", false, true), ("util", true, false)]);
+        assert_eq!(result.groups[0].edits, vec![0, 1, 2, 3]);
+        assert_eq!(result.groups[1].edits, vec![4]);
+    }
+
+    #[test]
+    fn test_syntax_regression_rolls_back() {
+        let dir = tempdir().unwrap();
+        let original = "function f() {\n  return 1;\n}\n";
+        fs::write(dir.path().join("a.ts"), original).unwrap();
+
+        let edits = parse(r#"[{"type": "replace", "path": "a.ts", "search": "return 1;\n}", "replace": "return 2;"}]"#);
+        let result = apply_with_transaction(dir.path(), &edits, false, false);
+        assert!(!result.success);
+        assert!(matches!(&result.edits[1], EditOutcome::Error { error, message, .. }
+            if error == "syntax_regression" && message.contains("line 1, column 14")));
+        assert_eq!(fs::read_to_string(dir.path().join("a.ts")).unwrap(), original);
+
+        let result = apply_with_transaction(dir.path(), &edits, false, true);
+        assert!(result.success);
+        assert!(matches!(&result.edits[1], EditOutcome::Warning { warning, .. } if warning == "syntax_regression"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_failed_verify_rolls_back() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), "one\n").unwrap();
+
+        let edits = parse(r#"[{"type": "replace", "path": "a.txt", "search": "one", "replace": "two"}]"#);
+        let plan = VerifyPlan {
+            commands: vec!["grep -q two a.txt".to_string(), "grep -q one a.txt".to_string()],
+            ..VerifyPlan::default()
+        };
+        let result = apply_groups_with_transaction(dir.path(), &edits, &[], &[], false, true, &plan);
+
+        assert!(!result.success);
+        assert_eq!(result.verify.len(), 2);
+        assert!(result.verify[0].success && !result.verify[1].success);
+        assert_eq!(fs::read_to_string(dir.path().join("a.txt")).unwrap(), "one\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_rollback_restores_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let script = dir.path().join("run.sh");
+        fs::write(&script, "echo hi\n").unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o644)).unwrap();
+
+        let edits = parse(
+            r#"[
+                {"type": "create", "path": "run.sh", "content": "echo bye\n", "permissions": "700"},
+                {"type": "create", "path": "new.sh", "content": "x", "mode": "create_new"},
+                {"type": "create", "path": "run.sh", "content": "x", "mode": "create_new"}
+            ]"#,
+        );
+        let result = apply_with_transaction(dir.path(), &edits, false, false);
+
+        assert!(!result.edits[2].is_success());
+        assert_eq!(fs::read_to_string(&script).unwrap(), "echo hi\n");
+        assert_eq!(fs::metadata(&script).unwrap().permissions().mode() & 0o777, 0o644);
+        assert!(!dir.path().join("new.sh").exists());
+    }
+
+    #[test]
+    fn test_dry_run_follows_rename() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("old.txt"), "hello\n").unwrap();
+        fs::write(dir.path().join("other.txt"), "a\nb\n").unwrap();
+
+        let edits = parse(
+            r#"[
+                {"type": "rename", "from": "old.txt", "to": "new.txt"},
+                {"type": "replace", "path": "new.txt", "search": "hello", "replace": "bye"},
+                {"type": "append", "path": "old.txt", "content": "x"},
+                {"type": "delete_match", "path": "other.txt", "search": "a"}
+            ]"#,
+        );
+        let result = apply_with_transaction(dir.path(), &edits, true, true);
+
+        assert!(result.edits[0].is_success());
+        assert!(result.edits[1].is_success());
+        assert!(!result.edits[2].is_success());
+        assert!(result.edits[3].is_success());
+        assert!(dir.path().join("old.txt").exists());
+    }
+
+    #[test]
+    fn test_dry_run_checks_edits_to_the_same_file() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("settings.json"), "{\"a\": 1}\n").unwrap();
+        fs::write(dir.path().join("a.py"), "x = 1\ny = 2\n").unwrap();
+
+        // Several edits to one file would take the batched path if they weren't a dry run
+        let edits = parse(
+            r#"[
+                {"type": "replace_between", "path": "settings.json", "start": "// BEGIN", "end": "// END", "content": "x"},
+                {"type": "set_key", "path": "settings.json", "key_path": "a", "value": 2},
+                {"type": "replace_lines", "path": "a.py", "start_line": 1, "end_line": 1, "content": "x = 3", "expected": "x = 9"},
+                {"type": "ensure_line", "path": "a.py", "line": "z = 3"}
+            ]"#,
+        );
+        let result = crate::apply_edits_with_options(dir.path(), &edits, true, true);
+
+        let errors: Vec<Option<&str>> = result
+            .edits
+            .iter()
+            .map(|o| match o {
+                EditOutcome::Error { error, .. } => Some(error.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(errors, vec![Some("marker_not_found"), None, Some("expected_mismatch"), None]);
+        assert_eq!(fs::read_to_string(dir.path().join("settings.json")).unwrap(), "{\"a\": 1}\n");
+    }
+}
@@ -0,0 +1,472 @@
+// NOTE(jimmylee)
+// Repository map for the `tree` subcommand.
+// Walks the workdir, dot files included, honoring .gitignore/.ignore; skips
+// DEFAULT_EXCLUDES directories (listed as excluded, not walked), and annotates
+// each file with its line count, size and language. Directories with too many files, or deeper than --max-depth,
+// are collapsed into a one-line summary ("412 files: svg 400, png 12") so the map
+// stays readable in a prompt. Generated, vendored and binary files are flagged.
+
+use crate::language::Language;
+use crate::output::format_bytes;
+use crate::read::{looks_binary, DEFAULT_EXCLUDES};
+use crate::walk::relative_path;
+use ignore::WalkBuilder;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+// NOTE(jimmylee)
+// Files a directory may list directly before they are collapsed.
+pub const DEFAULT_COLLAPSE_FILES: usize = 40;
+
+// NOTE(jimmylee)
+// Files larger than this get a size but no line count.
+const MAX_COUNTED_BYTES: u64 = 10 * 1024 * 1024;
+
+// NOTE(jimmylee)
+// Directory names whose contents are third-party code.
+const VENDORED_DIRS: &[&str] = &["third_party", "third-party", "vendored", "external", "extern"];
+
+// NOTE(jimmylee)
+// File names and suffixes of generated files.
+const GENERATED_NAMES: &[&str] = &[
+    "package-lock.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "bun.lockb",
+    "Cargo.lock",
+    "poetry.lock",
+    "uv.lock",
+    "Gemfile.lock",
+    "composer.lock",
+    "go.sum",
+];
+const GENERATED_SUFFIXES: &[&str] = &[
+    ".min.js",
+    ".min.css",
+    ".map",
+    ".pb.go",
+    "_pb2.py",
+    ".g.dart",
+    ".freezed.dart",
+    ".designer.cs",
+    ".snap",
+];
+
+// NOTE(jimmylee)
+// Options for building the tree.
+#[derive(Debug, Clone)]
+pub struct TreeOptions {
+    // Directories this deep (root's children are depth 1) are collapsed
+    pub max_depth: Option<usize>,
+    pub collapse_files: usize,
+}
+
+impl Default for TreeOptions {
+    fn default() -> Self {
+        TreeOptions {
+            max_depth: None,
+            collapse_files: DEFAULT_COLLAPSE_FILES,
+        }
+    }
+}
+
+// NOTE(jimmylee)
+// A file or directory in the tree.
+#[derive(Debug, Serialize)]
+pub struct TreeNode {
+    pub name: String,
+    pub path: String,
+    #[serde(rename = "type")]
+    pub kind: NodeKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lines: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(skip_serializing_if = "is_false")]
+    pub generated: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    pub vendored: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    pub binary: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    pub excluded: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collapsed: Option<Collapsed>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<TreeNode>,
+}
+
+// NOTE(jimmylee)
+// Whether a node is a file or a directory.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeKind {
+    Dir,
+    File,
+}
+
+// NOTE(jimmylee)
+// Summary of files not listed individually.
+#[derive(Debug, Serialize)]
+pub struct Collapsed {
+    pub files: usize,
+    pub bytes: u64,
+    // Most common extensions first
+    pub extensions: Vec<(String, usize)>,
+}
+
+// NOTE(jimmylee)
+// The whole map with totals.
+#[derive(Debug, Serialize)]
+pub struct Tree {
+    pub files: usize,
+    pub directories: usize,
+    pub root: TreeNode,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+// Directory contents gathered from the walk
+#[derive(Default)]
+struct Dir {
+    files: Vec<(String, u64)>,
+    subdirs: BTreeSet<String>,
+    excluded: BTreeSet<String>,
+}
+
+// NOTE(jimmylee)
+// Walks workdir and builds the tree.
+pub fn build_tree(workdir: &Path, options: &TreeOptions) -> Tree {
+    let excluded = Arc::new(Mutex::new(Vec::new()));
+    let walk_excluded = Arc::clone(&excluded);
+    let root = workdir.to_path_buf();
+
+    let walker = WalkBuilder::new(workdir)
+        .require_git(false)
+        .hidden(false)
+        .filter_entry(move |entry| {
+            let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+            let name = entry.file_name().to_string_lossy();
+            let skip = is_dir && entry.depth() > 0 && DEFAULT_EXCLUDES.contains(&name.as_ref());
+            if skip && name != ".git" {
+                if let Ok(mut excluded) = walk_excluded.lock() {
+                    excluded.push(relative_path(&root, entry.path()));
+                }
+            }
+            !skip
+        })
+        .build();
+
+    let mut dirs: BTreeMap<String, Dir> = BTreeMap::new();
+    dirs.entry(String::new()).or_default();
+    for entry in walker.flatten() {
+        if entry.depth() == 0 {
+            continue;
+        }
+        let rel_path = relative_path(workdir, entry.path());
+        let (parent, name) = split_path(&rel_path);
+        if entry.file_type().is_some_and(|t| t.is_dir()) {
+            dirs.entry(parent.to_string()).or_default().subdirs.insert(name.to_string());
+            dirs.entry(rel_path).or_default();
+        } else if entry.file_type().is_some_and(|t| t.is_file()) {
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            dirs.entry(parent.to_string()).or_default().files.push((name.to_string(), size));
+        }
+    }
+    for path in excluded.lock().map(|e| e.clone()).unwrap_or_default() {
+        let (parent, name) = split_path(&path);
+        dirs.entry(parent.to_string()).or_default().excluded.insert(name.to_string());
+    }
+
+    let files = dirs.values().map(|d| d.files.len()).sum();
+    let directories = dirs.len() - 1;
+    let mut root = build_node(workdir, &dirs, "", 0, options);
+    root.name = ".".to_string();
+    Tree { files, directories, root }
+}
+
+fn split_path(rel_path: &str) -> (&str, &str) {
+    rel_path.rsplit_once('/').unwrap_or(("", rel_path))
+}
+
+fn join(parent: &str, name: &str) -> String {
+    if parent.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", parent, name)
+    }
+}
+
+fn dir_node(name: &str, path: &str) -> TreeNode {
+    TreeNode {
+        name: name.to_string(),
+        path: path.to_string(),
+        kind: NodeKind::Dir,
+        lines: None,
+        bytes: None,
+        language: None,
+        generated: false,
+        vendored: is_vendored(path),
+        binary: false,
+        excluded: false,
+        collapsed: None,
+        children: Vec::new(),
+    }
+}
+
+fn build_node(workdir: &Path, dirs: &BTreeMap<String, Dir>, path: &str, depth: usize, options: &TreeOptions) -> TreeNode {
+    // Chains of directories holding a single directory are shown as one (src/main/java)
+    let mut path = path.to_string();
+    let mut name = split_path(&path).1.to_string();
+    while let Some(dir) = dirs.get(&path).filter(|d| depth > 0 && d.files.is_empty() && d.excluded.is_empty()) {
+        let [only] = dir.subdirs.iter().collect::<Vec<_>>()[..] else { break };
+        name = format!("{}/{}", name, only);
+        path = join(&path, only);
+    }
+
+    let mut node = dir_node(&name, &path);
+    let Some(dir) = dirs.get(&path) else { return node };
+
+    if depth > 0 && options.max_depth.is_some_and(|max| depth >= max) {
+        node.collapsed = Some(summarize(dirs, &path, true));
+        return node;
+    }
+
+    for subdir in &dir.subdirs {
+        node.children.push(build_node(workdir, dirs, &join(&path, subdir), depth + 1, options));
+    }
+    for excluded in &dir.excluded {
+        let mut child = dir_node(excluded, &join(&path, excluded));
+        child.excluded = true;
+        node.children.push(child);
+    }
+
+    if dir.files.len() > options.collapse_files {
+        node.collapsed = Some(summarize(dirs, &path, false));
+    } else {
+        let mut files: Vec<&(String, u64)> = dir.files.iter().collect();
+        files.sort();
+        for (file, size) in files {
+            node.children.push(file_node(workdir, &join(&path, file), file, *size));
+        }
+    }
+    node
+}
+
+// Files directly in path (or, recursively, everything under it)
+fn summarize(dirs: &BTreeMap<String, Dir>, path: &str, recursive: bool) -> Collapsed {
+    let prefix = format!("{}/", path);
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    let mut summary = Collapsed {
+        files: 0,
+        bytes: 0,
+        extensions: Vec::new(),
+    };
+
+    let included = dirs
+        .iter()
+        .filter(|(dir, _)| *dir == path || (recursive && (path.is_empty() || dir.starts_with(&prefix))));
+    for (_, dir) in included {
+        for (name, size) in &dir.files {
+            summary.files += 1;
+            summary.bytes += size;
+            let extension = Path::new(name)
+                .extension()
+                .map(|e| e.to_string_lossy().to_lowercase())
+                .unwrap_or_else(|| "(none)".to_string());
+            *counts.entry(extension).or_default() += 1;
+        }
+    }
+
+    summary.extensions = counts.into_iter().collect();
+    summary.extensions.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    summary
+}
+
+fn file_node(workdir: &Path, path: &str, name: &str, size: u64) -> TreeNode {
+    let mut node = TreeNode {
+        kind: NodeKind::File,
+        bytes: Some(size),
+        generated: is_generated_name(name),
+        ..dir_node(name, path)
+    };
+    let language = Language::from_path(path);
+    if language != Language::Unknown {
+        node.language = Some(language.name().to_string());
+    }
+    if size > MAX_COUNTED_BYTES {
+        return node;
+    }
+
+    let Ok(bytes) = fs::read(workdir.join(path)) else { return node };
+    if looks_binary(&bytes) {
+        node.binary = true;
+        node.language = None;
+        return node;
+    }
+    let newlines = bytes.iter().filter(|b| **b == b'\n').count();
+    node.lines = Some(newlines + usize::from(!bytes.is_empty() && !bytes.ends_with(b"\n")));
+
+    // Generator banners sit in the first few lines
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(1024)]);
+    let banner = head
+        .lines()
+        .take(5)
+        .any(|l| l.contains("@generated") || l.contains("DO NOT EDIT") || l.contains("Code generated"));
+    node.generated |= banner;
+    node
+}
+
+// NOTE(jimmylee)
+// True for lockfiles and generator output, judged by file name alone.
+pub(crate) fn is_generated_name(name: &str) -> bool {
+    GENERATED_NAMES.contains(&name) || GENERATED_SUFFIXES.iter().any(|s| name.ends_with(s)) || name.contains(".generated.")
+}
+
+fn is_vendored(path: &str) -> bool {
+    path.split('/').any(|part| VENDORED_DIRS.contains(&part))
+}
+
+// NOTE(jimmylee)
+// Renders the tree as indented text, one entry per line.
+pub fn render_text(tree: &Tree) -> String {
+    let mut output = format!("./ ({} files, {} directories)\n", tree.files, tree.directories);
+    for child in &tree.root.children {
+        render_node(child, 1, &mut output);
+    }
+    if let Some(collapsed) = &tree.root.collapsed {
+        output.push_str(&format!("  {}\n", describe_collapsed(collapsed)));
+    }
+    output.pop();
+    output
+}
+
+fn render_node(node: &TreeNode, depth: usize, output: &mut String) {
+    let indent = "  ".repeat(depth);
+    let mut notes: Vec<String> = Vec::new();
+
+    match node.kind {
+        NodeKind::Dir => {
+            if node.excluded {
+                notes.push("excluded".to_string());
+            }
+            if node.vendored {
+                notes.push("vendored".to_string());
+            }
+            let notes = if notes.is_empty() { String::new() } else { format!("  [{}]", notes.join(", ")) };
+            output.push_str(&format!("{}{}/{}\n", indent, node.name, notes));
+            for child in &node.children {
+                render_node(child, depth + 1, output);
+            }
+            if let Some(collapsed) = &node.collapsed {
+                output.push_str(&format!("{}  {}\n", indent, describe_collapsed(collapsed)));
+            }
+        }
+        NodeKind::File => {
+            if let Some(lines) = node.lines {
+                notes.push(format!("{} lines", lines));
+            }
+            notes.push(format_bytes(node.bytes.unwrap_or(0) as usize));
+            notes.extend(node.language.clone());
+            for (flag, label) in [(node.generated, "generated"), (node.vendored, "vendored"), (node.binary, "binary")] {
+                if flag {
+                    notes.push(label.to_string());
+                }
+            }
+            output.push_str(&format!("{}{}  ({})\n", indent, node.name, notes.join(", ")));
+        }
+    }
+}
+
+fn describe_collapsed(collapsed: &Collapsed) -> String {
+    let extensions: Vec<String> = collapsed
+        .extensions
+        .iter()
+        .take(4)
+        .map(|(extension, count)| format!("{} {}", extension, count))
+        .collect();
+    let more = if collapsed.extensions.len() > 4 { ", ..." } else { "" };
+    format!(
+        "... {} files, {} ({}{})",
+        collapsed.files,
+        format_bytes(collapsed.bytes as usize),
+        extensions.join(", "),
+        more
+    )
+}
+
+// NOTE(jimmylee)
+// Renders the tree for embedding in a prompt.
+pub fn render_prompt(tree: &Tree) -> String {
+    format!("## Repository Structure\n\n```\n{}\n```\n", render_text(tree))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn write(root: &Path, path: &str, content: &[u8]) {
+        let full = root.join(path);
+        fs::create_dir_all(full.parent().unwrap()).unwrap();
+        fs::write(full, content).unwrap();
+    }
+
+    #[test]
+    fn test_annotates_and_collapses() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        write(root, "src/main.rs", b"fn main() {}\nmod a;\n");
+        write(root, "src/lib/deep/util.ts", b"export {}");
+        write(root, "package-lock.json", b"{}\n");
+        write(root, "logo.png", b"\x89PNG\0\0");
+        write(root, "node_modules/x/index.js", b"");
+        write(root, "ignored/skip.rs", b"");
+        write(root, ".gitignore", b"ignored/\n");
+        for i in 0..5 {
+            write(root, &format!("assets/icons/{}.svg", i), b"<svg/>");
+        }
+
+        let options = TreeOptions {
+            max_depth: None,
+            collapse_files: 3,
+        };
+        let tree = build_tree(root, &options);
+        assert_eq!((tree.files, tree.directories), (10, 5));
+
+        let text = render_text(&tree);
+        assert_eq!(
+            text,
+            "./ (10 files, 5 directories)\n  assets/icons/\n    ... 5 files, 30 B (svg 5)\n  src/\n    lib/deep/\n      util.ts  (1 lines, 9 B, typescript)\n    main.rs  (2 lines, 20 B, rust)\n  node_modules/  [excluded]\n  .gitignore  (1 lines, 9 B)\n  logo.png  (6 B, binary)\n  package-lock.json  (1 lines, 3 B, json, generated)"
+        );
+    }
+
+    #[test]
+    fn test_max_depth_and_banners() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        write(root, "a/b/c.go", b"// Code generated by protoc. DO NOT EDIT.\npackage b\n");
+        write(root, "a/b/d.go", b"package b\n");
+        write(root, "a/x.go", b"package a\n");
+
+        let tree = build_tree(root, &TreeOptions::default());
+        let c = &tree.root.children[0].children[0].children[0];
+        assert_eq!((c.name.as_str(), c.generated), ("c.go", true));
+
+        let options = TreeOptions {
+            max_depth: Some(1),
+            ..TreeOptions::default()
+        };
+        let tree = build_tree(root, &options);
+        let a = &tree.root.children[0];
+        assert!(a.children.is_empty());
+        assert_eq!(a.collapsed.as_ref().map(|c| c.files), Some(3));
+    }
+}
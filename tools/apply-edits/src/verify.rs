@@ -0,0 +1,182 @@
+// NOTE(angeldev)
+// Post-apply verification commands.
+// Runs commands such as the test/build/lint commands from detect-validation.sh in
+// the workdir after the edits are written but before the transaction commits.
+// A non-zero exit (or timeout) rolls the whole request back.
+
+use serde::Serialize;
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+// NOTE(angeldev)
+// Default time each verification command may run.
+pub const DEFAULT_TIMEOUT_SECS: u64 = 300;
+
+// NOTE(angeldev)
+// Lines of stdout/stderr kept in the result.
+const TAIL_LINES: usize = 40;
+
+// NOTE(angeldev)
+// Commands to run before committing, with a per-command timeout.
+#[derive(Debug, Clone)]
+pub struct VerifyPlan {
+    pub commands: Vec<String>,
+    pub timeout: Duration,
+}
+
+impl Default for VerifyPlan {
+    fn default() -> Self {
+        VerifyPlan {
+            commands: Vec::new(),
+            timeout: Duration::from_secs(DEFAULT_TIMEOUT_SECS),
+        }
+    }
+}
+
+// NOTE(angeldev)
+// Result of one verification command.
+#[derive(Debug, Clone, Serialize)]
+pub struct VerifyOutcome {
+    pub command: String,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    pub duration_ms: u64,
+    pub stdout_tail: String,
+    pub stderr_tail: String,
+}
+
+// NOTE(angeldev)
+// Runs the plan's commands in order, stopping at the first failure.
+pub fn run_plan(workdir: &Path, plan: &VerifyPlan) -> Vec<VerifyOutcome> {
+    let mut outcomes = Vec::new();
+    for command in &plan.commands {
+        eprintln!("🧪 Verifying: {}", command);
+        let outcome = run_command(workdir, command, plan.timeout);
+        let success = outcome.success;
+        outcomes.push(outcome);
+        if !success {
+            break;
+        }
+    }
+    outcomes
+}
+
+// NOTE(angeldev)
+// Runs one shell command in workdir, killing it after timeout.
+pub fn run_command(workdir: &Path, command: &str, timeout: Duration) -> VerifyOutcome {
+    let started = Instant::now();
+    let failed = |message: String| VerifyOutcome {
+        command: command.to_string(),
+        success: false,
+        exit_code: None,
+        timed_out: false,
+        duration_ms: started.elapsed().as_millis() as u64,
+        stdout_tail: String::new(),
+        stderr_tail: message,
+    };
+
+    let mut shell = Command::new("sh");
+    shell
+        .arg("-c")
+        .arg(command)
+        .current_dir(workdir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    // Own process group, so a timeout also stops the command's children
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut shell, 0);
+
+    let mut child = match shell.spawn() {
+        Ok(child) => child,
+        Err(e) => return failed(format!("Failed to start command: {}", e)),
+    };
+
+    // Drain both pipes on threads so a chatty command can't block on a full pipe
+    let stdout = child.stdout.take().map(drain);
+    let stderr = child.stderr.take().map(drain);
+
+    let mut timed_out = false;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break Some(status),
+            Ok(None) if started.elapsed() >= timeout => {
+                timed_out = true;
+                #[cfg(unix)]
+                let _ = Command::new("kill")
+                    .args(["-s", "KILL", "--"])
+                    .arg(format!("-{}", child.id()))
+                    .stderr(Stdio::null())
+                    .status();
+                let _ = child.kill();
+                break child.wait().ok();
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(50)),
+            Err(_) => break None,
+        }
+    };
+
+    let collect = |handle: Option<std::thread::JoinHandle<Vec<u8>>>| {
+        handle
+            .and_then(|h| h.join().ok())
+            .map(|bytes| tail(&String::from_utf8_lossy(&bytes)))
+            .unwrap_or_default()
+    };
+    let mut stderr_tail = collect(stderr);
+    if timed_out {
+        stderr_tail.push_str(&format!("\n[timed out after {}s]", timeout.as_secs()));
+    }
+
+    VerifyOutcome {
+        command: command.to_string(),
+        success: !timed_out && status.is_some_and(|s| s.success()),
+        exit_code: status.and_then(|s| s.code()),
+        timed_out,
+        duration_ms: started.elapsed().as_millis() as u64,
+        stdout_tail: collect(stdout),
+        stderr_tail,
+    }
+}
+
+fn drain<R: Read + Send + 'static>(mut pipe: R) -> std::thread::JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut buffer = Vec::new();
+        let _ = pipe.read_to_end(&mut buffer);
+        buffer
+    })
+}
+
+// Last TAIL_LINES lines of output
+fn tail(output: &str) -> String {
+    let lines: Vec<&str> = output.trim_end().lines().collect();
+    lines[lines.len().saturating_sub(TAIL_LINES)..].join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_command() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("marker"), "").unwrap();
+
+        let ok = run_command(dir.path(), "ls; seq 1 100", Duration::from_secs(10));
+        assert!(ok.success);
+        assert_eq!(ok.exit_code, Some(0));
+        assert!(ok.stdout_tail.starts_with("61\n") && ok.stdout_tail.ends_with("100"));
+
+        let failed = run_command(dir.path(), "echo boom >&2; exit 3", Duration::from_secs(10));
+        assert!(!failed.success);
+        assert_eq!((failed.exit_code, failed.stderr_tail.as_str()), (Some(3), "boom"));
+
+        let slow = run_command(dir.path(), "sleep 5", Duration::from_millis(200));
+        assert!(slow.timed_out && !slow.success);
+    }
+}
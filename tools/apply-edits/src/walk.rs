@@ -0,0 +1,181 @@
+// NOTE(angeldev)
+// Glob expansion over the working directory.
+// Walks with the `ignore` crate so .gitignore and .ignore rules apply (even outside
+// a git checkout), then matches workdir-relative paths against the globs.
+// Used by edits that span several files.
+
+use crate::error::{EditError, EditResult};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+use std::path::Path;
+
+// NOTE(angeldev)
+// Most files a single `paths` edit may expand to.
+// Keeps a too-broad glob from rewriting a whole repository by accident.
+pub const MAX_GLOB_FILES: usize = 200;
+
+// NOTE(angeldev)
+// Compiled include/exclude patterns.
+// Patterns without a slash match the file name at any depth ("*.ts" is "**/*.ts"),
+// like .gitignore; a leading ! excludes ("!src/generated/**").
+pub struct PathFilter {
+    include: GlobSet,
+    exclude: GlobSet,
+}
+
+impl PathFilter {
+    // NOTE(angeldev)
+    // Compiles the patterns, failing with InvalidEdit on a malformed glob.
+    pub fn new(patterns: &[String]) -> EditResult<PathFilter> {
+        let mut include = GlobSetBuilder::new();
+        let mut exclude = GlobSetBuilder::new();
+
+        for pattern in patterns {
+            let (builder, pattern) = match pattern.strip_prefix('!') {
+                Some(rest) => (&mut exclude, rest),
+                None => (&mut include, pattern.as_str()),
+            };
+            let pattern = pattern.trim_start_matches("./");
+            let anchored = if pattern.contains('/') {
+                pattern.trim_start_matches('/').to_string()
+            } else {
+                format!("**/{}", pattern)
+            };
+
+            let glob = GlobBuilder::new(&anchored)
+                .literal_separator(true)
+                .build()
+                .map_err(|e| EditError::InvalidEdit {
+                    reason: format!("Invalid glob '{}': {}", pattern, e),
+                })?;
+            builder.add(glob);
+        }
+
+        let build = |builder: GlobSetBuilder| {
+            builder.build().map_err(|e| EditError::InvalidEdit {
+                reason: format!("Invalid glob: {}", e),
+            })
+        };
+        Ok(PathFilter {
+            include: build(include)?,
+            exclude: build(exclude)?,
+        })
+    }
+
+    // NOTE(angeldev)
+    // Returns true if a workdir-relative path is selected.
+    pub fn matches(&self, rel_path: &str) -> bool {
+        self.include.is_match(rel_path) && !self.exclude.is_match(rel_path)
+    }
+}
+
+// NOTE(angeldev)
+// Returns true if a pattern has a segment naming a dot file or directory
+// (".github/**/*.yml", "**/.env*"). The walk only sees hidden entries for these,
+// otherwise such a pattern would silently match nothing.
+pub fn names_hidden(patterns: &[String]) -> bool {
+    patterns
+        .iter()
+        .filter(|p| !p.starts_with('!'))
+        .flat_map(|p| p.split('/'))
+        .any(|segment| segment.starts_with('.') && segment != "." && segment != "..")
+}
+
+// NOTE(angeldev)
+// Expands globs to the matching files under workdir, sorted.
+// Ignored files are skipped unless a pattern names them exactly, hidden ones
+// unless a pattern names a dot segment; .git is never walked.
+pub fn expand_globs(workdir: &Path, patterns: &[String]) -> EditResult<Vec<String>> {
+    let filter = PathFilter::new(patterns)?;
+    let mut files = Vec::new();
+
+    let walker = WalkBuilder::new(workdir)
+        .require_git(false)
+        .hidden(!names_hidden(patterns))
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .build();
+    for entry in walker.flatten() {
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        let rel_path = relative_path(workdir, entry.path());
+        if filter.matches(&rel_path) {
+            files.push(rel_path);
+        }
+    }
+
+    // Explicit file names reach files the walk skips (e.g. a gitignored .env)
+    for pattern in patterns {
+        let literal = !pattern.starts_with('!') && !pattern.contains(['*', '?', '[', '{']);
+        let rel_path = pattern.trim_start_matches("./");
+        if literal && workdir.join(rel_path).is_file() && filter.matches(rel_path) {
+            files.push(rel_path.to_string());
+        }
+    }
+
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+// Workdir-relative path with forward slashes
+pub(crate) fn relative_path(workdir: &Path, path: &Path) -> String {
+    path.strip_prefix(workdir)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn patterns(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_expand_globs_respects_gitignore() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src/lib")).unwrap();
+        fs::create_dir_all(dir.path().join("dist")).unwrap();
+        fs::write(dir.path().join(".gitignore"), "dist/\n").unwrap();
+        fs::write(dir.path().join("src/a.ts"), "").unwrap();
+        fs::write(dir.path().join("src/lib/b.ts"), "").unwrap();
+        fs::write(dir.path().join("src/c.js"), "").unwrap();
+        fs::write(dir.path().join("dist/a.ts"), "").unwrap();
+
+        let files = expand_globs(dir.path(), &patterns(&["*.ts"])).unwrap();
+        assert_eq!(files, vec!["src/a.ts", "src/lib/b.ts"]);
+
+        let files = expand_globs(dir.path(), &patterns(&["src/*", "!src/c.js"])).unwrap();
+        assert_eq!(files, vec!["src/a.ts"]);
+
+        let files = expand_globs(dir.path(), &patterns(&["dist/a.ts"])).unwrap();
+        assert_eq!(files, vec!["dist/a.ts"]);
+    }
+
+    #[test]
+    fn test_expand_globs_reaches_dot_directories() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(".github/workflows")).unwrap();
+        fs::create_dir_all(dir.path().join(".git")).unwrap();
+        fs::write(dir.path().join(".github/workflows/ci.yml"), "").unwrap();
+        fs::write(dir.path().join(".git/config.yml"), "").unwrap();
+        fs::write(dir.path().join("app.yml"), "").unwrap();
+
+        assert_eq!(expand_globs(dir.path(), &patterns(&["*.yml"])).unwrap(), vec!["app.yml"]);
+        let files = expand_globs(dir.path(), &patterns(&[".github/**/*.yml"])).unwrap();
+        assert_eq!(files, vec![".github/workflows/ci.yml"]);
+        let files = expand_globs(dir.path(), &patterns(&["**/.github/**", "*.yml"])).unwrap();
+        assert_eq!(files, vec![".github/workflows/ci.yml", "app.yml"]);
+    }
+
+    #[test]
+    fn test_invalid_glob() {
+        let err = PathFilter::new(&patterns(&["src/[a"])).err().unwrap();
+        assert!(matches!(err, EditError::InvalidEdit { .. }));
+    }
+}